@@ -3,7 +3,15 @@ use codex_sdk::{Codex, CodexOptions, ThreadOptions, TurnOptions};
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let codex = Codex::new(CodexOptions::default())?;
-    let thread = codex.start_thread(ThreadOptions::default());
+    let thread_options = match std::env::var("OLLAMA_BASE_URL") {
+        Ok(server) => ThreadOptions {
+            oss: Some(true),
+            local_model_server: Some(server),
+            ..ThreadOptions::default()
+        },
+        Err(_) => ThreadOptions::default(),
+    };
+    let thread = codex.start_thread(thread_options);
 
     let turn = thread
         .run(
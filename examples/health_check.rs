@@ -0,0 +1,21 @@
+use codex_sdk::{Codex, CodexOptions};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let codex = Codex::new(CodexOptions::default())?;
+
+    match codex.health_check().await {
+        Ok(health) => {
+            println!(
+                "codex {} at {}",
+                health.version,
+                health.executable_path.display()
+            );
+            Ok(())
+        }
+        Err(error) => {
+            eprintln!("codex is not available: {error}");
+            Err(error.into())
+        }
+    }
+}
@@ -20,7 +20,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     while let Some(event) = events.next().await {
         match event? {
             ThreadEvent::ItemCompleted { item } => println!("Item: {item:?}"),
-            ThreadEvent::TurnCompleted { usage } => println!("Usage: {usage:?}"),
+            ThreadEvent::TurnCompleted { usage, .. } => println!("Usage: {usage:?}"),
             _ => {}
         }
     }
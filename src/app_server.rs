@@ -0,0 +1,853 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_stream::try_stream;
+use serde_json::{json, Map, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStderr, ChildStdout};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::CodexError;
+use crate::events::{
+    ApprovalDecision, ApprovalKind, ItemDelta, RateLimitSnapshot, ThreadError, ThreadEvent, Usage,
+};
+use crate::exec::{CodexExec, CodexExecArgs, CodexLineStream};
+use crate::items::ThreadItem;
+use crate::thread::ApprovalResponder;
+use crate::thread_id::ThreadId;
+
+const CLIENT_NAME: &str = "codex_sdk_rs";
+const RATE_LIMITS_UPDATED: &str = "account/rateLimits/updated";
+
+type PendingApprovals = Arc<Mutex<HashMap<String, (Value, mpsc::UnboundedSender<String>)>>>;
+
+#[derive(Clone)]
+pub(crate) struct AppServer {
+    exec: CodexExec,
+    spawn_args: Arc<CodexExecArgs>,
+    process: Arc<tokio::sync::Mutex<Option<Arc<AppServerProcess>>>>,
+}
+
+impl fmt::Debug for AppServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let running = self
+            .process
+            .try_lock()
+            .map(|process| process.as_ref().is_some_and(|process| process.is_alive()))
+            .ok();
+        f.debug_struct("AppServer")
+            .field("executable_path", &self.exec.executable_path())
+            .field("running", &running)
+            .finish()
+    }
+}
+
+impl AppServer {
+    pub(crate) fn new(exec: CodexExec, spawn_args: CodexExecArgs) -> Self {
+        Self {
+            exec,
+            spawn_args: Arc::new(spawn_args),
+            process: Arc::new(tokio::sync::Mutex::new(None)),
+        }
+    }
+
+    pub(crate) fn unsupported_setting(args: &CodexExecArgs) -> Option<&'static str> {
+        #[allow(deprecated)]
+        let network_access = args.network_access_enabled.is_some();
+        let settings = [
+            (args.subcommand.is_some(), "exec subcommands"),
+            (args.resume_last, "resume_last"),
+            (
+                args.additional_directories.is_some(),
+                "additional_directories",
+            ),
+            (
+                args.output_last_message_file.is_some(),
+                "output_last_message",
+            ),
+            (args.uses_oss(), "oss"),
+            (
+                args.sandbox_workspace_write.is_some() || network_access,
+                "sandbox_workspace_write",
+            ),
+            (
+                args.shell_environment_policy.is_some(),
+                "shell_environment_policy",
+            ),
+            (
+                args.web_search_mode.is_some() || args.web_search_enabled.is_some(),
+                "web_search",
+            ),
+            (args.tools.is_some(), "tools"),
+            (
+                args.dangerously_bypass_approvals_and_sandbox
+                    .unwrap_or(false),
+                "dangerously_bypass_approvals_and_sandbox",
+            ),
+        ];
+        settings
+            .into_iter()
+            .find(|(set, _)| *set)
+            .map(|(_, setting)| setting)
+    }
+
+    pub(crate) fn run(
+        &self,
+        args: CodexExecArgs,
+    ) -> Result<(CodexLineStream, ApprovalResponder), CodexError> {
+        args.validate()?;
+        if let Some(setting) = Self::unsupported_setting(&args) {
+            return Err(CodexError::InvalidArgs(format!(
+                "{} is not supported by the app-server backend",
+                setting
+            )));
+        }
+
+        let thread_params = Self::thread_params(&args);
+        let mut turn_params = Self::turn_params(&args)?;
+        let server = self.clone();
+        let requested_thread = args.thread_id.clone();
+        let cancel = args.cancel.clone();
+        let pending_approvals = PendingApprovals::default();
+        let approvals = pending_approvals.clone();
+
+        let stream = try_stream! {
+            if cancel.as_ref().is_some_and(CancellationToken::is_cancelled) {
+                log::debug!("Execution aborted before app-server turn");
+                Err(CodexError::Aborted)?;
+            }
+
+            let process = server.process().await?;
+            let thread_id = match requested_thread {
+                Some(thread_id) if process.is_loaded(&thread_id) => thread_id,
+                Some(thread_id) => {
+                    let mut params = thread_params;
+                    params.insert("threadId".to_string(), json!(thread_id));
+                    process.request("thread/resume", Value::Object(params)).await?;
+                    log::debug!("Resumed thread {} on app-server", thread_id);
+                    thread_id
+                }
+                None => {
+                    let result = process
+                        .request("thread/start", Value::Object(thread_params))
+                        .await?;
+                    let thread_id = result
+                        .pointer("/thread/id")
+                        .and_then(Value::as_str)
+                        .ok_or_else(|| {
+                            CodexError::AppServer(format!("thread/start returned no thread id: {result}"))
+                        })?;
+                    ThreadId::from(thread_id)
+                }
+            };
+            process.mark_loaded(&thread_id);
+
+            let mut subscription = process.subscribe(&thread_id)?;
+            yield serde_json::to_string(&ThreadEvent::ThreadStarted {
+                thread_id: thread_id.clone(),
+            })?;
+
+            turn_params.insert("threadId".to_string(), json!(thread_id));
+            let result = process.request("turn/start", Value::Object(turn_params)).await?;
+            let turn_id = result.pointer("/turn/id").cloned().unwrap_or(Value::Null);
+            log::debug!("Started app-server turn {} on {}", turn_id, thread_id);
+
+            let mut interrupt = TurnInterrupt {
+                process: process.clone(),
+                params: json!({ "threadId": thread_id, "turnId": turn_id }),
+                armed: true,
+            };
+            let mut translator = NotificationTranslator::default();
+            loop {
+                let message: Result<Option<Value>, CodexError> = tokio::select! {
+                    _ = async {
+                        match &cancel {
+                            Some(token) => token.cancelled().await,
+                            None => std::future::pending::<()>().await,
+                        }
+                    } => {
+                        log::debug!("Execution aborted during app-server turn");
+                        Err(CodexError::Aborted)
+                    }
+                    message = subscription.receiver.recv() => Ok(message),
+                };
+                let Some(message) = message? else {
+                    interrupt.armed = false;
+                    Err(CodexError::AppServer("process exited during turn".to_string()))?;
+                    break;
+                };
+
+                let method = message.get("method").and_then(Value::as_str).unwrap_or_default();
+                let params = message.get("params").cloned().unwrap_or(Value::Null);
+                if let Some(request_id) = message.get("id") {
+                    match approval_request(method, &params) {
+                        Some((kind, command, paths)) => {
+                            let id = match request_id {
+                                Value::String(id) => id.clone(),
+                                other => other.to_string(),
+                            };
+                            if let Ok(mut pending) = approvals.lock() {
+                                pending.insert(id.clone(), (request_id.clone(), process.outgoing.clone()));
+                            }
+                            log::debug!("Approval {} requested for {}", id, kind);
+                            yield serde_json::to_string(&ThreadEvent::ApprovalRequested {
+                                id,
+                                kind,
+                                command,
+                                paths,
+                            })?;
+                        }
+                        None => decline_request(request_id, method, &process.outgoing),
+                    }
+                    continue;
+                }
+                if let Some(event) = translator.translate(method, &params) {
+                    let finished = matches!(
+                        event,
+                        ThreadEvent::TurnCompleted { .. } | ThreadEvent::TurnFailed { .. }
+                    );
+                    if finished {
+                        interrupt.armed = false;
+                    }
+                    yield serde_json::to_string(&event)?;
+                    if finished {
+                        break;
+                    }
+                }
+            }
+        };
+
+        let responder: ApprovalResponder = Arc::new(move |id, decision| {
+            let pending = pending_approvals
+                .lock()
+                .ok()
+                .and_then(|mut pending| pending.remove(id));
+            let Some((request_id, outgoing)) = pending else {
+                return Err(CodexError::InvalidArgs(format!(
+                    "no pending approval with id {id}"
+                )));
+            };
+            let reply = json!({
+                "id": request_id,
+                "result": { "decision": wire_decision(decision) },
+            });
+            outgoing
+                .send(format!("{reply}\n"))
+                .map_err(|_| CodexError::AppServer("process is not running".to_string()))
+        });
+
+        Ok((Box::pin(stream), responder))
+    }
+
+    async fn process(&self) -> Result<Arc<AppServerProcess>, CodexError> {
+        let mut slot = self.process.lock().await;
+        if let Some(process) = slot.as_ref() {
+            if process.is_alive() {
+                return Ok(process.clone());
+            }
+            log::warn!("codex app-server exited; restarting");
+        }
+
+        let process = Arc::new(AppServerProcess::spawn(&self.exec, &self.spawn_args)?);
+        process
+            .request(
+                "initialize",
+                json!({
+                    "clientInfo": {
+                        "name": CLIENT_NAME,
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                }),
+            )
+            .await?;
+        process.notify("initialized", json!({}));
+        log::debug!("codex app-server initialized");
+        *slot = Some(process.clone());
+        Ok(process)
+    }
+
+    fn thread_params(args: &CodexExecArgs) -> Map<String, Value> {
+        let mut params = Map::new();
+        let strings = [
+            ("model", args.model.clone()),
+            ("modelProvider", args.model_provider.clone()),
+            ("profile", args.profile.clone()),
+            ("cwd", args.working_directory.clone()),
+            (
+                "approvalPolicy",
+                Some(args.effective_approval_policy().as_str().to_string()),
+            ),
+            (
+                "sandbox",
+                args.sandbox_mode
+                    .as_ref()
+                    .map(|mode| mode.as_str().to_string()),
+            ),
+            ("baseInstructions", args.base_instructions.clone()),
+        ];
+        for (key, value) in strings {
+            if let Some(value) = value {
+                params.insert(key.to_string(), Value::String(value));
+            }
+        }
+
+        let mut config: Map<String, Value> = args
+            .config
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        if let Some(verbosity) = &args.model_verbosity {
+            config.insert(
+                "model_verbosity".to_string(),
+                Value::String(verbosity.as_str().to_string()),
+            );
+        }
+        if !config.is_empty() {
+            params.insert("config".to_string(), Value::Object(config));
+        }
+        params
+    }
+
+    fn turn_params(args: &CodexExecArgs) -> Result<Map<String, Value>, CodexError> {
+        let mut input = Vec::new();
+        if !args.input.is_empty() {
+            input.push(json!({ "type": "text", "text": args.input }));
+        }
+        for image in args.deduplicated_images().unwrap_or_default() {
+            input.push(json!({ "type": "localImage", "path": image }));
+        }
+
+        let mut params = Map::new();
+        params.insert("input".to_string(), Value::Array(input));
+        let strings = [
+            ("model", args.model.clone()),
+            ("cwd", args.working_directory.clone()),
+            (
+                "approvalPolicy",
+                Some(args.effective_approval_policy().as_str().to_string()),
+            ),
+            (
+                "effort",
+                args.model_reasoning_effort
+                    .as_ref()
+                    .map(|effort| effort.as_str().to_string()),
+            ),
+            (
+                "summary",
+                args.reasoning_summary
+                    .as_ref()
+                    .map(|summary| summary.as_str().to_string()),
+            ),
+        ];
+        for (key, value) in strings {
+            if let Some(value) = value {
+                params.insert(key.to_string(), Value::String(value));
+            }
+        }
+        if let Some(path) = &args.output_schema_file {
+            let schema: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+            params.insert("outputSchema".to_string(), schema);
+        }
+        Ok(params)
+    }
+}
+
+type PendingRequests = HashMap<u64, oneshot::Sender<Result<Value, CodexError>>>;
+
+#[derive(Default)]
+struct SharedState {
+    closed: AtomicBool,
+    pending: Mutex<PendingRequests>,
+    subscribers: Mutex<HashMap<ThreadId, mpsc::UnboundedSender<Value>>>,
+}
+
+impl SharedState {
+    fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.clear();
+        }
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.clear();
+        }
+    }
+
+    fn subscriber_for(&self, message: &Value) -> Option<mpsc::UnboundedSender<Value>> {
+        let thread_id = message
+            .pointer("/params/threadId")
+            .or_else(|| message.pointer("/params/thread/id"))
+            .and_then(Value::as_str)
+            .map(ThreadId::from)?;
+        self.subscribers
+            .lock()
+            .ok()
+            .and_then(|subscribers| subscribers.get(&thread_id).cloned())
+    }
+
+    fn dispatch(&self, line: &str, outgoing: &mpsc::UnboundedSender<String>) {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message @ Value::Object(_)) => message,
+            _ => {
+                log::warn!("Skipping non-JSON app-server line: {}", line);
+                return;
+            }
+        };
+
+        let method = message.get("method").and_then(Value::as_str);
+        match (message.get("id"), method) {
+            (Some(id), Some(method)) => match self.subscriber_for(&message) {
+                Some(subscriber) => {
+                    if subscriber.send(message.clone()).is_err() {
+                        decline_request(id, method, outgoing);
+                    }
+                }
+                None => decline_request(id, method, outgoing),
+            },
+            (Some(id), None) => {
+                let sender = id
+                    .as_u64()
+                    .and_then(|id| self.pending.lock().ok()?.remove(&id));
+                let Some(sender) = sender else {
+                    log::debug!("Ignoring app-server response for request {}", id);
+                    return;
+                };
+                let result = match message.get("error") {
+                    Some(error) => Err(CodexError::AppServer(
+                        error
+                            .get("message")
+                            .and_then(Value::as_str)
+                            .map(str::to_string)
+                            .unwrap_or_else(|| error.to_string()),
+                    )),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = sender.send(result);
+            }
+            (None, Some(method)) => match self.subscriber_for(&message) {
+                Some(subscriber) => {
+                    let _ = subscriber.send(message);
+                }
+                None if method == RATE_LIMITS_UPDATED => {
+                    if let Ok(subscribers) = self.subscribers.lock() {
+                        for subscriber in subscribers.values() {
+                            let _ = subscriber.send(message.clone());
+                        }
+                    }
+                }
+                None => log::debug!("Dropping app-server notification {}", method),
+            },
+            (None, None) => log::debug!("Ignoring app-server message: {}", line),
+        }
+    }
+}
+
+fn decline_request(id: &Value, method: &str, outgoing: &mpsc::UnboundedSender<String>) {
+    log::warn!("Declining unsupported app-server request: {}", method);
+    let reply = json!({
+        "id": id,
+        "error": {
+            "code": -32601,
+            "message": format!("{method} is not supported by {CLIENT_NAME}"),
+        },
+    });
+    let _ = outgoing.send(format!("{reply}\n"));
+}
+
+fn approval_request(
+    method: &str,
+    params: &Value,
+) -> Option<(ApprovalKind, Option<String>, Vec<String>)> {
+    let kind = match method {
+        "item/commandExecution/requestApproval" => ApprovalKind::CommandExecution,
+        "item/fileChange/requestApproval" => ApprovalKind::FileChange,
+        _ => return None,
+    };
+    let command = params
+        .get("command")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let paths = match params.get("paths") {
+        Some(Value::Array(paths)) => paths
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+    Some((kind, command, paths))
+}
+
+fn wire_decision(decision: ApprovalDecision) -> &'static str {
+    match decision {
+        ApprovalDecision::Approve => "accept",
+        ApprovalDecision::ApproveForSession => "acceptForSession",
+        ApprovalDecision::Deny => "decline",
+        ApprovalDecision::Abort => "cancel",
+    }
+}
+
+struct AppServerProcess {
+    shared: Arc<SharedState>,
+    outgoing: mpsc::UnboundedSender<String>,
+    next_id: AtomicU64,
+    loaded_threads: Mutex<HashSet<ThreadId>>,
+    tasks: Vec<JoinHandle<()>>,
+    _child: Child,
+}
+
+impl AppServerProcess {
+    fn spawn(exec: &CodexExec, args: &CodexExecArgs) -> Result<Self, CodexError> {
+        let mut child = exec.spawn_app_server(args)?;
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or(CodexError::MissingChildStream("stdin"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or(CodexError::MissingChildStream("stdout"))?;
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or(CodexError::MissingChildStream("stderr"))?;
+        log::debug!("codex app-server spawned");
+
+        let shared = Arc::new(SharedState::default());
+        let (outgoing, mut outgoing_rx) = mpsc::unbounded_channel::<String>();
+        let writer_state = shared.clone();
+        let writer = tokio::spawn(async move {
+            while let Some(line) = outgoing_rx.recv().await {
+                if let Err(error) = stdin.write_all(line.as_bytes()).await {
+                    log::warn!("Failed to write to codex app-server: {}", error);
+                    writer_state.close();
+                    break;
+                }
+            }
+        });
+        let reader = tokio::spawn(Self::read_messages(
+            stdout,
+            shared.clone(),
+            outgoing.clone(),
+        ));
+        let stderr = tokio::spawn(Self::log_stderr(stderr));
+
+        Ok(Self {
+            shared,
+            outgoing,
+            next_id: AtomicU64::new(1),
+            loaded_threads: Mutex::new(HashSet::new()),
+            tasks: vec![writer, reader, stderr],
+            _child: child,
+        })
+    }
+
+    async fn read_messages(
+        stdout: ChildStdout,
+        shared: Arc<SharedState>,
+        outgoing: mpsc::UnboundedSender<String>,
+    ) {
+        let mut lines = BufReader::new(stdout).lines();
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => shared.dispatch(&line, &outgoing),
+                Ok(None) => break,
+                Err(error) => {
+                    log::warn!("Failed to read codex app-server output: {}", error);
+                    break;
+                }
+            }
+        }
+        log::debug!("codex app-server output closed");
+        shared.close();
+    }
+
+    async fn log_stderr(stderr: ChildStderr) {
+        let mut lines = BufReader::new(stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            log::warn!("Stderr: {}", line.trim());
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        !self.shared.closed.load(Ordering::SeqCst)
+    }
+
+    fn is_loaded(&self, thread_id: &ThreadId) -> bool {
+        self.loaded_threads
+            .lock()
+            .is_ok_and(|loaded| loaded.contains(thread_id))
+    }
+
+    fn mark_loaded(&self, thread_id: &ThreadId) {
+        if let Ok(mut loaded) = self.loaded_threads.lock() {
+            loaded.insert(thread_id.clone());
+        }
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value, CodexError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        if let Ok(mut pending) = self.shared.pending.lock() {
+            pending.insert(id, sender);
+        }
+        if !self.is_alive() {
+            if let Ok(mut pending) = self.shared.pending.lock() {
+                pending.remove(&id);
+            }
+            return Err(CodexError::AppServer("process is not running".to_string()));
+        }
+
+        log::debug!("Sending app-server request {} ({})", method, id);
+        let message = json!({ "id": id, "method": method, "params": params });
+        if self.outgoing.send(format!("{message}\n")).is_err() {
+            return Err(CodexError::AppServer("process is not running".to_string()));
+        }
+        receiver.await.map_err(|_| {
+            CodexError::AppServer(format!("process exited before answering {method}"))
+        })?
+    }
+
+    fn notify(&self, method: &str, params: Value) {
+        let message = json!({ "method": method, "params": params });
+        if self.outgoing.send(format!("{message}\n")).is_err() {
+            log::debug!("Could not send {} to codex app-server", method);
+        }
+    }
+
+    fn subscribe(&self, thread_id: &ThreadId) -> Result<Subscription, CodexError> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        {
+            let mut subscribers = self
+                .shared
+                .subscribers
+                .lock()
+                .map_err(|_| CodexError::AppServer("subscriber table poisoned".to_string()))?;
+            if subscribers.contains_key(thread_id) {
+                return Err(CodexError::ThreadInUse(thread_id.to_string()));
+            }
+            subscribers.insert(thread_id.clone(), sender);
+        }
+        let subscription = Subscription {
+            shared: self.shared.clone(),
+            thread_id: thread_id.clone(),
+            receiver,
+        };
+        if !self.is_alive() {
+            return Err(CodexError::AppServer("process is not running".to_string()));
+        }
+        Ok(subscription)
+    }
+}
+
+impl Drop for AppServerProcess {
+    fn drop(&mut self) {
+        log::debug!("Shutting down codex app-server");
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+struct Subscription {
+    shared: Arc<SharedState>,
+    thread_id: ThreadId,
+    receiver: mpsc::UnboundedReceiver<Value>,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Ok(mut subscribers) = self.shared.subscribers.lock() {
+            subscribers.remove(&self.thread_id);
+        }
+    }
+}
+
+struct TurnInterrupt {
+    process: Arc<AppServerProcess>,
+    params: Value,
+    armed: bool,
+}
+
+impl Drop for TurnInterrupt {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let process = self.process.clone();
+        let params = self.params.take();
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move {
+                    match process.request("turn/interrupt", params).await {
+                        Ok(_) => log::debug!("Interrupted app-server turn"),
+                        Err(error) => log::debug!("Could not interrupt app-server turn: {}", error),
+                    }
+                });
+            }
+            Err(_) => log::warn!("No tokio runtime available; app-server turn keeps running"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct NotificationTranslator {
+    usage: Usage,
+    rate_limits: Option<RateLimitSnapshot>,
+}
+
+impl NotificationTranslator {
+    fn translate(&mut self, method: &str, params: &Value) -> Option<ThreadEvent> {
+        match method {
+            "turn/started" => Some(ThreadEvent::TurnStarted),
+            "item/started" => Some(ThreadEvent::ItemStarted {
+                item: Self::item(params)?,
+            }),
+            "item/updated" => Some(ThreadEvent::ItemUpdated {
+                item: Self::item(params)?,
+            }),
+            "item/completed" => Some(ThreadEvent::ItemCompleted {
+                item: Self::item(params)?,
+            }),
+            "item/agentMessage/delta" => Self::delta(params, ItemDelta::AgentMessageText),
+            "item/commandExecution/outputDelta" => Self::delta(params, ItemDelta::CommandOutput),
+            "thread/tokenUsage/updated" => {
+                let last = params.pointer("/tokenUsage/last")?;
+                let tokens = |key: &str| last.get(key).and_then(Value::as_u64).unwrap_or(0);
+                self.usage.input_tokens += tokens("inputTokens");
+                self.usage.cached_input_tokens += tokens("cachedInputTokens");
+                self.usage.output_tokens += tokens("outputTokens");
+                None
+            }
+            RATE_LIMITS_UPDATED => {
+                if let Some(rate_limits) = params
+                    .get("rateLimits")
+                    .and_then(RateLimitSnapshot::from_value)
+                {
+                    self.rate_limits = Some(rate_limits);
+                }
+                None
+            }
+            "turn/completed" => {
+                let status = params
+                    .pointer("/turn/status")
+                    .and_then(Value::as_str)
+                    .unwrap_or("completed");
+                match status {
+                    "completed" => Some(ThreadEvent::TurnCompleted {
+                        usage: std::mem::take(&mut self.usage),
+                        rate_limits: self.rate_limits.take(),
+                    }),
+                    _ => {
+                        let message = params
+                            .pointer("/turn/error/message")
+                            .and_then(Value::as_str)
+                            .map(str::to_string)
+                            .unwrap_or_else(|| format!("turn {status}"));
+                        Some(ThreadEvent::TurnFailed {
+                            error: ThreadError {
+                                message,
+                                will_retry: None,
+                                attempt: None,
+                            },
+                        })
+                    }
+                }
+            }
+            "error" => {
+                let message = params
+                    .pointer("/error/message")
+                    .or_else(|| params.get("message"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error")
+                    .to_string();
+                Some(ThreadEvent::ThreadErrorEvent { message })
+            }
+            _ => {
+                log::debug!("Ignoring app-server notification: {}", method);
+                None
+            }
+        }
+    }
+
+    fn delta(params: &Value, delta: fn(String) -> ItemDelta) -> Option<ThreadEvent> {
+        Some(ThreadEvent::ItemDelta {
+            item_id: params.get("itemId")?.as_str()?.to_string(),
+            delta: delta(params.get("delta")?.as_str()?.to_string()),
+        })
+    }
+
+    fn item(params: &Value) -> Option<ThreadItem> {
+        let Value::Object(item) = params.get("item")? else {
+            return None;
+        };
+        let mut item = snake_case_keys(item);
+        for key in ["type", "status"] {
+            if let Some(Value::String(value)) = item.get_mut(key) {
+                *value = to_snake_case(value);
+            }
+        }
+        for key in ["result", "error"] {
+            if let Some(Value::Object(nested)) = item.get(key) {
+                let nested = snake_case_keys(nested);
+                item.insert(key.to_string(), Value::Object(nested));
+            }
+        }
+        if let Some(Value::Array(changes)) = item.get_mut("changes") {
+            for change in changes {
+                if let Some(kind) = change.get_mut("kind") {
+                    if let Some(kind_type) = kind.get("type").cloned() {
+                        *kind = kind_type;
+                    }
+                }
+            }
+        }
+        if item.get("type").and_then(Value::as_str) == Some("reasoning")
+            && !item.contains_key("text")
+        {
+            let summary = item
+                .get("summary")
+                .and_then(Value::as_array)
+                .map(|parts| {
+                    parts
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .unwrap_or_default();
+            item.insert("text".to_string(), Value::String(summary));
+        }
+
+        match serde_json::from_value(Value::Object(item)) {
+            Ok(item) => Some(item),
+            Err(error) => {
+                log::warn!("Skipping unrecognized app-server item: {}", error);
+                None
+            }
+        }
+    }
+}
+
+fn snake_case_keys(object: &Map<String, Value>) -> Map<String, Value> {
+    object
+        .iter()
+        .map(|(key, value)| (to_snake_case(key), value.clone()))
+        .collect()
+}
+
+fn to_snake_case(value: &str) -> String {
+    let mut converted = String::with_capacity(value.len() + 4);
+    for ch in value.chars() {
+        if ch.is_ascii_uppercase() {
+            converted.push('_');
+            converted.push(ch.to_ascii_lowercase());
+        } else {
+            converted.push(ch);
+        }
+    }
+    converted
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::error::CodexError;
+
+#[derive(Clone, Debug, Default)]
+pub struct ApplyOptions {
+    pub env: Option<HashMap<String, String>>,
+    pub cancel: Option<CancellationToken>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ApplyStatus {
+    Applied,
+    Conflicted,
+}
+
+impl ApplyStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApplyStatus::Applied => "applied",
+            ApplyStatus::Conflicted => "conflicted",
+        }
+    }
+}
+
+impl fmt::Display for ApplyStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApplyOutcome {
+    pub status: ApplyStatus,
+    pub changed_files: Vec<String>,
+    pub conflicts: Vec<String>,
+    pub output: String,
+}
+
+impl ApplyOutcome {
+    pub fn is_clean(&self) -> bool {
+        self.status == ApplyStatus::Applied
+    }
+
+    pub(crate) fn parse(output: &str) -> Self {
+        let mut changed_files = Vec::new();
+        let mut conflicts = Vec::new();
+
+        for line in output.lines().map(str::trim) {
+            if let Some(path) = line
+                .strip_prefix("Applied patch to ")
+                .or_else(|| line.strip_prefix("Applied patch "))
+            {
+                if let Some(path) = path.strip_suffix(" cleanly.") {
+                    push_unique(&mut changed_files, &unquote_path(path));
+                } else if let Some(path) = path.strip_suffix(" with conflicts.") {
+                    let path = unquote_path(path);
+                    push_unique(&mut changed_files, &path);
+                    push_unique(&mut conflicts, &path);
+                }
+            } else if let Some(path) = line.strip_prefix("U ") {
+                push_unique(&mut conflicts, &unquote_path(path));
+            } else if let Some(location) = line.strip_prefix("error: patch failed: ") {
+                let path = location
+                    .rsplit_once(':')
+                    .map(|(path, _)| path)
+                    .unwrap_or(location);
+                push_unique(&mut conflicts, &unquote_path(path));
+            } else if let Some(path) = line
+                .strip_prefix("error: ")
+                .and_then(|rest| rest.strip_suffix(": patch does not apply"))
+            {
+                push_unique(&mut conflicts, &unquote_path(path));
+            }
+        }
+
+        let status = if conflicts.is_empty() {
+            ApplyStatus::Applied
+        } else {
+            ApplyStatus::Conflicted
+        };
+
+        Self {
+            status,
+            changed_files,
+            conflicts,
+            output: output.to_string(),
+        }
+    }
+
+    pub(crate) fn validate_task_id(task_id: &str) -> Result<(), CodexError> {
+        if task_id.is_empty()
+            || task_id.starts_with('-')
+            || task_id.chars().any(char::is_whitespace)
+        {
+            return Err(CodexError::InvalidArgs(format!(
+                "invalid task id {:?}",
+                task_id
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn unquote_path(path: &str) -> String {
+    if let Some(inner) = path
+        .strip_prefix('\'')
+        .and_then(|rest| rest.strip_suffix('\''))
+    {
+        return inner.to_string();
+    }
+    let Some(inner) = path
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+    else {
+        return path.to_string();
+    };
+
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(ch.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('a') => bytes.push(0x07),
+            Some('b') => bytes.push(0x08),
+            Some('f') => bytes.push(0x0c),
+            Some('n') => bytes.push(b'\n'),
+            Some('r') => bytes.push(b'\r'),
+            Some('t') => bytes.push(b'\t'),
+            Some('v') => bytes.push(0x0b),
+            Some(digit @ '0'..='7') => {
+                let mut value = digit.to_digit(8).unwrap_or(0);
+                for _ in 0..2 {
+                    match chars.peek().and_then(|next| next.to_digit(8)) {
+                        Some(next) => {
+                            value = value * 8 + next;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => {
+                let mut buffer = [0; 4];
+                bytes.extend_from_slice(other.encode_utf8(&mut buffer).as_bytes());
+            }
+            None => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn push_unique(paths: &mut Vec<String>, path: &str) {
+    if !paths.iter().any(|existing| existing == path) {
+        paths.push(path.to_string());
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuthMethod {
+    ChatGpt,
+    ApiKey,
+    Other(String),
+}
+
+impl AuthMethod {
+    pub fn as_str(&self) -> &str {
+        match self {
+            AuthMethod::ChatGpt => "chatgpt",
+            AuthMethod::ApiKey => "api_key",
+            AuthMethod::Other(method) => method,
+        }
+    }
+}
+
+impl fmt::Display for AuthMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuthStatus {
+    Authenticated {
+        method: AuthMethod,
+        plan: Option<String>,
+    },
+    NotAuthenticated,
+}
+
+impl AuthStatus {
+    pub fn is_authenticated(&self) -> bool {
+        matches!(self, AuthStatus::Authenticated { .. })
+    }
+
+    pub(crate) fn parse(output: &str) -> Option<Self> {
+        for line in output.lines().map(str::trim) {
+            if line.eq_ignore_ascii_case("not logged in") {
+                return Some(AuthStatus::NotAuthenticated);
+            }
+            let Some(rest) = line.strip_prefix("Logged in using ") else {
+                continue;
+            };
+
+            let (description, plan) = match rest.rsplit_once(" (") {
+                Some((description, suffix)) => {
+                    let plan = suffix.trim_end_matches(')');
+                    let plan = plan
+                        .strip_suffix(" plan")
+                        .or_else(|| plan.strip_prefix("plan: "))
+                        .unwrap_or(plan);
+                    (description, Some(plan.trim().to_string()))
+                }
+                None => (rest, None),
+            };
+
+            let method = if description.starts_with("ChatGPT") {
+                AuthMethod::ChatGpt
+            } else if description.starts_with("an API key") || description.starts_with("API key") {
+                AuthMethod::ApiKey
+            } else {
+                AuthMethod::Other(description.to_string())
+            };
+            return Some(AuthStatus::Authenticated { method, plan });
+        }
+        None
+    }
+}
+
+impl fmt::Display for AuthStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthStatus::Authenticated {
+                method,
+                plan: Some(plan),
+            } => write!(f, "authenticated ({method}, plan={plan})"),
+            AuthStatus::Authenticated { method, plan: None } => {
+                write!(f, "authenticated ({method})")
+            }
+            AuthStatus::NotAuthenticated => f.write_str("not authenticated"),
+        }
+    }
+}
@@ -1,13 +1,36 @@
-use crate::codex_options::CodexOptions;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "app-server")]
+use crate::app_server::AppServer;
+use crate::apply::{ApplyOptions, ApplyOutcome};
+use crate::auth::AuthStatus;
+use crate::codex_options::{CodexOptions, ExecBackend};
 use crate::error::CodexError;
 use crate::exec::CodexExec;
-use crate::thread::Thread;
+#[cfg(feature = "app-server")]
+use crate::exec::CodexExecArgs;
+use crate::mcp::McpManager;
+use crate::sessions::{self, SessionRemoval, ThreadListFilter, ThreadSummary};
+use crate::thread::{ActiveThreads, Thread};
+use crate::thread_id::ThreadId;
 use crate::thread_options::ThreadOptions;
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct HealthCheckResult {
+    pub version: String,
+    pub executable_path: PathBuf,
+}
+
 #[derive(Clone, Debug)]
 pub struct Codex {
     exec: CodexExec,
     options: CodexOptions,
+    active_threads: ActiveThreads,
+    mcp_lock: Arc<tokio::sync::Mutex<()>>,
+    #[cfg(feature = "app-server")]
+    app_server: Option<AppServer>,
 }
 
 impl Codex {
@@ -15,16 +38,244 @@ impl Codex {
         let exec = CodexExec::new(
             options.codex_path_override.clone(),
             options.env.clone(),
-            options.config.clone(),
+            options.exec_config()?,
         )?;
-        Ok(Self { exec, options })
+        let exec = match &options.codex_home {
+            Some(codex_home) => exec.with_codex_home(codex_home.clone()),
+            None => exec,
+        };
+        let exec = match options.web_search_flag_style {
+            Some(style) => exec.with_web_search_flag_style(style),
+            None => exec,
+        };
+        let exec = match options.color {
+            Some(color) => exec.with_color(color),
+            None => exec,
+        };
+        let app_server = options.backend == Some(ExecBackend::AppServer);
+        #[cfg(not(feature = "app-server"))]
+        if app_server {
+            return Err(CodexError::InvalidArgs(
+                "the app-server backend requires the `app-server` feature".to_string(),
+            ));
+        }
+        #[cfg(feature = "app-server")]
+        let app_server = app_server.then(|| {
+            AppServer::new(
+                exec.clone(),
+                CodexExecArgs {
+                    base_url: options.base_url.clone(),
+                    api_key: options.api_key.clone(),
+                    ..Default::default()
+                },
+            )
+        });
+        Ok(Self {
+            exec,
+            options,
+            active_threads: Arc::new(Mutex::new(HashMap::new())),
+            mcp_lock: Arc::new(tokio::sync::Mutex::new(())),
+            #[cfg(feature = "app-server")]
+            app_server,
+        })
     }
 
     pub fn start_thread(&self, options: ThreadOptions) -> Thread {
-        Thread::new(self.exec.clone(), self.options.clone(), options, None)
+        self.new_thread(options, None)
+    }
+
+    pub fn resume_thread(&self, id: impl Into<ThreadId>, options: ThreadOptions) -> Thread {
+        self.new_thread(options, Some(id.into()))
+    }
+
+    pub fn resume_last_thread(&self, options: ThreadOptions) -> Thread {
+        self.new_thread(options, None).resuming_last()
+    }
+
+    fn new_thread(&self, options: ThreadOptions, id: Option<ThreadId>) -> Thread {
+        let thread = Thread::new(
+            self.exec.clone(),
+            self.options.clone(),
+            options,
+            id,
+            self.active_threads.clone(),
+        );
+        #[cfg(feature = "app-server")]
+        let thread = thread.with_app_server(self.app_server.clone());
+        thread
+    }
+
+    pub async fn health_check(&self) -> Result<HealthCheckResult, CodexError> {
+        let output = match self
+            .exec
+            .run_subcommand_output(vec!["--version".to_string()])
+            .await
+        {
+            Ok(output) => output,
+            Err(error @ CodexError::ExecFailed(..)) => {
+                log::debug!("codex --version failed, trying codex version: {}", error);
+                self.exec
+                    .run_subcommand_output(vec!["version".to_string()])
+                    .await
+                    .map_err(|_| error)?
+            }
+            Err(error) => return Err(error),
+        };
+
+        let version = Self::parse_version_output(&output).ok_or_else(|| {
+            CodexError::ExecFailed("no version in output".to_string(), output.clone())
+        })?;
+        log::debug!("codex version: {}", version);
+        self.exec.record_cli_version(&version);
+        Ok(HealthCheckResult {
+            version,
+            executable_path: self.exec.resolved_executable_path(),
+        })
+    }
+
+    fn parse_version_output(output: &str) -> Option<String> {
+        output
+            .split_whitespace()
+            .map(|part| part.strip_prefix('v').unwrap_or(part))
+            .find(|part| {
+                let mut numbers = part.split(['.', '-', '+']);
+                let mut numeric = || {
+                    numbers.next().is_some_and(|number| {
+                        !number.is_empty() && number.chars().all(|ch| ch.is_ascii_digit())
+                    })
+                };
+                numeric() && numeric()
+            })
+            .map(str::to_string)
+    }
+
+    pub async fn list_thread_ids(&self) -> Result<Vec<String>, CodexError> {
+        let output = self
+            .exec
+            .run_subcommand_output(vec!["list".to_string(), "--json".to_string()])
+            .await?;
+        log::debug!("Thread list output: {}", output);
+        Ok(serde_json::from_str(&output)?)
+    }
+
+    pub async fn apply_task(
+        &self,
+        task_id: &str,
+        opts: ApplyOptions,
+    ) -> Result<ApplyOutcome, CodexError> {
+        ApplyOutcome::validate_task_id(task_id)?;
+        let (status, stdout, stderr) = self
+            .exec
+            .run_subcommand_capture(
+                vec!["apply".to_string(), task_id.to_string()],
+                opts.env,
+                opts.cancel,
+            )
+            .await?;
+
+        let output = [stdout.trim_end(), stderr.trim_end()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let outcome = ApplyOutcome::parse(&output);
+        if !status.success() {
+            if outcome.conflicts.is_empty() {
+                return Err(CodexError::ExecFailed(
+                    CodexExec::exit_detail(status),
+                    stderr,
+                ));
+            }
+            log::debug!(
+                "Apply of task {} reported conflicts: {:?}",
+                task_id,
+                outcome.conflicts
+            );
+            return Ok(outcome);
+        }
+
+        log::debug!("Applied task {}: {}", task_id, outcome.status);
+        Ok(outcome)
+    }
+
+    pub async fn auth_status(&self) -> Result<AuthStatus, CodexError> {
+        let (status, stdout, stderr) = self
+            .exec
+            .run_subcommand_capture(vec!["login".to_string(), "status".to_string()], None, None)
+            .await?;
+
+        match AuthStatus::parse(&format!("{stdout}\n{stderr}")) {
+            Some(auth_status) => {
+                log::debug!("codex auth status: {}", auth_status);
+                Ok(auth_status)
+            }
+            None if !status.success() => Err(CodexError::ExecFailed(
+                CodexExec::exit_detail(status),
+                stderr,
+            )),
+            None => Err(CodexError::ExecFailed(
+                "unrecognized login status output".to_string(),
+                format!("{stdout}{stderr}"),
+            )),
+        }
+    }
+
+    pub async fn login_with_api_key(&self, key: &str) -> Result<(), CodexError> {
+        if key.is_empty() || key.chars().any(char::is_whitespace) {
+            return Err(CodexError::InvalidArgs(
+                "api key must be non-empty and contain no whitespace".to_string(),
+            ));
+        }
+
+        let (status, _, stderr) = self
+            .exec
+            .run_subcommand_capture(
+                vec![
+                    "login".to_string(),
+                    "--api-key".to_string(),
+                    key.to_string(),
+                ],
+                None,
+                None,
+            )
+            .await?;
+        if !status.success() {
+            return Err(CodexError::ExecFailed(
+                CodexExec::exit_detail(status),
+                stderr.replace(key, "[redacted]"),
+            ));
+        }
+        log::debug!("Logged in with api key");
+        Ok(())
+    }
+
+    pub fn mcp(&self) -> McpManager {
+        McpManager::new(
+            self.exec.clone(),
+            self.mcp_lock.clone(),
+            sessions::resolve_codex_home(&self.options).ok(),
+        )
+    }
+
+    pub fn delete_thread(&self, id: &str) -> Result<(), CodexError> {
+        self.remove_thread(id, SessionRemoval::Delete)
+    }
+
+    pub fn archive_thread(&self, id: &str) -> Result<(), CodexError> {
+        self.remove_thread(id, SessionRemoval::Archive)
+    }
+
+    fn remove_thread(&self, id: &str, removal: SessionRemoval) -> Result<(), CodexError> {
+        let id: ThreadId = id.parse()?;
+        sessions::remove_session(
+            &sessions::resolve_codex_home(&self.options)?,
+            &id,
+            &self.active_threads,
+            removal,
+        )
     }
 
-    pub fn resume_thread(&self, id: String, options: ThreadOptions) -> Thread {
-        Thread::new(self.exec.clone(), self.options.clone(), options, Some(id))
+    pub fn list_threads(&self, filter: ThreadListFilter) -> Result<Vec<ThreadSummary>, CodexError> {
+        sessions::list_sessions(&sessions::resolve_codex_home(&self.options)?, &filter)
     }
 }
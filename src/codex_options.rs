@@ -2,18 +2,322 @@ use std::collections::HashMap;
 use std::fmt;
 use std::path::PathBuf;
 
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+
+use crate::error::CodexError;
+use crate::pricing::ModelPricing;
+use crate::turn_options::TurnOptions;
 
 pub type CodexConfigValue = Value;
 pub type CodexConfigObject = serde_json::Map<String, Value>;
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WireApi {
+    Chat,
+    Responses,
+}
+
+impl WireApi {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WireApi::Chat => "chat",
+            WireApi::Responses => "responses",
+        }
+    }
+}
+
+impl fmt::Display for WireApi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebSearchFlagStyle {
+    #[default]
+    Auto,
+    Flag,
+    Config,
+}
+
+impl WebSearchFlagStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebSearchFlagStyle::Auto => "auto",
+            WebSearchFlagStyle::Flag => "flag",
+            WebSearchFlagStyle::Config => "config",
+        }
+    }
+}
+
+impl fmt::Display for WebSearchFlagStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    Always,
+    Never,
+    Auto,
+}
+
+impl ColorMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColorMode::Always => "always",
+            ColorMode::Never => "never",
+            ColorMode::Auto => "auto",
+        }
+    }
+}
+
+impl fmt::Display for ColorMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExecBackend {
+    #[default]
+    Exec,
+    AppServer,
+}
+
+impl ExecBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecBackend::Exec => "exec",
+            ExecBackend::AppServer => "app-server",
+        }
+    }
+}
+
+impl fmt::Display for ExecBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HistoryPersistence {
+    SaveAll,
+    None,
+}
+
+impl HistoryPersistence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryPersistence::SaveAll => "save-all",
+            HistoryPersistence::None => "none",
+        }
+    }
+}
+
+impl fmt::Display for HistoryPersistence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModelProviderConfig {
+    pub name: String,
+    pub base_url: String,
+    pub env_key: Option<String>,
+    pub wire_api: Option<WireApi>,
+    pub query_params: Option<HashMap<String, String>>,
+}
+
+impl ModelProviderConfig {
+    fn to_config_value(&self) -> Value {
+        let mut provider = CodexConfigObject::new();
+        provider.insert("name".to_string(), Value::String(self.name.clone()));
+        provider.insert("base_url".to_string(), Value::String(self.base_url.clone()));
+        if let Some(env_key) = &self.env_key {
+            provider.insert("env_key".to_string(), Value::String(env_key.clone()));
+        }
+        if let Some(wire_api) = &self.wire_api {
+            provider.insert(
+                "wire_api".to_string(),
+                Value::String(wire_api.as_str().to_string()),
+            );
+        }
+        if let Some(query_params) = &self.query_params {
+            provider.insert(
+                "query_params".to_string(),
+                Value::Object(
+                    query_params
+                        .iter()
+                        .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+                        .collect(),
+                ),
+            );
+        }
+        Value::Object(provider)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CodexOptions {
     pub codex_path_override: Option<PathBuf>,
+    pub codex_home: Option<PathBuf>,
     pub base_url: Option<String>,
     pub api_key: Option<String>,
     pub config: Option<Value>,
     pub env: Option<HashMap<String, String>>,
+    pub default_turn_options: Option<TurnOptions>,
+    pub allow_dangerous_bypass: Option<bool>,
+    pub model_providers: Option<HashMap<String, ModelProviderConfig>>,
+    pub notify_command: Option<Vec<String>>,
+    pub history: Option<HistoryPersistence>,
+    pub web_search_flag_style: Option<WebSearchFlagStyle>,
+    pub color: Option<ColorMode>,
+    pub backend: Option<ExecBackend>,
+    pub model_pricing: Option<HashMap<String, ModelPricing>>,
+}
+
+impl CodexOptions {
+    pub fn with_env(
+        mut self,
+        vars: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> CodexOptions {
+        self.env = Some(
+            vars.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        );
+        self
+    }
+
+    pub fn add_env_var(mut self, key: impl Into<String>, value: impl Into<String>) -> CodexOptions {
+        self.env
+            .get_or_insert_with(HashMap::new)
+            .insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_model_pricing(
+        mut self,
+        model: impl Into<String>,
+        pricing: ModelPricing,
+    ) -> CodexOptions {
+        self.model_pricing
+            .get_or_insert_with(HashMap::new)
+            .insert(model.into(), pricing);
+        self
+    }
+
+    pub fn pricing_for(&self, model: &str) -> Option<ModelPricing> {
+        self.model_pricing
+            .as_ref()
+            .and_then(|pricing| pricing.get(model).copied())
+            .or_else(|| ModelPricing::for_model(model))
+    }
+
+    pub fn to_json_str(&self) -> Result<String, CodexError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json_str(s: &str) -> Result<CodexOptions, CodexError> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    pub fn merge_config(mut self, extra: Value) -> Result<CodexOptions, CodexError> {
+        if !extra.is_object() {
+            return Err(CodexError::InvalidConfigRoot);
+        }
+
+        let mut config = self
+            .config
+            .take()
+            .unwrap_or_else(|| Value::Object(CodexConfigObject::new()));
+        json_patch::merge(&mut config, &extra);
+        self.config = Some(config);
+        Ok(self)
+    }
+
+    pub(crate) fn exec_config(&self) -> Result<Option<Value>, CodexError> {
+        let mut overlay = CodexConfigObject::new();
+
+        if let Some(providers) = self.model_providers.as_ref().filter(|p| !p.is_empty()) {
+            let providers: CodexConfigObject = providers
+                .iter()
+                .map(|(id, provider)| (id.clone(), provider.to_config_value()))
+                .collect();
+            overlay.insert("model_providers".to_string(), Value::Object(providers));
+        }
+
+        if let Some(command) = &self.notify_command {
+            if command.is_empty() {
+                return Err(CodexError::InvalidArgs(
+                    "notify_command must not be empty".to_string(),
+                ));
+            }
+            overlay.insert(
+                "notify".to_string(),
+                Value::Array(command.iter().cloned().map(Value::String).collect()),
+            );
+        }
+
+        if let Some(history) = &self.history {
+            overlay.insert(
+                "history".to_string(),
+                serde_json::json!({ "persistence": history.as_str() }),
+            );
+        }
+
+        if overlay.is_empty() {
+            return Ok(self.config.clone());
+        }
+
+        let mut config = self
+            .config
+            .clone()
+            .unwrap_or_else(|| Value::Object(CodexConfigObject::new()));
+        if config.is_object() {
+            json_patch::merge(&mut config, &Value::Object(overlay));
+        }
+        Ok(Some(config))
+    }
+
+    pub fn apply_patch(self, patch: CodexOptions) -> CodexOptions {
+        let config = match (self.config, patch.config) {
+            (base, Some(patch)) => {
+                let mut base = base.unwrap_or_else(|| Value::Object(CodexConfigObject::new()));
+                json_patch::merge(&mut base, &patch);
+                Some(base)
+            }
+            (base, None) => base,
+        };
+
+        CodexOptions {
+            codex_path_override: patch.codex_path_override.or(self.codex_path_override),
+            codex_home: patch.codex_home.or(self.codex_home),
+            base_url: patch.base_url.or(self.base_url),
+            api_key: patch.api_key.or(self.api_key),
+            config,
+            env: patch.env.or(self.env),
+            default_turn_options: patch.default_turn_options.or(self.default_turn_options),
+            allow_dangerous_bypass: patch.allow_dangerous_bypass.or(self.allow_dangerous_bypass),
+            model_providers: patch.model_providers.or(self.model_providers),
+            notify_command: patch.notify_command.or(self.notify_command),
+            history: patch.history.or(self.history),
+            web_search_flag_style: patch.web_search_flag_style.or(self.web_search_flag_style),
+            color: patch.color.or(self.color),
+            backend: patch.backend.or(self.backend),
+            model_pricing: patch.model_pricing.or(self.model_pricing),
+        }
+    }
 }
 
 impl fmt::Display for CodexOptions {
@@ -37,11 +341,42 @@ impl fmt::Display for CodexOptions {
                 format!("Some(keys={keys:?})")
             })
             .unwrap_or_else(|| "None".to_string());
+        let default_turn_options = self
+            .default_turn_options
+            .as_ref()
+            .map(|options| format!("Some({options})"))
+            .unwrap_or_else(|| "None".to_string());
+
+        let model_providers = self
+            .model_providers
+            .as_ref()
+            .map(|providers| {
+                let mut ids: Vec<&str> = providers.keys().map(String::as_str).collect();
+                ids.sort_unstable();
+                format!("Some(ids={ids:?})")
+            })
+            .unwrap_or_else(|| "None".to_string());
+
+        let history = self
+            .history
+            .as_ref()
+            .map(|history| format!("Some({history})"))
+            .unwrap_or_else(|| "None".to_string());
+
+        let model_pricing = self
+            .model_pricing
+            .as_ref()
+            .map(|pricing| {
+                let mut models: Vec<&str> = pricing.keys().map(String::as_str).collect();
+                models.sort_unstable();
+                format!("Some(models={models:?})")
+            })
+            .unwrap_or_else(|| "None".to_string());
 
         write!(
             f,
-            "CodexOptions {{ codex_path_override: {:?}, base_url: {:?}, api_key: {}, config: {}, env: {} }}",
-            self.codex_path_override, self.base_url, api_key, config, env
+            "CodexOptions {{ codex_path_override: {:?}, codex_home: {:?}, base_url: {:?}, api_key: {}, config: {}, env: {}, default_turn_options: {}, allow_dangerous_bypass: {:?}, model_providers: {}, notify_command: {:?}, history: {}, web_search_flag_style: {:?}, color: {:?}, backend: {:?}, model_pricing: {} }}",
+            self.codex_path_override, self.codex_home, self.base_url, api_key, config, env, default_turn_options, self.allow_dangerous_bypass, model_providers, self.notify_command, history, self.web_search_flag_style, self.color, self.backend, model_pricing
         )
     }
 }
@@ -1,5 +1,32 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetKind {
+    OutputItems,
+    Tokens,
+}
+
+impl BudgetKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetKind::OutputItems => "output_items",
+            BudgetKind::Tokens => "tokens",
+        }
+    }
+}
+
+impl fmt::Display for BudgetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum CodexError {
     #[error("unsupported platform: {0} ({1})")]
@@ -14,20 +41,429 @@ pub enum CodexError {
     InvalidConfigNull(String),
     #[error("unsupported codex config override value at {0}: {1}")]
     InvalidConfigValue(String, String),
-    #[error("output schema must be a plain JSON object")]
-    InvalidOutputSchema,
+    #[error("invalid output schema: {0}")]
+    InvalidOutputSchema(String),
+    #[error("output does not match schema: {}", errors.join("; "))]
+    OutputSchemaViolation { errors: Vec<String>, raw: String },
     #[error("failed to parse event: {0}")]
     InvalidEvent(String),
+    #[error("codex output is not valid UTF-8: {0}")]
+    InvalidUtf8(String),
     #[error("codex exec exited with {0}: {1}")]
     ExecFailed(String, String),
     #[error("codex exec aborted")]
     Aborted,
+    #[error("timed out waiting for codex output")]
+    Timeout,
+    #[error("invalid prompt template: {0}")]
+    InvalidTemplate(String),
+    #[error("missing prompt template variable: {0}")]
+    MissingTemplateVariable(String),
+    #[error("invalid exec args: {0}")]
+    InvalidArgs(String),
+    #[error(
+        "dangerously_bypass_approvals_and_sandbox requires CodexOptions::allow_dangerous_bypass"
+    )]
+    DangerousBypassNotAllowed,
+    #[error("input too large: {0}")]
+    InputTooLarge(String),
+    #[error("input file {0} cannot be embedded: {1}")]
+    InvalidInputFile(String, String),
+    #[error("image not found: {0}")]
+    ImageNotFound(PathBuf),
+    #[error("failed to download image {0}: {1}")]
+    ImageDownload(String, String),
+    #[error("invalid thread id: {0}")]
+    InvalidThreadId(String),
+    #[error("thread not found: {0}")]
+    ThreadNotFound(String),
+    #[error("thread is running: {0}")]
+    ThreadInUse(String),
+    #[error("a turn is already running on this thread")]
+    ConcurrentTurnNotAllowed,
     #[error("turn failed: {0}")]
     TurnFailed(String),
     #[error("child process missing {0}")]
     MissingChildStream(&'static str),
+    #[error("codex app-server error: {0}")]
+    AppServer(String),
+    #[error("interactive approvals need the app-server backend; codex exec cannot answer them")]
+    ApprovalsUnsupported,
+    #[error("{kind} budget exceeded: observed {observed}, limit {limit}")]
+    BudgetExceeded {
+        kind: BudgetKind,
+        limit: u64,
+        observed: u64,
+    },
+    #[error("{context}")]
+    Contextual {
+        context: String,
+        #[source]
+        source: Box<CodexError>,
+    },
     #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error(transparent)]
     Json(#[from] serde_json::Error),
 }
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum CodexErrorRepr {
+    UnsupportedPlatform {
+        platform: String,
+        arch: String,
+    },
+    InvalidConfigRoot,
+    InvalidConfigKey,
+    InvalidConfigNumber {
+        path: String,
+    },
+    InvalidConfigNull {
+        path: String,
+    },
+    InvalidConfigValue {
+        path: String,
+        value: String,
+    },
+    InvalidOutputSchema {
+        reason: String,
+    },
+    OutputSchemaViolation {
+        errors: Vec<String>,
+        raw: String,
+    },
+    InvalidEvent {
+        line: String,
+    },
+    InvalidUtf8 {
+        reason: String,
+    },
+    ExecFailed {
+        status: String,
+        stderr: String,
+    },
+    Aborted,
+    Timeout,
+    InvalidTemplate {
+        reason: String,
+    },
+    MissingTemplateVariable {
+        name: String,
+    },
+    InvalidArgs {
+        reason: String,
+    },
+    DangerousBypassNotAllowed,
+    InputTooLarge {
+        reason: String,
+    },
+    InvalidInputFile {
+        path: String,
+        reason: String,
+    },
+    ImageNotFound {
+        path: PathBuf,
+    },
+    ImageDownload {
+        url: String,
+        reason: String,
+    },
+    InvalidThreadId {
+        reason: String,
+    },
+    ThreadNotFound {
+        id: String,
+    },
+    ThreadInUse {
+        id: String,
+    },
+    ConcurrentTurnNotAllowed,
+    TurnFailed {
+        reason: String,
+    },
+    MissingChildStream {
+        stream: String,
+    },
+    AppServer {
+        reason: String,
+    },
+    ApprovalsUnsupported,
+    BudgetExceeded {
+        budget: BudgetKind,
+        limit: u64,
+        observed: u64,
+    },
+    Contextual {
+        context: String,
+        source: Box<CodexError>,
+    },
+    Io,
+    Json,
+}
+
+#[derive(Serialize)]
+struct ContextualWire<'a> {
+    kind: &'static str,
+    context: &'a str,
+    source: &'a CodexError,
+    message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CodexErrorWire {
+    #[serde(flatten)]
+    error: CodexErrorRepr,
+    #[serde(default)]
+    message: String,
+}
+
+impl CodexError {
+    pub fn context(self, context: impl Into<String>) -> CodexError {
+        CodexError::Contextual {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            CodexError::UnsupportedPlatform(..) => "unsupported platform",
+            CodexError::InvalidConfigRoot => "codex config overrides must be a plain object",
+            CodexError::InvalidConfigKey => "codex config override keys must be non-empty strings",
+            CodexError::InvalidConfigNumber(_) => "codex config override must be a finite number",
+            CodexError::InvalidConfigNull(_) => "codex config override cannot be null",
+            CodexError::InvalidConfigValue(..) => "unsupported codex config override value",
+            CodexError::OutputSchemaViolation { errors, .. } => errors
+                .first()
+                .map(String::as_str)
+                .unwrap_or("output does not match schema"),
+            CodexError::InvalidEvent(_) => "failed to parse event",
+            CodexError::ExecFailed(_, stderr) if !stderr.trim().is_empty() => stderr.trim(),
+            CodexError::ExecFailed(..) => "codex exec failed",
+            CodexError::Aborted => "codex exec aborted",
+            CodexError::Timeout => "timed out waiting for codex output",
+            CodexError::MissingTemplateVariable(_) => "missing prompt template variable",
+            CodexError::DangerousBypassNotAllowed => {
+                "dangerously_bypass_approvals_and_sandbox requires CodexOptions::allow_dangerous_bypass"
+            }
+            CodexError::ImageNotFound(_) => "image not found",
+            CodexError::ThreadNotFound(_) => "thread not found",
+            CodexError::ThreadInUse(_) => "thread is running",
+            CodexError::ConcurrentTurnNotAllowed => "a turn is already running on this thread",
+            CodexError::MissingChildStream(_) => "child process missing stream",
+            CodexError::ApprovalsUnsupported => {
+                "interactive approvals need the app-server backend; codex exec cannot answer them"
+            }
+            CodexError::InvalidOutputSchema(message)
+            | CodexError::InvalidUtf8(message)
+            | CodexError::InvalidTemplate(message)
+            | CodexError::InvalidArgs(message)
+            | CodexError::InputTooLarge(message)
+            | CodexError::InvalidInputFile(_, message)
+            | CodexError::ImageDownload(_, message)
+            | CodexError::InvalidThreadId(message)
+            | CodexError::TurnFailed(message)
+            | CodexError::AppServer(message)
+            | CodexError::Contextual {
+                context: message, ..
+            } => message,
+            CodexError::BudgetExceeded { .. } => "budget exceeded",
+            CodexError::Io(_) => "I/O error",
+            CodexError::Json(_) => "JSON error",
+        }
+    }
+
+    pub fn message_cow(&self) -> Cow<'_, str> {
+        match self {
+            CodexError::InvalidConfigRoot
+            | CodexError::InvalidConfigKey
+            | CodexError::Aborted
+            | CodexError::Timeout
+            | CodexError::DangerousBypassNotAllowed
+            | CodexError::ConcurrentTurnNotAllowed
+            | CodexError::ApprovalsUnsupported
+            | CodexError::Contextual { .. } => Cow::Borrowed(self.message()),
+            _ => Cow::Owned(self.to_string()),
+        }
+    }
+}
+
+impl Serialize for CodexError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let error = match self {
+            CodexError::UnsupportedPlatform(platform, arch) => {
+                CodexErrorRepr::UnsupportedPlatform {
+                    platform: platform.clone(),
+                    arch: arch.clone(),
+                }
+            }
+            CodexError::InvalidConfigRoot => CodexErrorRepr::InvalidConfigRoot,
+            CodexError::InvalidConfigKey => CodexErrorRepr::InvalidConfigKey,
+            CodexError::InvalidConfigNumber(path) => {
+                CodexErrorRepr::InvalidConfigNumber { path: path.clone() }
+            }
+            CodexError::InvalidConfigNull(path) => {
+                CodexErrorRepr::InvalidConfigNull { path: path.clone() }
+            }
+            CodexError::InvalidConfigValue(path, value) => CodexErrorRepr::InvalidConfigValue {
+                path: path.clone(),
+                value: value.clone(),
+            },
+            CodexError::InvalidOutputSchema(reason) => CodexErrorRepr::InvalidOutputSchema {
+                reason: reason.clone(),
+            },
+            CodexError::OutputSchemaViolation { errors, raw } => {
+                CodexErrorRepr::OutputSchemaViolation {
+                    errors: errors.clone(),
+                    raw: raw.clone(),
+                }
+            }
+            CodexError::InvalidEvent(line) => CodexErrorRepr::InvalidEvent { line: line.clone() },
+            CodexError::InvalidUtf8(reason) => CodexErrorRepr::InvalidUtf8 {
+                reason: reason.clone(),
+            },
+            CodexError::ExecFailed(status, stderr) => CodexErrorRepr::ExecFailed {
+                status: status.clone(),
+                stderr: stderr.clone(),
+            },
+            CodexError::Aborted => CodexErrorRepr::Aborted,
+            CodexError::Timeout => CodexErrorRepr::Timeout,
+            CodexError::InvalidTemplate(reason) => CodexErrorRepr::InvalidTemplate {
+                reason: reason.clone(),
+            },
+            CodexError::MissingTemplateVariable(name) => {
+                CodexErrorRepr::MissingTemplateVariable { name: name.clone() }
+            }
+            CodexError::InvalidArgs(reason) => CodexErrorRepr::InvalidArgs {
+                reason: reason.clone(),
+            },
+            CodexError::DangerousBypassNotAllowed => CodexErrorRepr::DangerousBypassNotAllowed,
+            CodexError::InputTooLarge(reason) => CodexErrorRepr::InputTooLarge {
+                reason: reason.clone(),
+            },
+            CodexError::InvalidInputFile(path, reason) => CodexErrorRepr::InvalidInputFile {
+                path: path.clone(),
+                reason: reason.clone(),
+            },
+            CodexError::ImageNotFound(path) => CodexErrorRepr::ImageNotFound { path: path.clone() },
+            CodexError::ImageDownload(url, reason) => CodexErrorRepr::ImageDownload {
+                url: url.clone(),
+                reason: reason.clone(),
+            },
+            CodexError::InvalidThreadId(reason) => CodexErrorRepr::InvalidThreadId {
+                reason: reason.clone(),
+            },
+            CodexError::ThreadNotFound(id) => CodexErrorRepr::ThreadNotFound { id: id.clone() },
+            CodexError::ThreadInUse(id) => CodexErrorRepr::ThreadInUse { id: id.clone() },
+            CodexError::ConcurrentTurnNotAllowed => CodexErrorRepr::ConcurrentTurnNotAllowed,
+            CodexError::TurnFailed(reason) => CodexErrorRepr::TurnFailed {
+                reason: reason.clone(),
+            },
+            CodexError::MissingChildStream(stream) => CodexErrorRepr::MissingChildStream {
+                stream: stream.to_string(),
+            },
+            CodexError::AppServer(reason) => CodexErrorRepr::AppServer {
+                reason: reason.clone(),
+            },
+            CodexError::ApprovalsUnsupported => CodexErrorRepr::ApprovalsUnsupported,
+            CodexError::BudgetExceeded {
+                kind,
+                limit,
+                observed,
+            } => CodexErrorRepr::BudgetExceeded {
+                budget: *kind,
+                limit: *limit,
+                observed: *observed,
+            },
+            CodexError::Contextual { context, source } => {
+                return ContextualWire {
+                    kind: "contextual",
+                    context,
+                    source,
+                    message: self.to_string(),
+                }
+                .serialize(serializer);
+            }
+            CodexError::Io(_) => CodexErrorRepr::Io,
+            CodexError::Json(_) => CodexErrorRepr::Json,
+        };
+
+        CodexErrorWire {
+            error,
+            message: self.to_string(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CodexError {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let CodexErrorWire { error, message } = CodexErrorWire::deserialize(deserializer)?;
+
+        Ok(match error {
+            CodexErrorRepr::UnsupportedPlatform { platform, arch } => {
+                CodexError::UnsupportedPlatform(platform, arch)
+            }
+            CodexErrorRepr::InvalidConfigRoot => CodexError::InvalidConfigRoot,
+            CodexErrorRepr::InvalidConfigKey => CodexError::InvalidConfigKey,
+            CodexErrorRepr::InvalidConfigNumber { path } => CodexError::InvalidConfigNumber(path),
+            CodexErrorRepr::InvalidConfigNull { path } => CodexError::InvalidConfigNull(path),
+            CodexErrorRepr::InvalidConfigValue { path, value } => {
+                CodexError::InvalidConfigValue(path, value)
+            }
+            CodexErrorRepr::InvalidOutputSchema { reason } => {
+                CodexError::InvalidOutputSchema(reason)
+            }
+            CodexErrorRepr::OutputSchemaViolation { errors, raw } => {
+                CodexError::OutputSchemaViolation { errors, raw }
+            }
+            CodexErrorRepr::InvalidEvent { line } => CodexError::InvalidEvent(line),
+            CodexErrorRepr::InvalidUtf8 { reason } => CodexError::InvalidUtf8(reason),
+            CodexErrorRepr::ExecFailed { status, stderr } => CodexError::ExecFailed(status, stderr),
+            CodexErrorRepr::Aborted => CodexError::Aborted,
+            CodexErrorRepr::Timeout => CodexError::Timeout,
+            CodexErrorRepr::InvalidTemplate { reason } => CodexError::InvalidTemplate(reason),
+            CodexErrorRepr::MissingTemplateVariable { name } => {
+                CodexError::MissingTemplateVariable(name)
+            }
+            CodexErrorRepr::InvalidArgs { reason } => CodexError::InvalidArgs(reason),
+            CodexErrorRepr::DangerousBypassNotAllowed => CodexError::DangerousBypassNotAllowed,
+            CodexErrorRepr::InputTooLarge { reason } => CodexError::InputTooLarge(reason),
+            CodexErrorRepr::InvalidInputFile { path, reason } => {
+                CodexError::InvalidInputFile(path, reason)
+            }
+            CodexErrorRepr::ImageNotFound { path } => CodexError::ImageNotFound(path),
+            CodexErrorRepr::ImageDownload { url, reason } => CodexError::ImageDownload(url, reason),
+            CodexErrorRepr::InvalidThreadId { reason } => CodexError::InvalidThreadId(reason),
+            CodexErrorRepr::ThreadNotFound { id } => CodexError::ThreadNotFound(id),
+            CodexErrorRepr::ThreadInUse { id } => CodexError::ThreadInUse(id),
+            CodexErrorRepr::ConcurrentTurnNotAllowed => CodexError::ConcurrentTurnNotAllowed,
+            CodexErrorRepr::TurnFailed { reason } => CodexError::TurnFailed(reason),
+            CodexErrorRepr::MissingChildStream { stream } => {
+                CodexError::MissingChildStream(match stream.as_str() {
+                    "stdin" => "stdin",
+                    "stdout" => "stdout",
+                    "stderr" => "stderr",
+                    _ => "stream",
+                })
+            }
+            CodexErrorRepr::AppServer { reason } => CodexError::AppServer(reason),
+            CodexErrorRepr::ApprovalsUnsupported => CodexError::ApprovalsUnsupported,
+            CodexErrorRepr::BudgetExceeded {
+                budget,
+                limit,
+                observed,
+            } => CodexError::BudgetExceeded {
+                kind: budget,
+                limit,
+                observed,
+            },
+            CodexErrorRepr::Contextual { context, source } => {
+                CodexError::Contextual { context, source }
+            }
+            CodexErrorRepr::Io => CodexError::Io(std::io::Error::other(message)),
+            CodexErrorRepr::Json => CodexError::Json(serde::de::Error::custom(message)),
+        })
+    }
+}
@@ -1,31 +1,199 @@
-use serde::Deserialize;
+use std::fmt;
+
 use serde::Serialize;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
 
 use crate::items::ThreadItem;
+use crate::thread_id::ThreadId;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct ThreadError {
     pub message: String,
+    #[serde(default)]
+    pub will_retry: Option<bool>,
+    #[serde(default)]
+    pub attempt: Option<u32>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+impl ThreadError {
+    pub fn is_retrying(&self) -> bool {
+        self.will_retry == Some(true)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
 pub struct Usage {
     pub input_tokens: u64,
     pub cached_input_tokens: u64,
     pub output_tokens: u64,
 }
 
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
+pub struct RateLimitSnapshot {
+    pub primary_used_percent: Option<f64>,
+    pub secondary_used_percent: Option<f64>,
+    pub resets_at: Option<i64>,
+    pub secondary_resets_at: Option<i64>,
+}
+
+impl RateLimitSnapshot {
+    pub(crate) fn from_value(value: &Value) -> Option<RateLimitSnapshot> {
+        let field = |value: &Value, keys: [&str; 2]| {
+            keys.iter()
+                .find_map(|key| value.get(*key).filter(|v| !v.is_null()).cloned())
+        };
+        let window = |name: &str| value.get(name).filter(|window| window.is_object());
+
+        let primary_used_percent = field(value, ["primary_used_percent", "primaryUsedPercent"])
+            .or_else(|| field(window("primary")?, ["used_percent", "usedPercent"]))
+            .and_then(|percent| percent.as_f64());
+        let secondary_used_percent =
+            field(value, ["secondary_used_percent", "secondaryUsedPercent"])
+                .or_else(|| field(window("secondary")?, ["used_percent", "usedPercent"]))
+                .and_then(|percent| percent.as_f64());
+        let resets_at = field(value, ["resets_at", "resetsAt"])
+            .or_else(|| field(window("primary")?, ["resets_at", "resetsAt"]))
+            .and_then(|resets_at| resets_at.as_i64());
+        let secondary_resets_at = field(value, ["secondary_resets_at", "secondaryResetsAt"])
+            .or_else(|| field(window("secondary")?, ["resets_at", "resetsAt"]))
+            .and_then(|resets_at| resets_at.as_i64());
+
+        let snapshot = RateLimitSnapshot {
+            primary_used_percent,
+            secondary_used_percent,
+            resets_at,
+            secondary_resets_at,
+        };
+        (snapshot != RateLimitSnapshot::default()).then_some(snapshot)
+    }
+}
+
+impl<'de> Deserialize<'de> for RateLimitSnapshot {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        RateLimitSnapshot::from_value(&value)
+            .ok_or_else(|| serde::de::Error::custom("unrecognized rate limit payload"))
+    }
+}
+
+fn lenient_rate_limits<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<RateLimitSnapshot>, D::Error> {
+    let value = Option::<Value>::deserialize(deserializer)?;
+    Ok(value.as_ref().and_then(RateLimitSnapshot::from_value))
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalKind {
+    CommandExecution,
+    FileChange,
+}
+
+impl ApprovalKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalKind::CommandExecution => "command_execution",
+            ApprovalKind::FileChange => "file_change",
+        }
+    }
+}
+
+impl fmt::Display for ApprovalKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ApprovalDecision {
+    Approve,
+    ApproveForSession,
+    Deny,
+    Abort,
+}
+
+impl ApprovalDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalDecision::Approve => "approve",
+            ApprovalDecision::ApproveForSession => "approve_for_session",
+            ApprovalDecision::Deny => "deny",
+            ApprovalDecision::Abort => "abort",
+        }
+    }
+}
+
+impl fmt::Display for ApprovalDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(tag = "type", content = "text", rename_all = "snake_case")]
+pub enum ItemDelta {
+    AgentMessageText(String),
+    CommandOutput(String),
+    Other,
+}
+
+#[derive(Deserialize)]
+struct ItemDeltaRepr {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: String,
+}
+
+impl<'de> Deserialize<'de> for ItemDelta {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = ItemDeltaRepr::deserialize(deserializer)?;
+        Ok(match repr.kind.as_str() {
+            "agent_message_text" => ItemDelta::AgentMessageText(repr.text),
+            "command_output" => ItemDelta::CommandOutput(repr.text),
+            _ => ItemDelta::Other,
+        })
+    }
+}
+
+impl ItemDelta {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ItemDelta::AgentMessageText(_) => "agent_message_text",
+            ItemDelta::CommandOutput(_) => "command_output",
+            ItemDelta::Other => "other",
+        }
+    }
+
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            ItemDelta::AgentMessageText(text) | ItemDelta::CommandOutput(text) => Some(text),
+            ItemDelta::Other => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type")]
 pub enum ThreadEvent {
     #[serde(rename = "thread.started")]
-    ThreadStarted { thread_id: String },
+    ThreadStarted { thread_id: ThreadId },
 
     #[serde(rename = "turn.started")]
     TurnStarted,
 
     #[serde(rename = "turn.completed")]
-    TurnCompleted { usage: Usage },
+    TurnCompleted {
+        usage: Usage,
+        #[serde(
+            default,
+            deserialize_with = "lenient_rate_limits",
+            skip_serializing_if = "Option::is_none"
+        )]
+        rate_limits: Option<RateLimitSnapshot>,
+    },
 
     #[serde(rename = "turn.failed")]
     TurnFailed { error: ThreadError },
@@ -38,6 +206,116 @@ pub enum ThreadEvent {
 
     #[serde(rename = "item.completed")]
     ItemCompleted { item: ThreadItem },
+
+    #[serde(rename = "item.delta")]
+    ItemDelta { item_id: String, delta: ItemDelta },
+
+    #[serde(rename = "approval.requested")]
+    ApprovalRequested {
+        id: String,
+        kind: ApprovalKind,
+        command: Option<String>,
+        #[serde(default)]
+        paths: Vec<String>,
+    },
+    #[serde(rename = "heartbeat")]
+    Heartbeat { elapsed_ms: u64 },
+
     #[serde(rename = "error")]
     ThreadErrorEvent { message: String },
 }
+
+impl ThreadEvent {
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            ThreadEvent::ThreadStarted { .. } => "thread.started",
+            ThreadEvent::TurnStarted => "turn.started",
+            ThreadEvent::TurnCompleted { .. } => "turn.completed",
+            ThreadEvent::TurnFailed { .. } => "turn.failed",
+            ThreadEvent::ItemStarted { .. } => "item.started",
+            ThreadEvent::ItemUpdated { .. } => "item.updated",
+            ThreadEvent::ItemCompleted { .. } => "item.completed",
+            ThreadEvent::ItemDelta { .. } => "item.delta",
+            ThreadEvent::ApprovalRequested { .. } => "approval.requested",
+            ThreadEvent::Heartbeat { .. } => "heartbeat",
+            ThreadEvent::ThreadErrorEvent { .. } => "error",
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        match self {
+            ThreadEvent::TurnCompleted { .. } | ThreadEvent::ThreadErrorEvent { .. } => true,
+            ThreadEvent::TurnFailed { error } => !error.is_retrying(),
+            _ => false,
+        }
+    }
+
+    pub fn is_item_event(&self) -> bool {
+        matches!(
+            self,
+            ThreadEvent::ItemStarted { .. }
+                | ThreadEvent::ItemUpdated { .. }
+                | ThreadEvent::ItemCompleted { .. }
+        )
+    }
+}
+
+impl fmt::Display for ThreadEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]", self.event_type())?;
+        match self {
+            ThreadEvent::ThreadStarted { thread_id } => write!(f, " thread_id={thread_id}"),
+            ThreadEvent::TurnStarted => Ok(()),
+            ThreadEvent::TurnCompleted { usage, .. } => {
+                write!(f, " tokens={}", usage.input_tokens + usage.output_tokens)
+            }
+            ThreadEvent::TurnFailed { error } => write!(f, " {}", error.message),
+            ThreadEvent::ItemStarted { item }
+            | ThreadEvent::ItemUpdated { item }
+            | ThreadEvent::ItemCompleted { item } => {
+                if let ThreadItem::Other = item {
+                    return write!(f, " {}", item.item_type());
+                }
+                write!(f, " {}(id={}", item.item_type(), item.id())?;
+                match item {
+                    ThreadItem::AgentMessage { text, .. } | ThreadItem::Reasoning { text, .. } => {
+                        write!(f, ", len={}", text.chars().count())?
+                    }
+                    ThreadItem::CommandExecution { exit_code, .. } => match exit_code {
+                        Some(code) => write!(f, ", exit={code}")?,
+                        None => write!(f, ", running")?,
+                    },
+                    ThreadItem::FileChange { changes, .. } => {
+                        write!(f, ", files={}", changes.len())?
+                    }
+                    ThreadItem::McpToolCall { server, tool, .. } => {
+                        write!(f, ", tool={server}/{tool}")?
+                    }
+                    ThreadItem::WebSearch { query, .. } => write!(f, ", query={query:?}")?,
+                    ThreadItem::TodoList { items, .. } => write!(f, ", items={}", items.len())?,
+                    ThreadItem::Error { message, .. } => write!(f, ", message={message:?}")?,
+                    ThreadItem::Other => {}
+                }
+                f.write_str(")")
+            }
+            ThreadEvent::ItemDelta { item_id, delta } => {
+                write!(f, " {}(id={item_id}", delta.kind())?;
+                if let Some(text) = delta.text() {
+                    write!(f, ", len={}", text.chars().count())?;
+                }
+                f.write_str(")")
+            }
+            ThreadEvent::ApprovalRequested {
+                id, kind, command, ..
+            } => {
+                write!(f, " {kind}(id={id}")?;
+                if let Some(command) = command {
+                    write!(f, ", command={command:?}")?;
+                }
+                f.write_str(")")
+            }
+            ThreadEvent::Heartbeat { elapsed_ms } => write!(f, " elapsed_ms={elapsed_ms}"),
+            ThreadEvent::ThreadErrorEvent { message } => write!(f, " {message}"),
+        }
+    }
+}
@@ -1,9 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fmt;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::process::Stdio;
+use std::sync::{Arc, RwLock};
 
 use async_stream::try_stream;
 use futures::Stream;
@@ -14,8 +15,14 @@ use tokio::task::JoinHandle;
 use tokio::time::{interval, Duration, MissedTickBehavior};
 use tokio_util::sync::CancellationToken;
 
+use crate::codex_options::{ColorMode, WebSearchFlagStyle};
 use crate::error::CodexError;
-use crate::thread_options::{ApprovalMode, ModelReasoningEffort, SandboxMode, WebSearchMode};
+use crate::stream_util::strip_ansi;
+use crate::thread_id::ThreadId;
+use crate::thread_options::{
+    ApprovalMode, ModelReasoningEffort, ModelVerbosity, ReasoningSummaryMode, SandboxMode,
+    SandboxWorkspaceWriteOptions, ShellEnvironmentPolicy, Tools, WebSearchMode,
+};
 
 pub type CodexLineStream = Pin<Box<dyn Stream<Item = Result<String, CodexError>> + Send>>;
 
@@ -24,6 +31,35 @@ pub struct CodexExec {
     executable_path: PathBuf,
     env_override: Option<HashMap<String, String>>,
     config_overrides: Option<Value>,
+    codex_home: Option<PathBuf>,
+    web_search_flag_style: WebSearchFlagStyle,
+    cli_version: Arc<RwLock<Option<(u64, u64, u64)>>>,
+    color: Option<ColorMode>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExecSubcommand {
+    Review { args: Vec<String> },
+}
+
+impl ExecSubcommand {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecSubcommand::Review { .. } => "review",
+        }
+    }
+
+    pub fn args(&self) -> &[String] {
+        match self {
+            ExecSubcommand::Review { args } => args,
+        }
+    }
+}
+
+impl fmt::Display for ExecSubcommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -31,20 +67,35 @@ pub struct CodexExecArgs {
     pub input: String,
     pub base_url: Option<String>,
     pub api_key: Option<String>,
-    pub thread_id: Option<String>,
+    pub thread_id: Option<ThreadId>,
+    pub resume_last: bool,
     pub images: Option<Vec<String>>,
     pub model: Option<String>,
+    pub model_provider: Option<String>,
+    pub oss: Option<bool>,
+    pub local_model_server: Option<String>,
+    pub profile: Option<String>,
     pub sandbox_mode: Option<SandboxMode>,
     pub working_directory: Option<String>,
     pub additional_directories: Option<Vec<String>>,
     pub skip_git_repo_check: Option<bool>,
     pub output_schema_file: Option<PathBuf>,
+    pub output_last_message_file: Option<PathBuf>,
     pub model_reasoning_effort: Option<ModelReasoningEffort>,
+    pub model_verbosity: Option<ModelVerbosity>,
+    pub reasoning_summary: Option<ReasoningSummaryMode>,
     pub cancel: Option<CancellationToken>,
     pub network_access_enabled: Option<bool>,
+    pub sandbox_workspace_write: Option<SandboxWorkspaceWriteOptions>,
+    pub shell_environment_policy: Option<ShellEnvironmentPolicy>,
     pub web_search_mode: Option<WebSearchMode>,
     pub web_search_enabled: Option<bool>,
+    pub tools: Option<Tools>,
     pub approval_policy: Option<ApprovalMode>,
+    pub base_instructions: Option<String>,
+    pub dangerously_bypass_approvals_and_sandbox: Option<bool>,
+    pub config: HashMap<String, Value>,
+    pub subcommand: Option<ExecSubcommand>,
 }
 
 impl fmt::Display for CodexExecArgs {
@@ -62,36 +113,205 @@ impl fmt::Display for CodexExecArgs {
 
         write!(
             f,
-            "CodexExecArgs {{ input_len: {}, base_url: {:?}, api_key: {}, thread_id: {:?}, images: {}, model: {:?}, sandbox_mode: {:?}, working_directory: {:?}, additional_directories: {:?}, skip_git_repo_check: {:?}, output_schema_file: {:?}, model_reasoning_effort: {:?}, cancel: {}, network_access_enabled: {:?}, web_search_mode: {:?}, web_search_enabled: {:?}, approval_policy: {:?} }}",
+            "CodexExecArgs {{ input_len: {}, base_url: {:?}, api_key: {}, thread_id: {:?}, resume_last: {}, images: {}, model: {:?}, model_provider: {:?}, oss: {:?}, local_model_server: {:?}, profile: {:?}, sandbox_mode: {:?}, working_directory: {:?}, additional_directories: {:?}, skip_git_repo_check: {:?}, output_schema_file: {:?}, output_last_message_file: {:?}, model_reasoning_effort: {:?}, model_verbosity: {:?}, reasoning_summary: {:?}, cancel: {}, network_access_enabled: {:?}, sandbox_workspace_write: {:?}, shell_environment_policy: {}, web_search_mode: {:?}, web_search_enabled: {:?}, tools: {:?}, approval_policy: {:?}, base_instructions_len: {:?}, dangerously_bypass_approvals_and_sandbox: {:?}, config_keys: {:?}, subcommand: {:?} }}",
             self.input.len(),
             self.base_url,
             api_key,
             self.thread_id,
+            self.resume_last,
             self.images.as_ref().map(|items| items.len()).unwrap_or(0),
             self.model,
+            self.model_provider,
+            self.oss,
+            self.local_model_server,
+            self.profile,
             self.sandbox_mode,
             self.working_directory,
             self.additional_directories,
             self.skip_git_repo_check,
             self.output_schema_file,
+            self.output_last_message_file,
             self.model_reasoning_effort,
+            self.model_verbosity,
+            self.reasoning_summary,
             cancel,
             self.network_access_enabled,
+            self.sandbox_workspace_write,
+            self.shell_environment_policy
+                .as_ref()
+                .map(|policy| format!("Some({policy})"))
+                .unwrap_or_else(|| "None".to_string()),
             self.web_search_mode,
             self.web_search_enabled,
+            self.tools,
             self.approval_policy,
+            self.base_instructions.as_ref().map(String::len),
+            self.dangerously_bypass_approvals_and_sandbox,
+            self.config_keys(),
+            self.subcommand,
         )
     }
 }
 
+impl CodexExecArgs {
+    pub fn extend_config(&mut self, key: impl Into<String>, value: Value) -> &mut Self {
+        self.config.insert(key.into(), value);
+        self
+    }
+
+    pub fn effective_approval_policy(&self) -> ApprovalMode {
+        self.approval_policy.clone().unwrap_or(ApprovalMode::Never)
+    }
+
+    pub fn deduplicated_images(&self) -> Option<Vec<String>> {
+        let images = self.images.as_ref()?;
+        let mut seen = HashSet::new();
+        Some(
+            images
+                .iter()
+                .filter(|image| seen.insert(image.as_str()))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    pub(crate) fn uses_oss(&self) -> bool {
+        self.oss.unwrap_or(false) && self.model_provider.is_none()
+    }
+
+    fn config_keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.config.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    pub fn validate(&self) -> Result<(), CodexError> {
+        if self.input.is_empty() && self.subcommand.is_none() {
+            return Err(CodexError::InvalidArgs(
+                "input must not be empty".to_string(),
+            ));
+        }
+
+        if let Some(base_url) = &self.base_url {
+            if !base_url.starts_with("http") {
+                return Err(CodexError::InvalidArgs(format!(
+                    "base_url must be an http(s) url, got {:?}",
+                    base_url
+                )));
+            }
+        }
+
+        if let Some(images) = &self.images {
+            if images.iter().any(|image| image.is_empty()) {
+                return Err(CodexError::InvalidArgs(
+                    "image paths must not be empty".to_string(),
+                ));
+            }
+        }
+
+        if let Some(thread_id) = &self.thread_id {
+            thread_id.validate()?;
+            if self.resume_last {
+                return Err(CodexError::InvalidArgs(
+                    "thread_id and resume_last are mutually exclusive".to_string(),
+                ));
+            }
+        }
+
+        if let Some(subcommand) = &self.subcommand {
+            if self.thread_id.is_some() || self.resume_last {
+                return Err(CodexError::InvalidArgs(format!(
+                    "exec {} cannot resume a thread",
+                    subcommand
+                )));
+            }
+            if self
+                .images
+                .as_ref()
+                .is_some_and(|images| !images.is_empty())
+            {
+                return Err(CodexError::InvalidArgs(format!(
+                    "exec {} does not accept images",
+                    subcommand
+                )));
+            }
+        }
+
+        if self
+            .dangerously_bypass_approvals_and_sandbox
+            .unwrap_or(false)
+            && self.sandbox_mode.is_some()
+        {
+            return Err(CodexError::InvalidArgs(
+                "dangerously_bypass_approvals_and_sandbox conflicts with sandbox_mode".to_string(),
+            ));
+        }
+
+        if self.oss.unwrap_or(false) && self.model_provider.is_some() {
+            log::warn!("Both oss and model_provider are set; model_provider wins");
+        }
+
+        if self.local_model_server.is_some() && !self.uses_oss() {
+            log::warn!("local_model_server is ignored unless oss is enabled");
+        }
+
+        if self.web_search_mode.is_some() && self.web_search_enabled.is_some() {
+            log::warn!("Both web_search_mode and web_search_enabled are set; web_search_mode wins");
+        }
+
+        let tools_web_search = self
+            .tools
+            .as_ref()
+            .is_some_and(|tools| tools.web_search.is_some());
+        if tools_web_search && (self.web_search_mode.is_some() || self.web_search_enabled.is_some())
+        {
+            log::warn!(
+                "tools.web_search is ignored because web_search_mode or web_search_enabled is set"
+            );
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct CommandSpec {
     pub args: Vec<String>,
     pub env: HashMap<String, String>,
 }
 
+const SENSITIVE_ENV_MARKERS: [&str; 4] = ["KEY", "SECRET", "TOKEN", "PASSWORD"];
+const SENSITIVE_CONFIG_KEYS: [&str; 4] = [
+    "base_instructions",
+    "shell_environment_policy",
+    "http_headers",
+    "env_http_headers",
+];
+
+impl CommandSpec {
+    pub fn masked_env(&self) -> HashMap<String, String> {
+        self.env
+            .iter()
+            .map(|(key, value)| {
+                let upper = key.to_ascii_uppercase();
+                let value = if SENSITIVE_ENV_MARKERS
+                    .iter()
+                    .any(|marker| upper.contains(marker))
+                {
+                    "[redacted]".to_string()
+                } else {
+                    value.clone()
+                };
+                (key.clone(), value)
+            })
+            .collect()
+    }
+}
+
 const INTERNAL_ORIGINATOR_ENV: &str = "CODEX_INTERNAL_ORIGINATOR_OVERRIDE";
 const RUST_SDK_ORIGINATOR: &str = "codex_sdk_rs";
+const LOG_COMMANDS_ENV: &str = "CODEX_SDK_LOG_COMMANDS";
+const SEARCH_FLAG_MIN_VERSION: (u64, u64, u64) = (0, 47, 0);
 
 impl CodexExec {
     pub fn new(
@@ -108,21 +328,155 @@ impl CodexExec {
             executable_path,
             env_override: env,
             config_overrides,
+            codex_home: None,
+            web_search_flag_style: WebSearchFlagStyle::default(),
+            cli_version: Arc::new(RwLock::new(None)),
+            color: None,
         })
     }
 
+    pub fn executable_path(&self) -> &Path {
+        &self.executable_path
+    }
+
+    pub(crate) fn resolved_executable_path(&self) -> PathBuf {
+        if self.executable_path.components().count() != 1 {
+            return self.executable_path.clone();
+        }
+        let search_path = match &self.env_override {
+            Some(vars) => vars.get("PATH").map(Into::into),
+            None => env::var_os("PATH"),
+        };
+        let extensions: Vec<String> = if cfg!(windows) {
+            env::var("PATHEXT")
+                .unwrap_or_else(|_| ".EXE;.CMD;.BAT;.COM".to_string())
+                .split(';')
+                .map(str::to_string)
+                .chain([String::new()])
+                .collect()
+        } else {
+            vec![String::new()]
+        };
+        search_path
+            .iter()
+            .flat_map(env::split_paths)
+            .flat_map(|dir| {
+                extensions.iter().map(move |extension| {
+                    let mut name = self.executable_path.clone().into_os_string();
+                    name.push(extension);
+                    dir.join(name)
+                })
+            })
+            .find(|candidate| candidate.is_file())
+            .unwrap_or_else(|| self.executable_path.clone())
+    }
+
+    pub fn with_codex_home(mut self, codex_home: impl Into<PathBuf>) -> Self {
+        self.codex_home = Some(codex_home.into());
+        self
+    }
+
+    pub fn with_web_search_flag_style(mut self, style: WebSearchFlagStyle) -> Self {
+        self.web_search_flag_style = style;
+        self
+    }
+
+    pub fn with_color(mut self, color: ColorMode) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn with_cli_version(mut self, version: &str) -> Self {
+        self.cli_version = Arc::new(RwLock::new(None));
+        self.record_cli_version(version);
+        self
+    }
+
+    pub(crate) fn record_cli_version(&self, version: &str) {
+        let parsed = Self::parse_version(version);
+        if parsed.is_none() {
+            log::warn!("Unrecognized codex version: {:?}", version);
+        }
+        if let Ok(mut cli_version) = self.cli_version.write() {
+            *cli_version = parsed;
+        }
+    }
+
+    fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+        let version = version
+            .split_whitespace()
+            .find(|part| part.starts_with(|ch: char| ch.is_ascii_digit()))?;
+        let mut parts = version
+            .split(['.', '-', '+'])
+            .map(|part| part.parse::<u64>().ok());
+        Some((
+            parts.next()??,
+            parts.next()??,
+            parts.next().flatten().unwrap_or(0),
+        ))
+    }
+
+    fn uses_search_flag(&self) -> bool {
+        match self.web_search_flag_style {
+            WebSearchFlagStyle::Flag => true,
+            WebSearchFlagStyle::Config => false,
+            WebSearchFlagStyle::Auto => self
+                .cli_version
+                .read()
+                .ok()
+                .and_then(|version| *version)
+                .is_some_and(|version| version >= SEARCH_FLAG_MIN_VERSION),
+        }
+    }
+
+    pub fn from_executable(path: impl Into<PathBuf>) -> Result<Self, CodexError> {
+        Self::new(Some(path.into()), None, None)
+    }
+
+    pub fn default_exec() -> Result<Self, CodexError> {
+        Self::from_executable("codex")
+    }
+
     #[doc(hidden)]
     pub fn build_command(&self, args: &CodexExecArgs) -> Result<CommandSpec, CodexError> {
         log::debug!("Building codex command");
         let mut command_args = vec!["exec".to_string(), "--experimental-json".to_string()];
 
+        if let Some(profile) = &args.profile {
+            command_args.push("--profile".to_string());
+            command_args.push(profile.clone());
+        }
+
+        if let Some(color) = self.color {
+            command_args.push("--color".to_string());
+            command_args.push(color.as_str().to_string());
+        }
+
+        let mut overrides = Vec::new();
         if let Some(config_overrides) = &self.config_overrides {
-            let overrides = Self::serialize_config_overrides(config_overrides)?;
+            overrides.extend(Self::serialize_config_overrides(config_overrides)?);
             log::debug!("Config override count: {}", overrides.len());
-            for override_entry in overrides {
-                command_args.push("--config".to_string());
-                command_args.push(override_entry);
+        }
+
+        for key in args.config_keys() {
+            if key.is_empty() {
+                return Err(CodexError::InvalidConfigKey);
+            }
+            Self::flatten_config_overrides(&args.config[key], key, &mut overrides)?;
+        }
+
+        for override_entry in overrides {
+            if args.model_reasoning_effort.is_some()
+                && override_entry.starts_with("model_reasoning_effort=")
+            {
+                continue;
             }
+            command_args.push("--config".to_string());
+            command_args.push(override_entry);
+        }
+
+        if args.uses_oss() {
+            command_args.push("--oss".to_string());
         }
 
         if let Some(model) = &args.model {
@@ -135,15 +489,22 @@ impl CodexExec {
             command_args.push(mode.as_str().to_string());
         }
 
+        if args
+            .dangerously_bypass_approvals_and_sandbox
+            .unwrap_or(false)
+        {
+            command_args.push("--dangerously-bypass-approvals-and-sandbox".to_string());
+        }
+
         if let Some(dir) = &args.working_directory {
             command_args.push("--cd".to_string());
             command_args.push(dir.clone());
         }
 
         if let Some(dirs) = &args.additional_directories {
-            for dir in dirs {
+            for dir in Self::resolve_directories(dirs, args.working_directory.as_deref()) {
                 command_args.push("--add-dir".to_string());
-                command_args.push(dir.clone());
+                command_args.push(dir);
             }
         }
 
@@ -156,12 +517,31 @@ impl CodexExec {
             command_args.push(path.to_string_lossy().to_string());
         }
 
+        if let Some(path) = &args.output_last_message_file {
+            command_args.push("--output-last-message".to_string());
+            command_args.push(path.to_string_lossy().to_string());
+        }
+
         if let Some(effort) = &args.model_reasoning_effort {
             command_args.push("--config".to_string());
             command_args.push(format!("model_reasoning_effort=\"{}\"", effort.as_str()));
         }
 
-        if let Some(network_access) = args.network_access_enabled {
+        if let Some(verbosity) = &args.model_verbosity {
+            command_args.push("--config".to_string());
+            command_args.push(format!("model_verbosity=\"{}\"", verbosity.as_str()));
+        }
+
+        if let Some(summary) = &args.reasoning_summary {
+            command_args.push("--config".to_string());
+            command_args.push(format!("model_reasoning_summary=\"{}\"", summary.as_str()));
+        }
+
+        let workspace_write = args.sandbox_workspace_write.clone().unwrap_or_default();
+        if let Some(network_access) = workspace_write
+            .network_access
+            .or(args.network_access_enabled)
+        {
             command_args.push("--config".to_string());
             command_args.push(format!(
                 "sandbox_workspace_write.network_access={}",
@@ -169,17 +549,85 @@ impl CodexExec {
             ));
         }
 
-        if let Some(mode) = &args.web_search_mode {
+        if let Some(exclude) = workspace_write.exclude_tmpdir_env_var {
             command_args.push("--config".to_string());
-            command_args.push(format!("web_search=\"{}\"", mode.as_str()));
-        } else if let Some(web_search_enabled) = args.web_search_enabled {
-            let value = if web_search_enabled {
-                "live"
+            command_args.push(format!(
+                "sandbox_workspace_write.exclude_tmpdir_env_var={}",
+                exclude
+            ));
+        }
+
+        if let Some(exclude) = workspace_write.exclude_slash_tmp {
+            command_args.push("--config".to_string());
+            command_args.push(format!(
+                "sandbox_workspace_write.exclude_slash_tmp={}",
+                exclude
+            ));
+        }
+
+        if let Some(roots) = &workspace_write.writable_roots {
+            let roots = Value::Array(
+                roots
+                    .iter()
+                    .map(|root| Value::String(root.to_string_lossy().to_string()))
+                    .collect(),
+            );
+            command_args.push("--config".to_string());
+            command_args.push(format!(
+                "sandbox_workspace_write.writable_roots={}",
+                Self::to_toml_value(&roots, "sandbox_workspace_write.writable_roots")?
+            ));
+        }
+
+        if let Some(policy) = &args.shell_environment_policy {
+            for override_entry in Self::shell_environment_policy_overrides(policy)? {
+                command_args.push("--config".to_string());
+                command_args.push(override_entry);
+            }
+        }
+
+        let web_search = match &args.web_search_mode {
+            Some(mode) => Some(mode.as_str()),
+            None => args.web_search_enabled.map(|enabled| {
+                if enabled {
+                    WebSearchMode::Live.as_str()
+                } else {
+                    WebSearchMode::Disabled.as_str()
+                }
+            }),
+        };
+        if let Some(value) = web_search {
+            if value == WebSearchMode::Live.as_str() && self.uses_search_flag() {
+                command_args.push("--search".to_string());
             } else {
-                "disabled"
-            };
+                command_args.push("--config".to_string());
+                command_args.push(format!("web_search=\"{}\"", value));
+            }
+        }
+
+        let tools = args.tools.clone().unwrap_or_default();
+        let tools_web_search = tools
+            .web_search
+            .filter(|_| args.web_search_mode.is_none() && args.web_search_enabled.is_none());
+        let tool_flags = [
+            ("tools.web_search", tools_web_search),
+            ("tools.view_image", tools.view_image),
+            ("include_plan_tool", tools.plan),
+            ("include_apply_patch_tool", tools.apply_patch),
+        ];
+        for (key, enabled) in tool_flags {
+            if let Some(enabled) = enabled {
+                command_args.push("--config".to_string());
+                command_args.push(format!("{key}={enabled}"));
+            }
+        }
+
+        if let Some(provider) = &args.model_provider {
             command_args.push("--config".to_string());
-            command_args.push(format!("web_search=\"{}\"", value));
+            command_args.push(format!(
+                "model_provider={}",
+                serde_json::to_string(provider)?
+            ));
         }
 
         if let Some(policy) = &args.approval_policy {
@@ -187,34 +635,71 @@ impl CodexExec {
             command_args.push(format!("approval_policy=\"{}\"", policy.as_str()));
         }
 
-        if let Some(thread_id) = &args.thread_id {
+        if let Some(instructions) = &args.base_instructions {
+            command_args.push("--config".to_string());
+            command_args.push(format!(
+                "base_instructions={}",
+                serde_json::to_string(instructions)?
+            ));
+        }
+
+        if let Some(subcommand) = &args.subcommand {
+            command_args.push(subcommand.as_str().to_string());
+            command_args.extend(subcommand.args().iter().cloned());
+        } else if let Some(thread_id) = &args.thread_id {
             command_args.push("resume".to_string());
-            command_args.push(thread_id.clone());
+            command_args.push(thread_id.to_string());
+        } else if args.resume_last {
+            command_args.push("resume".to_string());
+            command_args.push("--last".to_string());
         }
 
-        if let Some(images) = &args.images {
+        if let Some(images) = args.deduplicated_images() {
+            let provided = args.images.as_ref().map(Vec::len).unwrap_or(0);
+            if images.len() < provided {
+                log::warn!(
+                    "Dropped {} duplicate image path(s)",
+                    provided - images.len()
+                );
+            }
             for image in images {
                 command_args.push("--image".to_string());
-                command_args.push(image.clone());
+                command_args.push(image);
             }
         }
 
-        let env = self.build_env(args);
+        let command = CommandSpec {
+            args: command_args,
+            env: self.build_env(args),
+        };
 
-        log::debug!("Command args count: {}", command_args.len());
-        for arg in &command_args {
+        log::debug!("Command args count: {}", command.args.len());
+        for arg in Self::redacted_args(&command.args) {
             log::debug!("\t Arg: {}", arg);
         }
 
-        log::debug!("Environment variable count: {}", env.len());
-        for (key, value) in &env {
+        log::debug!("Environment variable count: {}", command.env.len());
+        for (key, value) in command.masked_env() {
             log::debug!("\t {}={}", key, value);
         }
 
-        Ok(CommandSpec {
-            args: command_args,
-            env,
-        })
+        Ok(command)
+    }
+
+    fn resolve_directories(dirs: &[String], working_directory: Option<&str>) -> Vec<String> {
+        let mut resolved: Vec<String> = Vec::with_capacity(dirs.len());
+        for dir in dirs {
+            let path = match working_directory {
+                Some(base) if Path::new(dir).is_relative() => {
+                    Path::new(base).join(dir).to_string_lossy().to_string()
+                }
+                _ => dir.clone(),
+            };
+            if !resolved.contains(&path) {
+                resolved.push(path);
+            }
+        }
+        resolved
     }
 
     fn build_env(&self, args: &CodexExecArgs) -> HashMap<String, String> {
@@ -229,6 +714,13 @@ impl CodexExec {
             log::debug!("Using inherited environment");
         }
 
+        if let Some(codex_home) = &self.codex_home {
+            env_vars.insert(
+                "CODEX_HOME".to_string(),
+                codex_home.to_string_lossy().into_owned(),
+            );
+        }
+
         env_vars
             .entry(INTERNAL_ORIGINATOR_ENV.to_string())
             .or_insert_with(|| RUST_SDK_ORIGINATOR.to_string());
@@ -239,6 +731,10 @@ impl CodexExec {
         env_vars
             .entry("TERM".to_string())
             .or_insert_with(|| "xterm".to_string());
+        if self.color == Some(ColorMode::Never) {
+            env_vars.insert("NO_COLOR".to_string(), "1".to_string());
+            log::debug!("NO_COLOR set");
+        }
 
         if let Some(base_url) = &args.base_url {
             env_vars.insert("OPENAI_BASE_URL".to_string(), base_url.clone());
@@ -248,15 +744,154 @@ impl CodexExec {
             env_vars.insert("CODEX_API_KEY".to_string(), api_key.clone());
             log::debug!("CODEX_API_KEY set");
         }
+        if let Some(server) = args.local_model_server.as_ref().filter(|_| args.uses_oss()) {
+            env_vars.insert("CODEX_OSS_BASE_URL".to_string(), server.clone());
+            log::debug!("CODEX_OSS_BASE_URL set");
+        }
 
         env_vars
     }
 
     pub fn run(&self, args: CodexExecArgs) -> Result<CodexLineStream, CodexError> {
+        args.validate()?;
         let command = self.build_command(&args)?;
+        if env::var(LOG_COMMANDS_ENV).as_deref() == Ok("1") {
+            log::info!("Running codex command: {}", self.describe_command(&command));
+        }
+        Ok(self.stream_command(command, args.input.clone(), args.cancel.clone()))
+    }
+
+    fn describe_command(&self, command: &CommandSpec) -> String {
+        let mut env_keys: Vec<&str> = command.env.keys().map(String::as_str).collect();
+        env_keys.sort_unstable();
+        format!(
+            "{} {:?} env_keys={:?}",
+            self.executable_path.display(),
+            Self::redacted_args(&command.args),
+            env_keys
+        )
+    }
+
+    pub fn run_subcommand(
+        &self,
+        args: Vec<String>,
+        env: Option<HashMap<String, String>>,
+        cancel: Option<CancellationToken>,
+    ) -> Result<CodexLineStream, CodexError> {
+        log::debug!("Running codex subcommand: {:?}", Self::redacted_args(&args));
+        let mut command_env = self.build_env(&CodexExecArgs::default());
+        if let Some(env) = env {
+            command_env.extend(env);
+        }
+        Ok(self.stream_command(
+            CommandSpec {
+                args,
+                env: command_env,
+            },
+            String::new(),
+            cancel,
+        ))
+    }
+
+    pub(crate) async fn run_subcommand_capture(
+        &self,
+        args: Vec<String>,
+        env: Option<HashMap<String, String>>,
+        cancel: Option<CancellationToken>,
+    ) -> Result<(std::process::ExitStatus, String, String), CodexError> {
+        log::debug!(
+            "Capturing codex subcommand: {:?}",
+            Self::redacted_args(&args)
+        );
+        if cancel.as_ref().is_some_and(CancellationToken::is_cancelled) {
+            return Err(CodexError::Aborted);
+        }
+        let mut command_env = self.build_env(&CodexExecArgs::default());
+        if let Some(env) = env {
+            command_env.extend(env);
+        }
+        let mut child = Self::spawn_codex(&self.executable_path, &[], &args, &command_env)?;
+        drop(child.stdin.take());
+        let output = match cancel {
+            Some(token) => tokio::select! {
+                output = child.wait_with_output() => output?,
+                _ = token.cancelled() => return Err(CodexError::Aborted),
+            },
+            None => child.wait_with_output().await?,
+        };
+        Ok((
+            output.status,
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+            strip_ansi(&String::from_utf8_lossy(&output.stderr)),
+        ))
+    }
+
+    fn redacted_args(args: &[String]) -> Vec<String> {
+        let mut redacted = Vec::with_capacity(args.len());
+        let mut previous: Option<&str> = None;
+        for arg in args {
+            let value = match previous {
+                Some("--api-key") => "[redacted]".to_string(),
+                Some("--env") => match arg.split_once('=') {
+                    Some((key, _)) => format!("{key}=[redacted]"),
+                    None => arg.clone(),
+                },
+                Some("--config") | Some("-c") => Self::redacted_config(arg),
+                _ if arg.starts_with("--api-key=") => "--api-key=[redacted]".to_string(),
+                _ => match arg.strip_prefix("--config=") {
+                    Some(entry) => format!("--config={}", Self::redacted_config(entry)),
+                    None => arg.clone(),
+                },
+            };
+            redacted.push(value);
+            previous = Some(arg.as_str());
+        }
+        redacted
+    }
+
+    fn redacted_config(entry: &str) -> String {
+        let Some((key, _)) = entry.split_once('=') else {
+            return entry.to_string();
+        };
+        let upper = key.to_ascii_uppercase();
+        let sensitive = key
+            .split('.')
+            .any(|part| SENSITIVE_CONFIG_KEYS.contains(&part))
+            || SENSITIVE_ENV_MARKERS
+                .iter()
+                .any(|marker| upper.contains(marker));
+        if sensitive {
+            format!("{key}=[redacted]")
+        } else {
+            entry.to_string()
+        }
+    }
+
+    pub(crate) fn exit_detail(status: std::process::ExitStatus) -> String {
+        status
+            .code()
+            .map(|code| format!("code {}", code))
+            .unwrap_or_else(|| "signal".to_string())
+    }
+
+    pub async fn run_subcommand_output(&self, args: Vec<String>) -> Result<String, CodexError> {
+        use futures::StreamExt;
+
+        let mut lines = self.run_subcommand(args, None, None)?;
+        let mut output = Vec::new();
+        while let Some(line) = lines.next().await {
+            output.push(line?);
+        }
+        Ok(output.join("\n"))
+    }
+
+    fn stream_command(
+        &self,
+        command: CommandSpec,
+        input: String,
+        cancel: Option<CancellationToken>,
+    ) -> CodexLineStream {
         let executable_path = self.executable_path.clone();
-        let cancel = args.cancel.clone();
-        let input = args.input.clone();
 
         log::debug!(
             "Running codex with executable: {}",
@@ -271,7 +906,10 @@ impl CodexExec {
                 }
             }
 
-            let mut child = Self::spawn_codex(&executable_path, &[], &command.args, &command.env)?;
+            let mut child = Self::codex_command(&executable_path, &[], &command.args, &command.env)
+                .kill_on_drop(true)
+                .spawn()
+                .map_err(CodexError::from)?;
 
             if let Some(mut stdin) = child.stdin.take() {
                 use tokio::io::AsyncWriteExt;
@@ -297,7 +935,7 @@ impl CodexExec {
 
             loop {
                 let action = if exit_status.is_some() {
-                    LoopAction::Line(lines.next_line().await?)
+                    LoopAction::Line(lines.next_line().await.map_err(Self::line_error)?)
                 } else {
                     let result: Result<LoopAction, CodexError> = tokio::select! {
                         _ = async {
@@ -311,7 +949,7 @@ impl CodexExec {
                             log::debug!("Execution aborted during stream");
                             Err(CodexError::Aborted)
                         }
-                        line = lines.next_line() => line.map(LoopAction::Line).map_err(CodexError::from),
+                        line = lines.next_line() => line.map(LoopAction::Line).map_err(Self::line_error),
                         _ = poll.tick() => Ok(LoopAction::Tick),
                     };
                     result?
@@ -341,16 +979,49 @@ impl CodexExec {
             };
             let stderr_buffer = stderr_task.await.unwrap_or_default();
             if !status.success() {
-                let detail = status
-                    .code()
-                    .map(|code| format!("code {}", code))
-                    .unwrap_or_else(|| "signal".to_string());
-                let stderr_text = String::from_utf8_lossy(&stderr_buffer).to_string();
+                let detail = Self::exit_detail(status);
+                let stderr_text = strip_ansi(&String::from_utf8_lossy(&stderr_buffer));
                 Err(CodexError::ExecFailed(detail, stderr_text))?;
             }
         };
 
-        Ok(Box::pin(stream))
+        Box::pin(stream)
+    }
+
+    fn line_error(error: std::io::Error) -> CodexError {
+        if error.kind() == std::io::ErrorKind::InvalidData {
+            log::debug!("Codex output was not valid UTF-8: {}", error);
+            CodexError::InvalidUtf8(error.to_string())
+        } else {
+            CodexError::Io(error)
+        }
+    }
+
+    #[cfg(feature = "app-server")]
+    pub(crate) fn spawn_app_server(&self, args: &CodexExecArgs) -> Result<Child, CodexError> {
+        let mut command_args = Vec::new();
+        if let Some(config_overrides) = &self.config_overrides {
+            for override_entry in Self::serialize_config_overrides(config_overrides)? {
+                command_args.push("--config".to_string());
+                command_args.push(override_entry);
+            }
+        }
+        command_args.push("app-server".to_string());
+
+        let env = self.build_env(args);
+        if env::var(LOG_COMMANDS_ENV).as_deref() == Ok("1") {
+            log::info!(
+                "Running codex command: {}",
+                self.describe_command(&CommandSpec {
+                    args: command_args.clone(),
+                    env: env.clone(),
+                })
+            );
+        }
+        Self::codex_command(&self.executable_path, &[], &command_args, &env)
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(CodexError::from)
     }
 
     fn spawn_codex(
@@ -359,6 +1030,18 @@ impl CodexExec {
         args: &[String],
         envs: &HashMap<String, String>,
     ) -> Result<Child, CodexError> {
+        Self::codex_command(exe, pre_args, args, envs)
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(CodexError::from)
+    }
+
+    fn codex_command(
+        exe: &Path,
+        pre_args: &[String],
+        args: &[String],
+        envs: &HashMap<String, String>,
+    ) -> Command {
         #[cfg(target_os = "windows")]
         let mut command = {
             let mut cmd = Command::new("cmd");
@@ -375,9 +1058,8 @@ impl CodexExec {
             .envs(envs)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(CodexError::from)
+            .stderr(Stdio::piped());
+        command
     }
 
     fn capture_stderr(stderr: tokio::process::ChildStderr) -> JoinHandle<Vec<u8>> {
@@ -403,6 +1085,49 @@ impl CodexExec {
         Ok(overrides)
     }
 
+    fn shell_environment_policy_overrides(
+        policy: &ShellEnvironmentPolicy,
+    ) -> Result<Vec<String>, CodexError> {
+        let strings = |values: &Vec<String>| {
+            Value::Array(values.iter().cloned().map(Value::String).collect())
+        };
+        let fields = [
+            (
+                "inherit",
+                policy
+                    .inherit
+                    .as_ref()
+                    .map(|inherit| Value::String(inherit.as_str().to_string())),
+            ),
+            (
+                "ignore_default_excludes",
+                policy.ignore_default_excludes.map(Value::Bool),
+            ),
+            ("exclude", policy.exclude.as_ref().map(strings)),
+            ("include_only", policy.include_only.as_ref().map(strings)),
+            (
+                "set",
+                policy.set.as_ref().map(|vars| {
+                    Value::Object(
+                        vars.iter()
+                            .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+                            .collect(),
+                    )
+                }),
+            ),
+        ];
+
+        let mut overrides = Vec::new();
+        for (key, value) in fields {
+            if let Some(value) = value {
+                let path = format!("shell_environment_policy.{key}");
+                let rendered = Self::to_toml_value(&value, &path)?;
+                overrides.push(format!("{path}={rendered}"));
+            }
+        }
+        Ok(overrides)
+    }
+
     fn flatten_config_overrides(
         value: &Value,
         prefix: &str,
@@ -440,9 +1165,9 @@ impl CodexExec {
                 continue;
             }
             let path = if prefix.is_empty() {
-                key.clone()
+                Self::format_toml_key(key)
             } else {
-                format!("{}.{}", prefix, key)
+                format!("{}.{}", prefix, Self::format_toml_key(key))
             };
             if child.is_object() {
                 Self::flatten_config_overrides(child, &path, overrides)?;
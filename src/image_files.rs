@@ -0,0 +1,132 @@
+use std::fmt;
+use std::io::Write;
+#[cfg(feature = "image-download")]
+use std::time::Duration;
+
+use tempfile::TempPath;
+
+use crate::error::CodexError;
+
+#[cfg(feature = "image-download")]
+const DOWNLOAD_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+#[cfg(feature = "image-download")]
+const DOWNLOAD_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+}
+
+impl ImageFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpeg",
+            ImageFormat::Gif => "gif",
+            ImageFormat::Webp => "webp",
+        }
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ImageFiles {
+    files: Vec<TempPath>,
+}
+
+impl ImageFiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write_bytes(&mut self, data: &[u8], format: &ImageFormat) -> Result<String, CodexError> {
+        let path = self.persist(data, &format!(".{}", format.as_str()))?;
+        log::debug!("Wrote {} byte {} image to {}", data.len(), format, path);
+        Ok(path)
+    }
+
+    #[cfg(not(feature = "image-download"))]
+    pub fn resolve_url(&mut self, url: &str) -> Result<String, CodexError> {
+        log::debug!("Passing image url through: {}", url);
+        Ok(url.to_string())
+    }
+
+    #[cfg(feature = "image-download")]
+    pub fn resolve_url(&mut self, url: &str) -> Result<String, CodexError> {
+        use tokio::runtime::{Handle, RuntimeFlavor};
+
+        let bytes = match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(|| Self::download(url))
+            }
+            _ => Self::download(url),
+        }?;
+
+        let path = self.persist(&bytes, &Self::extension_of(url))?;
+        log::debug!("Downloaded image {} to {}", url, path);
+        Ok(path)
+    }
+
+    #[cfg(feature = "image-download")]
+    fn download(url: &str) -> Result<Vec<u8>, CodexError> {
+        use std::io::Read;
+
+        let download_error = |detail: String| CodexError::ImageDownload(url.to_string(), detail);
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(DOWNLOAD_CONNECT_TIMEOUT)
+            .timeout_read(DOWNLOAD_READ_TIMEOUT)
+            .build();
+        let response = agent
+            .get(url)
+            .call()
+            .map_err(|error| download_error(error.to_string()))?;
+        let mut bytes = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut bytes)
+            .map_err(|error| download_error(error.to_string()))?;
+        Ok(bytes)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    fn persist(&mut self, bytes: &[u8], suffix: &str) -> Result<String, CodexError> {
+        let mut file = tempfile::Builder::new()
+            .prefix("codex-input-image-")
+            .suffix(suffix)
+            .tempfile()?;
+        file.write_all(bytes)?;
+        let path = file.into_temp_path();
+        let resolved = path.to_string_lossy().to_string();
+        self.files.push(path);
+        Ok(resolved)
+    }
+
+    #[cfg(feature = "image-download")]
+    fn extension_of(url: &str) -> String {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        let name = path.rsplit('/').next().unwrap_or(path);
+        match name.rsplit_once('.') {
+            Some((_, ext))
+                if !ext.is_empty() && ext.chars().all(|ch| ch.is_ascii_alphanumeric()) =>
+            {
+                format!(".{}", ext)
+            }
+            _ => String::new(),
+        }
+    }
+}
@@ -1,7 +1,24 @@
+use std::fmt;
+use std::path::Path;
+
+use glob::{MatchOptions, Pattern};
+
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value;
 
+const DISPLAY_TEXT_CHARS: usize = 60;
+
+pub(crate) fn truncate_chars(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    match max_chars.checked_sub(1) {
+        Some(kept) => text.chars().take(kept).chain(['…']).collect(),
+        None => String::new(),
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum CommandExecutionStatus {
@@ -10,7 +27,23 @@ pub enum CommandExecutionStatus {
     Failed,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+impl CommandExecutionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommandExecutionStatus::InProgress => "in_progress",
+            CommandExecutionStatus::Completed => "completed",
+            CommandExecutionStatus::Failed => "failed",
+        }
+    }
+}
+
+impl fmt::Display for CommandExecutionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "snake_case")]
 pub enum PatchChangeKind {
     Add,
@@ -18,6 +51,22 @@ pub enum PatchChangeKind {
     Update,
 }
 
+impl PatchChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PatchChangeKind::Add => "add",
+            PatchChangeKind::Delete => "delete",
+            PatchChangeKind::Update => "update",
+        }
+    }
+}
+
+impl fmt::Display for PatchChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum PatchApplyStatus {
@@ -25,6 +74,21 @@ pub enum PatchApplyStatus {
     Failed,
 }
 
+impl PatchApplyStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PatchApplyStatus::Completed => "completed",
+            PatchApplyStatus::Failed => "failed",
+        }
+    }
+}
+
+impl fmt::Display for PatchApplyStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum McpToolCallStatus {
@@ -33,6 +97,22 @@ pub enum McpToolCallStatus {
     Failed,
 }
 
+impl McpToolCallStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            McpToolCallStatus::InProgress => "in_progress",
+            McpToolCallStatus::Completed => "completed",
+            McpToolCallStatus::Failed => "failed",
+        }
+    }
+}
+
+impl fmt::Display for McpToolCallStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct CommandExecutionItem {
     pub id: String,
@@ -40,14 +120,34 @@ pub struct CommandExecutionItem {
     pub kind: String,
     pub command: String,
     pub aggregated_output: String,
+    #[serde(default)]
+    pub stdout: Option<String>,
+    #[serde(default)]
+    pub stderr: Option<String>,
     pub exit_code: Option<i32>,
     pub status: CommandExecutionStatus,
+    #[serde(default)]
+    pub started_at: Option<String>,
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+}
+
+impl CommandExecutionItem {
+    pub fn stderr_has_errors(&self) -> bool {
+        self.stderr
+            .as_deref()
+            .is_some_and(|stderr| !stderr.is_empty())
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct FileUpdateChange {
     pub path: String,
     pub kind: PatchChangeKind,
+    #[serde(default)]
+    pub diff: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -83,6 +183,32 @@ pub struct McpToolCallItem {
     pub status: McpToolCallStatus,
 }
 
+impl McpToolCallItem {
+    pub fn is_successful(&self) -> bool {
+        Self::successful(&self.status, &self.error)
+    }
+
+    pub fn is_pending(&self) -> bool {
+        Self::pending(&self.status)
+    }
+
+    pub fn is_failed(&self) -> bool {
+        Self::failed(&self.status, &self.error)
+    }
+
+    fn successful(status: &McpToolCallStatus, error: &Option<McpToolCallError>) -> bool {
+        *status == McpToolCallStatus::Completed && error.is_none()
+    }
+
+    fn pending(status: &McpToolCallStatus) -> bool {
+        *status == McpToolCallStatus::InProgress
+    }
+
+    fn failed(status: &McpToolCallStatus, error: &Option<McpToolCallError>) -> bool {
+        *status == McpToolCallStatus::Failed || error.is_some()
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct AgentMessageItem {
     pub id: String,
@@ -91,6 +217,102 @@ pub struct AgentMessageItem {
     pub text: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CodeBlock {
+    pub language: Option<String>,
+    pub source: String,
+}
+
+impl FileUpdateChange {
+    pub fn matches_glob(&self, pattern: &str) -> bool {
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+        Pattern::new(pattern)
+            .map(|pattern| pattern.matches_with(&self.path, options))
+            .unwrap_or(false)
+    }
+}
+
+impl FileChangeItem {
+    pub fn any_change_matches(&self, pattern: &str) -> bool {
+        self.changes
+            .iter()
+            .any(|change| change.matches_glob(pattern))
+    }
+
+    pub fn changes_under<'a>(
+        &'a self,
+        directory: &'a str,
+    ) -> impl Iterator<Item = &'a FileUpdateChange> + 'a {
+        self.changes
+            .iter()
+            .filter(move |change| Path::new(&change.path).starts_with(directory))
+    }
+}
+
+impl AgentMessageItem {
+    pub fn code_blocks(&self) -> Vec<CodeBlock> {
+        let mut blocks = Vec::new();
+        let mut open: Option<(char, usize, Option<String>, Vec<&str>)> = None;
+
+        for line in self.text.lines() {
+            let trimmed = line.trim_start();
+            match open.as_mut() {
+                None => {
+                    if let Some((fence_char, fence_len)) = Self::fence(trimmed) {
+                        let info = trimmed[fence_len..].trim();
+                        let language = info
+                            .split_whitespace()
+                            .next()
+                            .map(|language| language.to_string());
+                        open = Some((fence_char, fence_len, language, Vec::new()));
+                    }
+                }
+                Some((fence_char, fence_len, _, lines)) => {
+                    let is_closing = Self::fence(trimmed)
+                        .map(|(ch, len)| {
+                            ch == *fence_char
+                                && len >= *fence_len
+                                && trimmed[len..].trim().is_empty()
+                        })
+                        .unwrap_or(false);
+                    if is_closing {
+                        if let Some((_, _, language, lines)) = open.take() {
+                            blocks.push(CodeBlock {
+                                language,
+                                source: lines.join("\n"),
+                            });
+                        }
+                    } else {
+                        lines.push(line);
+                    }
+                }
+            }
+        }
+
+        if let Some((_, _, language, lines)) = open {
+            blocks.push(CodeBlock {
+                language,
+                source: lines.join("\n"),
+            });
+        }
+
+        blocks
+    }
+
+    fn fence(line: &str) -> Option<(char, usize)> {
+        let fence_char = line.chars().next().filter(|ch| *ch == '`' || *ch == '~')?;
+        let len = line.chars().take_while(|ch| *ch == fence_char).count();
+        if len >= 3 {
+            Some((fence_char, len))
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct ReasoningItem {
     pub id: String,
@@ -99,12 +321,31 @@ pub struct ReasoningItem {
     pub text: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct WebSearchResult {
+    pub urls: Vec<String>,
+    pub snippet: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct WebSearchHit {
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub snippet: Option<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct WebSearchItem {
     pub id: String,
     #[serde(rename = "type")]
     pub kind: String,
     pub query: String,
+    pub result: Option<WebSearchResult>,
+    #[serde(default)]
+    pub results: Vec<WebSearchHit>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -113,6 +354,10 @@ pub struct ErrorItem {
     #[serde(rename = "type")]
     pub kind: String,
     pub message: String,
+    #[serde(default)]
+    pub will_retry: Option<bool>,
+    #[serde(default)]
+    pub attempt: Option<u32>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -141,8 +386,18 @@ pub enum ThreadItem {
         id: String,
         command: String,
         aggregated_output: String,
+        #[serde(default)]
+        stdout: Option<String>,
+        #[serde(default)]
+        stderr: Option<String>,
         exit_code: Option<i32>,
         status: CommandExecutionStatus,
+        #[serde(default)]
+        started_at: Option<String>,
+        #[serde(default)]
+        duration_ms: Option<u64>,
+        #[serde(default)]
+        cwd: Option<String>,
     },
     #[serde(rename = "file_change")]
     FileChange {
@@ -161,9 +416,133 @@ pub enum ThreadItem {
         status: McpToolCallStatus,
     },
     #[serde(rename = "web_search")]
-    WebSearch { id: String, query: String },
+    WebSearch {
+        id: String,
+        query: String,
+        result: Option<WebSearchResult>,
+        #[serde(default)]
+        results: Vec<WebSearchHit>,
+    },
     #[serde(rename = "todo_list")]
     TodoList { id: String, items: Vec<TodoItem> },
     #[serde(rename = "error")]
-    Error { id: String, message: String },
+    Error {
+        id: String,
+        message: String,
+        #[serde(default)]
+        will_retry: Option<bool>,
+        #[serde(default)]
+        attempt: Option<u32>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+impl ThreadItem {
+    pub fn item_type(&self) -> &'static str {
+        match self {
+            ThreadItem::AgentMessage { .. } => "agent_message",
+            ThreadItem::Reasoning { .. } => "reasoning",
+            ThreadItem::CommandExecution { .. } => "command_execution",
+            ThreadItem::FileChange { .. } => "file_change",
+            ThreadItem::McpToolCall { .. } => "mcp_tool_call",
+            ThreadItem::WebSearch { .. } => "web_search",
+            ThreadItem::TodoList { .. } => "todo_list",
+            ThreadItem::Error { .. } => "error",
+            ThreadItem::Other => "other",
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        match self {
+            ThreadItem::AgentMessage { id, .. }
+            | ThreadItem::Reasoning { id, .. }
+            | ThreadItem::CommandExecution { id, .. }
+            | ThreadItem::FileChange { id, .. }
+            | ThreadItem::McpToolCall { id, .. }
+            | ThreadItem::WebSearch { id, .. }
+            | ThreadItem::TodoList { id, .. }
+            | ThreadItem::Error { id, .. } => id,
+            ThreadItem::Other => "",
+        }
+    }
+
+    pub fn is_mcp_call_successful(&self) -> bool {
+        match self {
+            ThreadItem::McpToolCall { status, error, .. } => {
+                McpToolCallItem::successful(status, error)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_mcp_call_pending(&self) -> bool {
+        match self {
+            ThreadItem::McpToolCall { status, .. } => McpToolCallItem::pending(status),
+            _ => false,
+        }
+    }
+
+    pub fn is_mcp_call_failed(&self) -> bool {
+        match self {
+            ThreadItem::McpToolCall { status, error, .. } => McpToolCallItem::failed(status, error),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn fill_streamed_text(mut self, streamed: String) -> ThreadItem {
+        match &mut self {
+            ThreadItem::AgentMessage { text, .. } if text.is_empty() => *text = streamed,
+            ThreadItem::CommandExecution {
+                aggregated_output, ..
+            } if aggregated_output.is_empty() => *aggregated_output = streamed,
+            _ => {}
+        }
+        self
+    }
+}
+
+impl fmt::Display for ThreadItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let ThreadItem::Other = self {
+            return f.write_str(self.item_type());
+        }
+
+        write!(f, "{}: ", self.item_type())?;
+        match self {
+            ThreadItem::AgentMessage { text, .. } | ThreadItem::Reasoning { text, .. } => f
+                .write_str(&truncate_chars(
+                    &text.trim().replace('\n', " "),
+                    DISPLAY_TEXT_CHARS,
+                )),
+            ThreadItem::CommandExecution {
+                command,
+                exit_code,
+                status,
+                ..
+            } => match exit_code {
+                Some(code) => write!(f, "{command} (exit={code})"),
+                None => write!(f, "{command} ({status})"),
+            },
+            ThreadItem::FileChange {
+                changes, status, ..
+            } => {
+                let noun = if changes.len() == 1 { "file" } else { "files" };
+                write!(f, "{} {noun} ({status})", changes.len())
+            }
+            ThreadItem::McpToolCall {
+                server,
+                tool,
+                status,
+                ..
+            } => write!(f, "{server}/{tool} ({status})"),
+            ThreadItem::WebSearch { query, .. } => f.write_str(query),
+            ThreadItem::TodoList { items, .. } => {
+                let done = items.iter().filter(|item| item.completed).count();
+                write!(f, "{done}/{} done", items.len())
+            }
+            ThreadItem::Error { message, .. } => f.write_str(message),
+            ThreadItem::Other => Ok(()),
+        }
+    }
 }
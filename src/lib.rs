@@ -1,29 +1,65 @@
+#[cfg(feature = "app-server")]
+mod app_server;
+pub mod apply;
+pub mod auth;
 pub mod codex;
 pub mod codex_options;
 pub mod error;
 pub mod events;
 pub mod exec;
+pub mod image_files;
 pub mod items;
+pub mod mcp;
+pub mod output_last_message_file;
 pub mod output_schema_file;
+pub mod plan_progress;
+pub mod pricing;
+pub mod prompt;
+pub mod review;
+pub mod sessions;
+pub mod stream_util;
 pub mod thread;
+pub mod thread_id;
 pub mod thread_options;
 pub mod turn_options;
+pub mod usage;
 
-pub use codex::Codex;
-pub use codex_options::{CodexConfigObject, CodexConfigValue, CodexOptions};
-pub use error::CodexError;
-pub use events::{ThreadError, ThreadEvent, Usage};
-pub use exec::{CodexExec, CodexExecArgs, CodexLineStream, CommandSpec};
+pub use apply::{ApplyOptions, ApplyOutcome, ApplyStatus};
+pub use auth::{AuthMethod, AuthStatus};
+pub use codex::{Codex, HealthCheckResult};
+pub use codex_options::{
+    CodexConfigObject, CodexConfigValue, CodexOptions, ColorMode, ExecBackend, HistoryPersistence,
+    ModelProviderConfig, WebSearchFlagStyle, WireApi,
+};
+pub use error::{BudgetKind, CodexError};
+pub use events::{
+    ApprovalDecision, ApprovalKind, ItemDelta, RateLimitSnapshot, ThreadError, ThreadEvent, Usage,
+};
+pub use exec::{CodexExec, CodexExecArgs, CodexLineStream, CommandSpec, ExecSubcommand};
+pub use image_files::{ImageFiles, ImageFormat};
 pub use items::{
-    AgentMessageItem, CommandExecutionItem, ErrorItem, FileChangeItem, FileUpdateChange,
+    AgentMessageItem, CodeBlock, CommandExecutionItem, ErrorItem, FileChangeItem, FileUpdateChange,
     McpToolCallItem, PatchApplyStatus, PatchChangeKind, ReasoningItem, ThreadItem, TodoItem,
-    TodoListItem, WebSearchItem,
+    TodoListItem, WebSearchHit, WebSearchItem, WebSearchResult,
 };
+pub use mcp::{McpManager, McpServerConfig, McpServerEntry, McpTransportKind};
+pub use output_last_message_file::OutputLastMessageFile;
 pub use output_schema_file::OutputSchemaFile;
+pub use plan_progress::PlanProgress;
+pub use pricing::{Cost, ModelPricing};
+pub use prompt::PromptTemplate;
+pub use review::{ReviewCodeLocation, ReviewFinding, ReviewLineRange, ReviewRequest, ReviewResult};
+pub use sessions::{ThreadListFilter, ThreadListOrder, ThreadSummary};
+pub use stream_util::{strip_ansi, with_line_timeout, PlanProgressStream, ThreadEventStreamExt};
 pub use thread::{
-    Input, RunResult, RunStreamedResult, StreamedTurn, Thread, ThreadEventStream, Turn, UserInput,
+    Input, ItemSubscription, RunResult, RunStreamedResult, StreamedTurn, Thread, ThreadEventStream,
+    TimestampedItemStream, TimestampedThreadItem, Turn, UserInput,
 };
+pub use thread_id::ThreadId;
 pub use thread_options::{
-    ApprovalMode, ModelReasoningEffort, SandboxMode, ThreadOptions, WebSearchMode,
+    ApprovalMode, FileInputLimits, ModelReasoningEffort, ModelVerbosity, ReasoningSummaryMode,
+    SandboxMode, SandboxWorkspaceWriteOptions, ShellEnvironmentInherit, ShellEnvironmentPolicy,
+    ThreadOptions, Tools, WebSearchMode,
 };
-pub use turn_options::TurnOptions;
+pub use turn_options::{FinalResponseMode, OutputLastMessage, TurnOptions};
+pub use usage::{CostModel, UsageAccumulator};
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::error::CodexError;
+use crate::exec::CodexExec;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum McpTransportKind {
+    Stdio,
+    StreamableHttp,
+}
+
+impl McpTransportKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            McpTransportKind::Stdio => "stdio",
+            McpTransportKind::StreamableHttp => "streamable_http",
+        }
+    }
+}
+
+impl fmt::Display for McpTransportKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum McpServerConfig {
+    Stdio {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        env: Option<HashMap<String, String>>,
+    },
+    StreamableHttp {
+        url: String,
+        #[serde(default)]
+        bearer_token_env_var: Option<String>,
+    },
+}
+
+impl McpServerConfig {
+    pub fn kind(&self) -> McpTransportKind {
+        match self {
+            McpServerConfig::Stdio { .. } => McpTransportKind::Stdio,
+            McpServerConfig::StreamableHttp { .. } => McpTransportKind::StreamableHttp,
+        }
+    }
+
+    fn add_args(&self) -> Result<Vec<String>, CodexError> {
+        match self {
+            McpServerConfig::Stdio { command, args, env } => {
+                if command.is_empty() {
+                    return Err(CodexError::InvalidArgs(
+                        "mcp server command must not be empty".to_string(),
+                    ));
+                }
+                let mut add_args = Vec::new();
+                if let Some(env) = env {
+                    let mut keys: Vec<&String> = env.keys().collect();
+                    keys.sort_unstable();
+                    for key in keys {
+                        add_args.push("--env".to_string());
+                        add_args.push(format!("{}={}", key, env[key]));
+                    }
+                }
+                add_args.push("--".to_string());
+                add_args.push(command.clone());
+                add_args.extend(args.iter().cloned());
+                Ok(add_args)
+            }
+            McpServerConfig::StreamableHttp {
+                url,
+                bearer_token_env_var,
+            } => {
+                if !url.starts_with("http") {
+                    return Err(CodexError::InvalidArgs(format!(
+                        "mcp server url must be an http(s) url, got {:?}",
+                        url
+                    )));
+                }
+                let mut add_args = vec!["--url".to_string(), url.clone()];
+                if let Some(var) = bearer_token_env_var {
+                    add_args.push("--bearer-token-env-var".to_string());
+                    add_args.push(var.clone());
+                }
+                Ok(add_args)
+            }
+        }
+    }
+}
+
+impl fmt::Display for McpServerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            McpServerConfig::Stdio { command, args, env } => {
+                let env_keys = env.as_ref().map(|env| {
+                    let mut keys: Vec<&str> = env.keys().map(String::as_str).collect();
+                    keys.sort_unstable();
+                    keys
+                });
+                write!(
+                    f,
+                    "stdio {{ command: {:?}, args: {:?}, env_keys: {:?} }}",
+                    command, args, env_keys
+                )
+            }
+            McpServerConfig::StreamableHttp {
+                url,
+                bearer_token_env_var,
+            } => write!(
+                f,
+                "streamable_http {{ url: {:?}, bearer_token_env_var: {:?} }}",
+                url, bearer_token_env_var
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct McpServerEntry {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub transport: McpServerConfig,
+    #[serde(default)]
+    pub startup_timeout_sec: Option<f64>,
+    #[serde(default)]
+    pub tool_timeout_sec: Option<f64>,
+}
+
+impl McpServerEntry {
+    pub fn transport_kind(&self) -> McpTransportKind {
+        self.transport.kind()
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+const CONFIG_LOCK_FILE: &str = "config.toml.lock";
+
+#[derive(Clone, Debug)]
+pub struct McpManager {
+    exec: CodexExec,
+    lock: Arc<Mutex<()>>,
+    codex_home: Option<PathBuf>,
+}
+
+impl McpManager {
+    pub(crate) fn new(exec: CodexExec, lock: Arc<Mutex<()>>, codex_home: Option<PathBuf>) -> Self {
+        Self {
+            exec,
+            lock,
+            codex_home,
+        }
+    }
+
+    pub async fn add(&self, name: &str, config: McpServerConfig) -> Result<(), CodexError> {
+        Self::validate_name(name)?;
+        let mut args = vec!["mcp".to_string(), "add".to_string(), name.to_string()];
+        args.extend(config.add_args()?);
+
+        let _guard = self.lock.lock().await;
+        let _config_lock = self.lock_config().await?;
+        log::debug!("Adding MCP server {}: {}", name, config);
+        self.exec.run_subcommand_output(args).await?;
+        Ok(())
+    }
+
+    pub async fn list(&self) -> Result<Vec<McpServerEntry>, CodexError> {
+        let _guard = self.lock.lock().await;
+        let output = self
+            .exec
+            .run_subcommand_output(vec![
+                "mcp".to_string(),
+                "list".to_string(),
+                "--json".to_string(),
+            ])
+            .await?;
+        log::debug!("MCP server list output length: {}", output.len());
+        Ok(serde_json::from_str(&output)?)
+    }
+
+    pub async fn remove(&self, name: &str) -> Result<(), CodexError> {
+        Self::validate_name(name)?;
+        let _guard = self.lock.lock().await;
+        let _config_lock = self.lock_config().await?;
+        log::debug!("Removing MCP server {}", name);
+        self.exec
+            .run_subcommand_output(vec![
+                "mcp".to_string(),
+                "remove".to_string(),
+                name.to_string(),
+            ])
+            .await?;
+        Ok(())
+    }
+
+    async fn lock_config(&self) -> Result<Option<File>, CodexError> {
+        let Some(codex_home) = self.codex_home.clone() else {
+            log::debug!("No CODEX_HOME; MCP config edits are only serialized in this process");
+            return Ok(None);
+        };
+        let file = tokio::task::spawn_blocking(move || {
+            fs::create_dir_all(&codex_home)?;
+            let file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(codex_home.join(CONFIG_LOCK_FILE))?;
+            file.lock()?;
+            Ok::<_, std::io::Error>(file)
+        })
+        .await
+        .map_err(|error| CodexError::Io(std::io::Error::other(error)))??;
+        Ok(Some(file))
+    }
+
+    fn validate_name(name: &str) -> Result<(), CodexError> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+        if !valid {
+            return Err(CodexError::InvalidArgs(format!(
+                "invalid mcp server name {:?}",
+                name
+            )));
+        }
+        Ok(())
+    }
+}
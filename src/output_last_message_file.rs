@@ -0,0 +1,63 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use tempfile::TempDir;
+
+use crate::error::CodexError;
+use crate::turn_options::OutputLastMessage;
+
+pub struct OutputLastMessageFile {
+    path: Option<PathBuf>,
+    _temp_dir: Option<TempDir>,
+}
+
+impl OutputLastMessageFile {
+    pub fn new(mode: Option<&OutputLastMessage>) -> Result<Self, CodexError> {
+        match mode {
+            None => Ok(Self {
+                path: None,
+                _temp_dir: None,
+            }),
+            Some(OutputLastMessage::Path(path)) => {
+                log::debug!("Writing last message to {:?}", path);
+                match std::fs::remove_file(path) {
+                    Ok(()) => log::debug!("Removed stale last message file {:?}", path),
+                    Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(error) => return Err(error.into()),
+                }
+                Ok(Self {
+                    path: Some(path.clone()),
+                    _temp_dir: None,
+                })
+            }
+            Some(OutputLastMessage::TempFile) => {
+                let temp_dir = tempfile::Builder::new()
+                    .prefix("codex-last-message-")
+                    .tempdir()?;
+                let path = temp_dir.path().join("last_message.txt");
+                log::debug!("Writing last message to temp file {:?}", path);
+
+                Ok(Self {
+                    path: Some(path),
+                    _temp_dir: Some(temp_dir),
+                })
+            }
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn read(&self) -> Option<String> {
+        let path = self.path.as_ref()?;
+        match std::fs::read_to_string(path) {
+            Ok(contents) if !contents.is_empty() => Some(contents),
+            Ok(_) => None,
+            Err(error) => {
+                log::debug!("Last message file {:?} not readable: {}", path, error);
+                None
+            }
+        }
+    }
+}
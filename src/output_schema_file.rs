@@ -1,11 +1,14 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::path::Path;
-use std::path::PathBuf;
+use std::path::{Component, PathBuf};
 
 use serde_json::Value;
 use tempfile::TempDir;
 
 use crate::error::CodexError;
 
+#[derive(Debug)]
 pub struct OutputSchemaFile {
     schema_path: Option<PathBuf>,
     _temp_dir: Option<TempDir>,
@@ -22,16 +25,13 @@ impl OutputSchemaFile {
                 })
             }
             Some(value) => {
-                if !value.is_object() {
-                    return Err(CodexError::InvalidOutputSchema);
-                }
+                Self::check_schema(value)?;
 
                 let temp_dir = tempfile::Builder::new()
                     .prefix("codex-output-schema-")
                     .tempdir()?;
                 let schema_path = temp_dir.path().join("schema.json");
-                std::fs::write(&schema_path, serde_json::to_vec(value)?)?;
-                log::debug!("Wrote output schema to {:?}", schema_path);
+                Self::write_schema(&schema_path, value)?;
 
                 Ok(Self {
                     schema_path: Some(schema_path),
@@ -41,7 +41,162 @@ impl OutputSchemaFile {
         }
     }
 
+    pub fn at_path(path: impl Into<PathBuf>, schema: &Value) -> Result<Self, CodexError> {
+        let schema_path = path.into();
+        Self::check_schema(schema)?;
+        if let Some(parent) = schema_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::write_schema(&schema_path, schema)?;
+
+        Ok(Self {
+            schema_path: Some(schema_path),
+            _temp_dir: None,
+        })
+    }
+
+    pub fn from_directory(dir: &Path, entry: &str) -> Result<Self, CodexError> {
+        let escapes = Path::new(entry).components().any(|component| {
+            matches!(
+                component,
+                Component::ParentDir | Component::RootDir | Component::Prefix(_)
+            )
+        });
+        if escapes {
+            return Err(CodexError::InvalidOutputSchema(format!(
+                "entry {:?} must be a relative path inside the schema directory",
+                entry
+            )));
+        }
+
+        let root = std::fs::canonicalize(dir)?;
+        let temp_dir = tempfile::Builder::new()
+            .prefix("codex-output-schema-")
+            .tempdir()?;
+        Self::copy_dir(&root, &root, temp_dir.path())?;
+
+        let schema_path = temp_dir.path().join(entry);
+        if !schema_path.is_file() {
+            return Err(CodexError::InvalidOutputSchema(format!(
+                "entry {:?} not found in {:?}",
+                entry, dir
+            )));
+        }
+        log::debug!(
+            "Copied output schema directory {:?} to {:?}",
+            dir,
+            temp_dir.path()
+        );
+
+        Ok(Self {
+            schema_path: Some(schema_path),
+            _temp_dir: Some(temp_dir),
+        })
+    }
+
+    fn copy_dir(root: &Path, from: &Path, to: &Path) -> Result<(), CodexError> {
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            let target = to.join(entry.file_name());
+            let file_type = entry.file_type()?;
+            if file_type.is_symlink() {
+                let resolved = std::fs::canonicalize(entry.path())?;
+                if !resolved.starts_with(root) || resolved.is_dir() {
+                    return Err(CodexError::InvalidOutputSchema(format!(
+                        "symlink {:?} must point to a file inside the schema directory",
+                        entry.path()
+                    )));
+                }
+                std::fs::copy(&resolved, &target)?;
+            } else if file_type.is_dir() {
+                std::fs::create_dir_all(&target)?;
+                Self::copy_dir(root, &entry.path(), &target)?;
+            } else {
+                std::fs::copy(entry.path(), &target)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn cache_key(schema: &Value) -> String {
+        Self::canonicalize(schema).to_string()
+    }
+
+    pub(crate) fn file_name(key: &str) -> String {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        format!("codex-output-schema-{:016x}.json", hasher.finish())
+    }
+
+    fn canonicalize(value: &Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+                Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key.clone(), Self::canonicalize(value)))
+                        .collect(),
+                )
+            }
+            Value::Array(items) => Value::Array(items.iter().map(Self::canonicalize).collect()),
+            other => other.clone(),
+        }
+    }
+
     pub fn schema_path(&self) -> Option<&Path> {
         self.schema_path.as_deref()
     }
+
+    fn check_schema(value: &Value) -> Result<(), CodexError> {
+        if !value.is_object() {
+            return Err(CodexError::InvalidOutputSchema(
+                "must be a plain JSON object".to_string(),
+            ));
+        }
+
+        #[cfg(feature = "schema-validation")]
+        jsonschema::draft7::meta::validate(value)
+            .map_err(|error| CodexError::InvalidOutputSchema(error.to_string()))?;
+
+        Ok(())
+    }
+
+    fn write_schema(path: &Path, value: &Value) -> Result<(), CodexError> {
+        std::fs::write(path, serde_json::to_vec(value)?)?;
+        log::debug!("Wrote output schema to {:?}", path);
+        Ok(())
+    }
+
+    pub fn validate_output(schema: &Value, raw: &str) -> Result<(), CodexError> {
+        let violation = |errors: Vec<String>| CodexError::OutputSchemaViolation {
+            errors,
+            raw: raw.to_string(),
+        };
+
+        let instance: Value =
+            serde_json::from_str(raw).map_err(|error| violation(vec![error.to_string()]))?;
+
+        #[cfg(feature = "schema-validation")]
+        {
+            let validator = jsonschema::draft7::new(schema)
+                .map_err(|error| CodexError::InvalidOutputSchema(error.to_string()))?;
+            let errors: Vec<String> = validator
+                .iter_errors(&instance)
+                .map(|error| error.to_string())
+                .collect();
+            if !errors.is_empty() {
+                return Err(violation(errors));
+            }
+        }
+
+        #[cfg(not(feature = "schema-validation"))]
+        {
+            let _ = (schema, instance);
+            log::debug!("schema-validation feature disabled; output only checked for valid JSON");
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,81 @@
+use crate::events::ThreadEvent;
+use crate::items::{ThreadItem, TodoItem};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlanProgress {
+    lists: Vec<(String, Vec<TodoItem>)>,
+    last_completed: Option<String>,
+}
+
+impl PlanProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn observe(&mut self, event: &ThreadEvent) -> bool {
+        match event {
+            ThreadEvent::ItemStarted { item }
+            | ThreadEvent::ItemUpdated { item }
+            | ThreadEvent::ItemCompleted { item } => self.observe_item(item),
+            _ => false,
+        }
+    }
+
+    pub fn observe_item(&mut self, item: &ThreadItem) -> bool {
+        let ThreadItem::TodoList { id, items } = item else {
+            return false;
+        };
+
+        let position = self.lists.iter().position(|(list_id, _)| list_id == id);
+        let previous = position.map(|index| &self.lists[index].1);
+        if previous == Some(items) {
+            return false;
+        }
+
+        let newly_completed = items.iter().rev().find(|step| {
+            step.completed
+                && !previous.is_some_and(|previous| {
+                    previous
+                        .iter()
+                        .any(|old| old.completed && old.text == step.text)
+                })
+        });
+        if let Some(step) = newly_completed {
+            self.last_completed = Some(step.text.clone());
+        }
+
+        match position {
+            Some(index) => self.lists[index].1 = items.clone(),
+            None => self.lists.push((id.clone(), items.clone())),
+        }
+        true
+    }
+
+    pub fn completed_count(&self) -> usize {
+        self.steps().filter(|step| step.completed).count()
+    }
+
+    pub fn total_count(&self) -> usize {
+        self.steps().count()
+    }
+
+    pub fn percent(&self) -> f64 {
+        let total = self.total_count();
+        if total == 0 {
+            return 0.0;
+        }
+        self.completed_count() as f64 * 100.0 / total as f64
+    }
+
+    pub fn last_completed(&self) -> Option<&str> {
+        self.last_completed.as_deref()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.total_count() > 0 && self.completed_count() == self.total_count()
+    }
+
+    fn steps(&self) -> impl Iterator<Item = &TodoItem> {
+        self.lists.iter().flat_map(|(_, steps)| steps.iter())
+    }
+}
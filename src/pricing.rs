@@ -0,0 +1,79 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::Usage;
+
+const TOKENS_PER_MILLION: f64 = 1_000_000.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input_per_million: f64,
+    pub cached_input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+impl ModelPricing {
+    pub const SNAPSHOT_DATE: &'static str = "2025-09-15";
+
+    pub const fn new(
+        input_per_million: f64,
+        cached_input_per_million: f64,
+        output_per_million: f64,
+    ) -> Self {
+        Self {
+            input_per_million,
+            cached_input_per_million,
+            output_per_million,
+        }
+    }
+
+    pub fn for_model(model: &str) -> Option<ModelPricing> {
+        KNOWN_MODELS
+            .iter()
+            .find(|(name, _)| *name == model)
+            .map(|(_, pricing)| *pricing)
+    }
+}
+
+const KNOWN_MODELS: &[(&str, ModelPricing)] = &[
+    ("gpt-5", ModelPricing::new(1.25, 0.125, 10.0)),
+    ("gpt-5-codex", ModelPricing::new(1.25, 0.125, 10.0)),
+    ("gpt-5-mini", ModelPricing::new(0.25, 0.025, 2.0)),
+    ("gpt-5-nano", ModelPricing::new(0.05, 0.005, 0.4)),
+    ("gpt-4.1", ModelPricing::new(2.0, 0.5, 8.0)),
+    ("o3", ModelPricing::new(2.0, 0.5, 8.0)),
+    ("o4-mini", ModelPricing::new(1.1, 0.275, 4.4)),
+    ("codex-mini-latest", ModelPricing::new(1.5, 0.375, 6.0)),
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cost {
+    pub input: f64,
+    pub cached_input: f64,
+    pub output: f64,
+}
+
+impl Cost {
+    pub fn total(&self) -> f64 {
+        self.input + self.cached_input + self.output
+    }
+}
+
+impl fmt::Display for Cost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${:.4}", self.total())
+    }
+}
+
+impl Usage {
+    pub fn estimated_cost(&self, pricing: &ModelPricing) -> Cost {
+        let uncached_input = self.input_tokens.saturating_sub(self.cached_input_tokens);
+        Cost {
+            input: uncached_input as f64 * pricing.input_per_million / TOKENS_PER_MILLION,
+            cached_input: self.cached_input_tokens as f64 * pricing.cached_input_per_million
+                / TOKENS_PER_MILLION,
+            output: self.output_tokens as f64 * pricing.output_per_million / TOKENS_PER_MILLION,
+        }
+    }
+}
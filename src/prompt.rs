@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use crate::error::CodexError;
+use crate::thread::{Input, UserInput};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Variable(String),
+    Image(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PromptTemplate {
+    segments: Vec<Segment>,
+    strict: bool,
+}
+
+impl PromptTemplate {
+    pub fn parse(source: &str) -> Result<Self, CodexError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = source;
+
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("\\{{") {
+                literal.push_str("{{");
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("{{") {
+                let end = after.find("}}").ok_or_else(|| {
+                    CodexError::InvalidTemplate(format!("unclosed placeholder in {:?}", source))
+                })?;
+                let name = after[..end].trim();
+                let segment = match name.strip_prefix("image:") {
+                    Some(image) => Segment::Image(Self::placeholder_name(image.trim())?),
+                    None => Segment::Variable(Self::placeholder_name(name)?),
+                };
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                }
+                segments.push(segment);
+                rest = &after[end + 2..];
+            } else {
+                let mut chars = rest.chars();
+                if let Some(ch) = chars.next() {
+                    literal.push(ch);
+                }
+                rest = chars.as_str();
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self {
+            segments,
+            strict: false,
+        })
+    }
+
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    pub fn variables(&self) -> Vec<&str> {
+        self.segments
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Variable(name) | Segment::Image(name) => Some(name.as_str()),
+                Segment::Literal(_) => None,
+            })
+            .collect()
+    }
+
+    pub fn render(&self, values: &HashMap<&str, &str>) -> Result<Input, CodexError> {
+        let has_images = self
+            .segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Image(_)));
+
+        let mut items = Vec::new();
+        let mut text = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => text.push_str(literal),
+                Segment::Variable(name) => {
+                    if let Some(value) = self.lookup(values, name)? {
+                        text.push_str(value);
+                    }
+                }
+                Segment::Image(name) => {
+                    if let Some(path) = self.lookup(values, name)? {
+                        Self::push_text(&mut items, &mut text);
+                        items.push(UserInput::LocalImage {
+                            path: path.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+
+        if !has_images {
+            return Ok(Input::Text(text));
+        }
+
+        Self::push_text(&mut items, &mut text);
+        Ok(Input::Structured(items))
+    }
+
+    fn lookup<'a>(
+        &self,
+        values: &HashMap<&str, &'a str>,
+        name: &str,
+    ) -> Result<Option<&'a str>, CodexError> {
+        match values.get(name) {
+            Some(value) => Ok(Some(value)),
+            None if self.strict => Err(CodexError::MissingTemplateVariable(name.to_string())),
+            None => {
+                log::debug!("Template variable {} not provided", name);
+                Ok(None)
+            }
+        }
+    }
+
+    fn push_text(items: &mut Vec<UserInput>, text: &mut String) {
+        let trimmed = text.trim();
+        if !trimmed.is_empty() {
+            items.push(UserInput::Text {
+                text: trimmed.to_string(),
+            });
+        }
+        text.clear();
+    }
+
+    fn placeholder_name(name: &str) -> Result<String, CodexError> {
+        if name.is_empty() {
+            return Err(CodexError::InvalidTemplate(
+                "placeholder names must not be empty".to_string(),
+            ));
+        }
+        Ok(name.to_string())
+    }
+}
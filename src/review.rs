@@ -0,0 +1,169 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::CodexError;
+use crate::events::Usage;
+use crate::exec::ExecSubcommand;
+use crate::items::{AgentMessageItem, ThreadItem};
+use crate::thread::Turn;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReviewRequest {
+    Uncommitted,
+    BaseBranch(String),
+    Patch(String),
+}
+
+impl ReviewRequest {
+    pub(crate) fn to_exec(&self) -> Result<(String, ExecSubcommand), CodexError> {
+        match self {
+            ReviewRequest::Uncommitted => Ok((
+                String::new(),
+                ExecSubcommand::Review {
+                    args: vec!["--uncommitted".to_string()],
+                },
+            )),
+            ReviewRequest::BaseBranch(branch) => {
+                if branch.trim().is_empty() {
+                    return Err(CodexError::InvalidArgs(
+                        "review base branch must not be empty".to_string(),
+                    ));
+                }
+                Ok((
+                    String::new(),
+                    ExecSubcommand::Review {
+                        args: vec!["--base".to_string(), branch.clone()],
+                    },
+                ))
+            }
+            ReviewRequest::Patch(patch) => {
+                if patch.trim().is_empty() {
+                    return Err(CodexError::InvalidArgs(
+                        "review patch must not be empty".to_string(),
+                    ));
+                }
+                Ok((
+                    format!(
+                        "Review the following patch and report any issues it introduces.\n\n```diff\n{}\n```",
+                        patch.trim_end()
+                    ),
+                    ExecSubcommand::Review {
+                        args: vec!["-".to_string()],
+                    },
+                ))
+            }
+        }
+    }
+}
+
+impl fmt::Display for ReviewRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReviewRequest::Uncommitted => f.write_str("uncommitted"),
+            ReviewRequest::BaseBranch(branch) => write!(f, "base={branch}"),
+            ReviewRequest::Patch(patch) => write!(f, "patch(len={})", patch.len()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ReviewLineRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ReviewCodeLocation {
+    pub absolute_file_path: PathBuf,
+    pub line_range: ReviewLineRange,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ReviewFinding {
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub confidence_score: Option<f64>,
+    #[serde(default)]
+    pub priority: Option<u8>,
+    #[serde(default)]
+    pub code_location: Option<ReviewCodeLocation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReviewOutput {
+    findings: Vec<ReviewFinding>,
+    #[serde(default)]
+    overall_correctness: Option<String>,
+    #[serde(default)]
+    overall_explanation: Option<String>,
+    #[serde(default)]
+    overall_confidence_score: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReviewResult {
+    pub findings: Vec<ReviewFinding>,
+    pub overall_correctness: Option<String>,
+    pub overall_explanation: Option<String>,
+    pub overall_confidence_score: Option<f64>,
+    pub final_response: String,
+    pub items: Vec<ThreadItem>,
+    pub usage: Option<Usage>,
+}
+
+impl ReviewResult {
+    pub fn from_turn(turn: Turn) -> Self {
+        let output = turn
+            .items
+            .iter()
+            .rev()
+            .filter_map(|item| match item {
+                ThreadItem::AgentMessage { id, text } => Self::parse_output(id, text),
+                _ => None,
+            })
+            .next();
+        if output.is_none() {
+            log::debug!("No structured review output found in turn");
+        }
+
+        let (findings, overall_correctness, overall_explanation, overall_confidence_score) =
+            match output {
+                Some(output) => (
+                    output.findings,
+                    output.overall_correctness,
+                    output.overall_explanation,
+                    output.overall_confidence_score,
+                ),
+                None => (Vec::new(), None, None, None),
+            };
+
+        Self {
+            findings,
+            overall_correctness,
+            overall_explanation,
+            overall_confidence_score,
+            final_response: turn.final_response,
+            items: turn.items,
+            usage: turn.usage,
+        }
+    }
+
+    fn parse_output(id: &str, text: &str) -> Option<ReviewOutput> {
+        if let Ok(output) = serde_json::from_str::<ReviewOutput>(text.trim()) {
+            return Some(output);
+        }
+        let message = AgentMessageItem {
+            id: id.to_string(),
+            kind: "agent_message".to_string(),
+            text: text.to_string(),
+        };
+        message
+            .code_blocks()
+            .into_iter()
+            .filter(|block| block.language.as_deref().is_none_or(|lang| lang == "json"))
+            .find_map(|block| serde_json::from_str::<ReviewOutput>(&block.source).ok())
+    }
+}
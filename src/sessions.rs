@@ -0,0 +1,451 @@
+use std::env;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::codex_options::CodexOptions;
+use crate::error::CodexError;
+use crate::items::{truncate_chars, CommandExecutionStatus, ThreadItem};
+use crate::thread::ActiveThreads;
+use crate::thread_id::ThreadId;
+
+const ARCHIVE_DIR: &str = "archived_sessions";
+
+const PREVIEW_CHARS: usize = 80;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ThreadListOrder {
+    #[default]
+    NewestFirst,
+    OldestFirst,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ThreadListFilter {
+    pub working_directory: Option<PathBuf>,
+    pub limit: Option<usize>,
+    pub order: ThreadListOrder,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThreadSummary {
+    pub id: ThreadId,
+    pub started_at: String,
+    pub cwd: Option<PathBuf>,
+    pub first_user_message_preview: Option<String>,
+    pub path: PathBuf,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum SessionRemoval {
+    Delete,
+    Archive,
+}
+
+pub(crate) fn resolve_codex_home(options: &CodexOptions) -> Result<PathBuf, CodexError> {
+    if let Some(codex_home) = &options.codex_home {
+        return Ok(codex_home.clone());
+    }
+    let from_env = match &options.env {
+        Some(vars) => vars.get("CODEX_HOME").cloned(),
+        None => env::var("CODEX_HOME").ok(),
+    };
+    if let Some(codex_home) = from_env.filter(|value| !value.is_empty()) {
+        return Ok(PathBuf::from(codex_home));
+    }
+
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".codex"))
+        .ok_or_else(|| CodexError::InvalidArgs("cannot locate CODEX_HOME".to_string()))
+}
+
+pub(crate) fn remove_session(
+    codex_home: &Path,
+    id: &ThreadId,
+    active_threads: &ActiveThreads,
+    removal: SessionRemoval,
+) -> Result<(), CodexError> {
+    let in_use = active_threads
+        .lock()
+        .map(|active| active.contains_key(id))
+        .unwrap_or(false);
+    if in_use {
+        return Err(CodexError::ThreadInUse(id.to_string()));
+    }
+
+    let files = find_session_files(codex_home, id)?;
+    if files.is_empty() {
+        return Err(CodexError::ThreadNotFound(id.to_string()));
+    }
+
+    for file in files {
+        match removal {
+            SessionRemoval::Delete => {
+                log::debug!("Deleting session file {:?}", file);
+                std::fs::remove_file(&file)?;
+            }
+            SessionRemoval::Archive => {
+                let archive_dir = codex_home.join(ARCHIVE_DIR);
+                std::fs::create_dir_all(&archive_dir)?;
+                let target = archive_dir.join(file.file_name().unwrap_or_default());
+                log::debug!("Archiving session file {:?} to {:?}", file, target);
+                std::fs::rename(&file, &target)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn fork_session(codex_home: &Path, id: &ThreadId) -> Result<ThreadId, CodexError> {
+    let files = find_session_files(codex_home, id)?;
+    if files.is_empty() {
+        return Err(CodexError::ThreadNotFound(id.to_string()));
+    }
+
+    let fork_id = ThreadId::from(uuid::Uuid::now_v7().to_string());
+    for file in files {
+        let file_name = file
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .replace(id.as_str(), fork_id.as_str());
+        let target = file.with_file_name(file_name);
+        log::debug!("Copying session file {:?} to {:?}", file, target);
+
+        let contents = std::fs::read_to_string(&file)?;
+        let mut forked = String::with_capacity(contents.len());
+        for (index, line) in contents.split_inclusive('\n').enumerate() {
+            if index == 0 {
+                forked.push_str(&rename_session_meta(line, &fork_id)?);
+            } else {
+                forked.push_str(line);
+            }
+        }
+        std::fs::write(&target, forked)?;
+    }
+    Ok(fork_id)
+}
+
+fn rename_session_meta(line: &str, fork_id: &ThreadId) -> Result<String, CodexError> {
+    let mut record: Value = serde_json::from_str(line)?;
+    let meta = match record.get("type").and_then(Value::as_str) {
+        Some("session_meta") => record.get_mut("payload"),
+        _ => Some(&mut record),
+    };
+    if let Some(Value::Object(meta)) = meta {
+        meta.insert("id".to_string(), Value::String(fork_id.to_string()));
+    }
+
+    let mut renamed = serde_json::to_string(&record)?;
+    renamed.push_str(&line[line.trim_end().len()..]);
+    Ok(renamed)
+}
+
+pub(crate) fn find_session_files(
+    codex_home: &Path,
+    id: &ThreadId,
+) -> Result<Vec<PathBuf>, CodexError> {
+    let sessions_dir = codex_home.join("sessions");
+    let mut files = Vec::new();
+    if sessions_dir.is_dir() {
+        collect_session_files(&sessions_dir, &mut files)?;
+    }
+
+    let suffix = format!("-{id}");
+    files.retain(|path| {
+        path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.ends_with(&suffix))
+    });
+    files.sort();
+    Ok(files)
+}
+
+pub(crate) fn list_sessions(
+    codex_home: &Path,
+    filter: &ThreadListFilter,
+) -> Result<Vec<ThreadSummary>, CodexError> {
+    let sessions_dir = codex_home.join("sessions");
+    log::debug!("Listing sessions under {:?}", sessions_dir);
+
+    let mut files = Vec::new();
+    if sessions_dir.is_dir() {
+        collect_session_files(&sessions_dir, &mut files)?;
+    }
+
+    let mut summaries: Vec<ThreadSummary> = files
+        .into_iter()
+        .filter_map(|path| match read_summary(&path) {
+            Ok(summary) => Some(summary),
+            Err(error) => {
+                log::warn!("Skipping session file {:?}: {}", path, error);
+                None
+            }
+        })
+        .filter(|summary| match &filter.working_directory {
+            Some(dir) => summary.cwd.as_deref() == Some(dir.as_path()),
+            None => true,
+        })
+        .collect();
+
+    summaries.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    if filter.order == ThreadListOrder::NewestFirst {
+        summaries.reverse();
+    }
+    if let Some(limit) = filter.limit {
+        summaries.truncate(limit);
+    }
+    Ok(summaries)
+}
+
+fn collect_session_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), CodexError> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if let Err(error) = collect_session_files(&path, files) {
+                log::warn!(
+                    "Skipping unreadable session directory {}: {}",
+                    path.display(),
+                    error
+                );
+            }
+        } else if path.extension().is_some_and(|ext| ext == "jsonl") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn read_summary(path: &Path) -> Result<ThreadSummary, CodexError> {
+    let mut lines = BufReader::new(File::open(path)?).lines();
+
+    let first = lines
+        .next()
+        .transpose()?
+        .ok_or_else(|| CodexError::InvalidEvent("empty session file".to_string()))?;
+    let first: Value = serde_json::from_str(&first)?;
+    let meta = match first.get("type").and_then(Value::as_str) {
+        Some("session_meta") => first.get("payload").cloned().unwrap_or(Value::Null),
+        _ => first,
+    };
+
+    let id = meta
+        .get("id")
+        .and_then(Value::as_str)
+        .ok_or_else(|| CodexError::InvalidEvent("session meta has no id".to_string()))?;
+    let id: ThreadId = id.parse()?;
+    let started_at = meta
+        .get("timestamp")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let cwd = meta.get("cwd").and_then(Value::as_str).map(PathBuf::from);
+
+    let mut preview = None;
+    for line in lines {
+        let Ok(record) = serde_json::from_str::<Value>(&line?) else {
+            continue;
+        };
+        if let Some(message) = user_message(&record) {
+            preview = Some(truncate_preview(message));
+            break;
+        }
+    }
+
+    Ok(ThreadSummary {
+        id,
+        started_at,
+        cwd,
+        first_user_message_preview: preview,
+        path: path.to_path_buf(),
+    })
+}
+
+fn user_message(record: &Value) -> Option<&str> {
+    let payload = record.get("payload")?;
+    match (
+        record.get("type").and_then(Value::as_str),
+        payload.get("type").and_then(Value::as_str),
+    ) {
+        (Some("event_msg"), Some("user_message")) => payload.get("message")?.as_str(),
+        (Some("response_item"), Some("message"))
+            if payload.get("role").and_then(Value::as_str) == Some("user") =>
+        {
+            payload
+                .get("content")?
+                .as_array()?
+                .iter()
+                .filter_map(|part| part.get("text").and_then(Value::as_str))
+                .find(|text| !text.trim_start().starts_with('<'))
+        }
+        _ => None,
+    }
+}
+
+fn truncate_preview(message: &str) -> String {
+    let line = message.trim().lines().next().unwrap_or_default();
+    truncate_chars(line, PREVIEW_CHARS)
+}
+
+pub(crate) fn read_history(files: &[PathBuf]) -> Result<Vec<ThreadItem>, CodexError> {
+    let mut items = Vec::new();
+    let mut pending_calls: Vec<(String, usize)> = Vec::new();
+
+    for path in files {
+        log::debug!("Reading history from {:?}", path);
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            let record: Value = match serde_json::from_str(&line) {
+                Ok(record) => record,
+                Err(error) => {
+                    log::warn!("Skipping malformed history line in {:?}: {}", path, error);
+                    continue;
+                }
+            };
+            if record.get("type").and_then(Value::as_str) != Some("response_item") {
+                continue;
+            }
+            let Some(payload) = record.get("payload") else {
+                continue;
+            };
+
+            let id = payload
+                .get("id")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("item_{}", items.len()));
+            match payload.get("type").and_then(Value::as_str) {
+                Some("message") => {
+                    if payload.get("role").and_then(Value::as_str) == Some("assistant") {
+                        items.push(ThreadItem::AgentMessage {
+                            id,
+                            text: joined_text(payload.get("content"), "text"),
+                        });
+                    }
+                }
+                Some("reasoning") => items.push(ThreadItem::Reasoning {
+                    id,
+                    text: joined_text(payload.get("summary"), "text"),
+                }),
+                Some("function_call") | Some("local_shell_call") => match shell_command(payload) {
+                    Some(command) => {
+                        if let Some(call_id) = payload.get("call_id").and_then(Value::as_str) {
+                            pending_calls.push((call_id.to_string(), items.len()));
+                        }
+                        items.push(ThreadItem::CommandExecution {
+                            id,
+                            command,
+                            aggregated_output: String::new(),
+                            stdout: None,
+                            stderr: None,
+                            exit_code: None,
+                            status: CommandExecutionStatus::InProgress,
+                            started_at: None,
+                            duration_ms: None,
+                            cwd: None,
+                        });
+                    }
+                    None => items.push(ThreadItem::Other),
+                },
+                Some("function_call_output") => {
+                    let call_id = payload.get("call_id").and_then(Value::as_str);
+                    let Some(position) = pending_calls
+                        .iter()
+                        .position(|(pending, _)| Some(pending.as_str()) == call_id)
+                    else {
+                        continue;
+                    };
+                    let (_, index) = pending_calls.remove(position);
+                    if let Some(ThreadItem::CommandExecution {
+                        aggregated_output,
+                        exit_code,
+                        status,
+                        ..
+                    }) = items.get_mut(index)
+                    {
+                        let (output, code) = call_output(payload.get("output"));
+                        *aggregated_output = output;
+                        *exit_code = code;
+                        *status = match code {
+                            Some(0) | None => CommandExecutionStatus::Completed,
+                            Some(_) => CommandExecutionStatus::Failed,
+                        };
+                    }
+                }
+                Some("web_search_call") => items.push(ThreadItem::WebSearch {
+                    id,
+                    query: payload
+                        .pointer("/action/query")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    result: None,
+                    results: Vec::new(),
+                }),
+                _ => items.push(ThreadItem::Other),
+            }
+        }
+    }
+
+    Ok(items)
+}
+
+fn joined_text(parts: Option<&Value>, field: &str) -> String {
+    parts
+        .and_then(Value::as_array)
+        .map(|parts| {
+            parts
+                .iter()
+                .filter_map(|part| part.get(field).and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+fn shell_command(payload: &Value) -> Option<String> {
+    let command = match payload.get("type").and_then(Value::as_str) {
+        Some("local_shell_call") => payload.pointer("/action/command")?.clone(),
+        _ => {
+            if payload.get("name").and_then(Value::as_str) != Some("shell") {
+                return None;
+            }
+            let arguments: Value =
+                serde_json::from_str(payload.get("arguments")?.as_str()?).ok()?;
+            arguments.get("command")?.clone()
+        }
+    };
+
+    let parts: Vec<&str> = command
+        .as_array()?
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    match parts.as_slice() {
+        [shell, "-lc" | "-c", script] if shell.ends_with("sh") => Some(script.to_string()),
+        parts => Some(parts.join(" ")),
+    }
+}
+
+fn call_output(output: Option<&Value>) -> (String, Option<i32>) {
+    let Some(raw) = output.and_then(Value::as_str) else {
+        return (String::new(), None);
+    };
+    match serde_json::from_str::<Value>(raw) {
+        Ok(parsed) if parsed.get("output").is_some() => (
+            parsed
+                .get("output")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            parsed
+                .pointer("/metadata/exit_code")
+                .and_then(Value::as_i64)
+                .and_then(|code| i32::try_from(code).ok()),
+        ),
+        _ => (raw.to_string(), None),
+    }
+}
@@ -0,0 +1,118 @@
+use std::pin::Pin;
+use std::time::Duration;
+
+use futures::{Stream, StreamExt};
+
+use crate::error::CodexError;
+use crate::events::ThreadEvent;
+use crate::exec::CodexLineStream;
+use crate::plan_progress::PlanProgress;
+use crate::thread::ThreadEventStream;
+
+pub type PlanProgressStream = Pin<Box<dyn Stream<Item = Result<PlanProgress, CodexError>> + Send>>;
+
+pub trait ThreadEventStreamExt: Stream<Item = Result<ThreadEvent, CodexError>> {
+    fn plan_progress(self) -> PlanProgressStream;
+}
+
+impl<S> ThreadEventStreamExt for S
+where
+    S: Stream<Item = Result<ThreadEvent, CodexError>> + Send + 'static,
+{
+    fn plan_progress(self) -> PlanProgressStream {
+        Box::pin(async_stream::try_stream! {
+            let mut events = Box::pin(self);
+            let mut progress = PlanProgress::new();
+            while let Some(event) = events.next().await {
+                if progress.observe(&event?) {
+                    log::debug!(
+                        "Plan progress: {}/{}",
+                        progress.completed_count(),
+                        progress.total_count()
+                    );
+                    yield progress.clone();
+                }
+            }
+        })
+    }
+}
+
+pub fn with_line_timeout(stream: CodexLineStream, timeout: Duration) -> CodexLineStream {
+    Box::pin(async_stream::stream! {
+        let mut stream = stream;
+        loop {
+            match tokio::time::timeout(timeout, stream.next()).await {
+                Ok(Some(line)) => yield line,
+                Ok(None) => break,
+                Err(_) => {
+                    log::debug!("No codex output within {:?}", timeout);
+                    yield Err(CodexError::Timeout);
+                    break;
+                }
+            }
+        }
+    })
+}
+
+pub(crate) fn with_heartbeat(stream: ThreadEventStream, interval: Duration) -> ThreadEventStream {
+    Box::pin(async_stream::stream! {
+        let mut stream = stream;
+        let mut last_event = tokio::time::Instant::now();
+        let mut finished = false;
+        loop {
+            if finished {
+                match stream.next().await {
+                    Some(event) => yield event,
+                    None => break,
+                }
+                continue;
+            }
+            match tokio::time::timeout(interval, stream.next()).await {
+                Ok(Some(event)) => {
+                    finished = matches!(&event, Ok(event) if event.is_terminal());
+                    last_event = tokio::time::Instant::now();
+                    yield event;
+                }
+                Ok(None) => break,
+                Err(_) => {
+                    let elapsed_ms = last_event.elapsed().as_millis() as u64;
+                    log::debug!("No codex event for {}ms; sending heartbeat", elapsed_ms);
+                    yield Ok(ThreadEvent::Heartbeat { elapsed_ms });
+                }
+            }
+        }
+    })
+}
+
+pub fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\u{1b}' {
+            stripped.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                for ch in chars.by_ref() {
+                    if ('@'..='~').contains(&ch) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(ch) = chars.next() {
+                    if ch == '\u{7}' {
+                        break;
+                    }
+                    if ch == '\u{1b}' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    stripped
+}
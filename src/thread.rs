@@ -1,39 +1,310 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
 use async_stream::try_stream;
 use futures::{Stream, StreamExt};
+use serde_json::Value;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tokio_util::sync::{CancellationToken, DropGuard};
 
+#[cfg(feature = "app-server")]
+use crate::app_server::AppServer;
 use crate::codex_options::CodexOptions;
-use crate::error::CodexError;
-use crate::events::{ThreadError, ThreadEvent, Usage};
-use crate::exec::{CodexExec, CodexExecArgs};
-use crate::items::ThreadItem;
+use crate::error::{BudgetKind, CodexError};
+use crate::events::{ApprovalDecision, RateLimitSnapshot, ThreadError, ThreadEvent, Usage};
+use crate::exec::{CodexExec, CodexExecArgs, CodexLineStream, ExecSubcommand};
+use crate::image_files::{ImageFiles, ImageFormat};
+use crate::items::{truncate_chars, ThreadItem};
+use crate::output_last_message_file::OutputLastMessageFile;
 use crate::output_schema_file::OutputSchemaFile;
-use crate::thread_options::ThreadOptions;
-use crate::turn_options::TurnOptions;
+use crate::pricing::{Cost, ModelPricing};
+use crate::review::{ReviewRequest, ReviewResult};
+use crate::sessions::{self, SessionRemoval};
+use crate::stream_util::with_heartbeat;
+use crate::thread_id::ThreadId;
+use crate::thread_options::{ApprovalMode, FileInputLimits, ThreadOptions};
+use crate::turn_options::{link_cancellation, FinalResponseMode, TurnOptions};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Turn {
     pub items: Vec<ThreadItem>,
     pub final_response: String,
     pub usage: Option<Usage>,
+    pub skipped_lines: usize,
+    pub pricing: Option<ModelPricing>,
+    pub rate_limits: Option<RateLimitSnapshot>,
+    pub auto_compacted: bool,
+}
+
+impl Turn {
+    pub fn first_error(&self) -> Option<&ThreadItem> {
+        self.items.iter().find(|item| match item {
+            ThreadItem::Error { .. } => true,
+            ThreadItem::CommandExecution { exit_code, .. } => {
+                exit_code.is_some_and(|code| code != 0)
+            }
+            _ => false,
+        })
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.first_error().is_some()
+    }
+
+    pub fn combined_diff(&self) -> Option<String> {
+        let changes = self
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ThreadItem::FileChange { changes, .. } => Some(changes),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|change| Some((change.path.as_str(), change.diff.as_deref()?)))
+            .filter(|(_, diff)| !diff.is_empty());
+
+        let mut by_path: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (path, diff) in changes {
+            let diffs = by_path.entry(path).or_default();
+            if !diffs.contains(&diff) {
+                diffs.push(diff);
+            }
+        }
+        if by_path.is_empty() {
+            return None;
+        }
+
+        let mut combined = String::new();
+        for diffs in by_path.values() {
+            for (index, diff) in diffs.iter().enumerate() {
+                let diff = if index == 0 { diff } else { diff_hunks(diff) };
+                combined.push_str(diff);
+                if !diff.is_empty() && !diff.ends_with('\n') {
+                    combined.push('\n');
+                }
+            }
+        }
+        Some(combined)
+    }
+
+    pub fn citations(&self) -> Vec<&str> {
+        let mut citations: Vec<&str> = Vec::new();
+        for item in &self.items {
+            let ThreadItem::WebSearch {
+                result, results, ..
+            } = item
+            else {
+                continue;
+            };
+            let urls = result
+                .iter()
+                .flat_map(|result| result.urls.iter())
+                .chain(results.iter().map(|hit| &hit.url))
+                .filter(|url| !url.is_empty());
+            for url in urls {
+                if !citations.contains(&url.as_str()) {
+                    citations.push(url);
+                }
+            }
+        }
+        citations
+    }
+
+    pub fn slowest_commands(&self, n: usize) -> Vec<&ThreadItem> {
+        let mut commands: Vec<(u64, &ThreadItem)> = self
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ThreadItem::CommandExecution {
+                    duration_ms: Some(duration_ms),
+                    ..
+                } => Some((*duration_ms, item)),
+                _ => None,
+            })
+            .collect();
+        commands.sort_by_key(|(duration_ms, _)| std::cmp::Reverse(*duration_ms));
+        commands.into_iter().take(n).map(|(_, item)| item).collect()
+    }
+
+    pub fn estimated_cost(&self) -> Option<Cost> {
+        Some(self.usage.as_ref()?.estimated_cost(self.pricing.as_ref()?))
+    }
 }
 
 pub type RunResult = Turn;
 
 pub type ThreadEventStream = Pin<Box<dyn Stream<Item = Result<ThreadEvent, CodexError>> + Send>>;
 
+pub(crate) type ApprovalResponder =
+    Arc<dyn Fn(&str, ApprovalDecision) -> Result<(), CodexError> + Send + Sync>;
+
 pub struct StreamedTurn {
     pub events: ThreadEventStream,
+    skipped_lines: Arc<AtomicUsize>,
+    final_response_mode: FinalResponseMode,
+    last_message_file: Arc<OutputLastMessageFile>,
+    output_validation_schema: Option<Value>,
+    approval_responder: Option<ApprovalResponder>,
+    pricing: Option<ModelPricing>,
+    rate_limits: Arc<Mutex<Option<RateLimitSnapshot>>>,
+}
+
+impl StreamedTurn {
+    pub fn respond_approval(&self, id: &str, decision: ApprovalDecision) -> Result<(), CodexError> {
+        let responder = self
+            .approval_responder
+            .as_ref()
+            .ok_or(CodexError::ApprovalsUnsupported)?;
+        log::debug!("Answering approval {} with {}", id, decision);
+        responder(id, decision)
+    }
+
+    pub fn rate_limits(&self) -> Option<RateLimitSnapshot> {
+        self.rate_limits
+            .lock()
+            .ok()
+            .and_then(|rate_limits| rate_limits.clone())
+    }
+
+    pub async fn collect(self) -> Result<Vec<ThreadEvent>, CodexError> {
+        let mut events = self.events;
+        let mut collected = Vec::new();
+        while let Some(event) = events.next().await {
+            collected.push(event?);
+        }
+        Ok(collected)
+    }
+
+    pub async fn collect_turn(self) -> Result<Turn, CodexError> {
+        self.collect_turn_with(|_| {}).await
+    }
+
+    async fn collect_turn_with(
+        self,
+        mut on_item: impl FnMut(&ThreadItem),
+    ) -> Result<Turn, CodexError> {
+        let mut events = self.events;
+        let mut items = Vec::new();
+        let mut messages: Vec<String> = Vec::new();
+        let mut usage: Option<Usage> = None;
+        let mut rate_limits: Option<RateLimitSnapshot> = None;
+        let mut turn_failure: Option<ThreadError> = None;
+        let mut streamed_text: HashMap<String, String> = HashMap::new();
+
+        while let Some(event) = events.next().await {
+            let event = event?;
+            match event {
+                ThreadEvent::ItemDelta { item_id, delta } => {
+                    if let Some(text) = delta.text() {
+                        streamed_text.entry(item_id).or_default().push_str(text);
+                    }
+                }
+                ThreadEvent::ItemCompleted { item } => {
+                    let item = match streamed_text.remove(item.id()) {
+                        Some(text) => item.fill_streamed_text(text),
+                        None => item,
+                    };
+                    if let ThreadItem::AgentMessage { text, .. } = &item {
+                        messages.push(text.clone());
+                    }
+                    on_item(&item);
+                    items.push(item);
+                }
+                ThreadEvent::TurnCompleted {
+                    usage: event_usage,
+                    rate_limits: event_rate_limits,
+                } => {
+                    usage = Some(event_usage);
+                    rate_limits = event_rate_limits;
+                    log::debug!("Turn completed");
+                }
+                ThreadEvent::TurnStarted => {
+                    if let Some(error) = turn_failure.take() {
+                        log::debug!("Turn restarted after retryable failure: {}", error.message);
+                    }
+                }
+                ThreadEvent::TurnFailed { error } if error.is_retrying() => {
+                    log::debug!(
+                        "Turn failed on attempt {:?}; waiting for retry",
+                        error.attempt
+                    );
+                    turn_failure = Some(error);
+                }
+                ThreadEvent::TurnFailed { error } => {
+                    turn_failure = Some(error);
+                    log::debug!("Turn failed");
+                    break;
+                }
+                ThreadEvent::ApprovalRequested { id, kind, .. } => {
+                    log::warn!("Denying {} approval {}; nobody can answer it", kind, id);
+                    if let Some(responder) = &self.approval_responder {
+                        responder(&id, ApprovalDecision::Deny)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(error) = turn_failure {
+            return Err(CodexError::TurnFailed(error.message));
+        }
+
+        let final_response = if messages.is_empty() {
+            self.last_message_file.read().unwrap_or_default()
+        } else {
+            match self.final_response_mode {
+                FinalResponseMode::LastMessage => messages.pop().unwrap_or_default(),
+                FinalResponseMode::FirstMessage => messages.into_iter().next().unwrap_or_default(),
+                FinalResponseMode::Concatenate { separator } => messages.join(&separator),
+            }
+        };
+
+        if let Some(schema) = &self.output_validation_schema {
+            OutputSchemaFile::validate_output(schema, &final_response)?;
+        }
+
+        Ok(Turn {
+            items,
+            final_response,
+            usage,
+            skipped_lines: self.skipped_lines.load(Ordering::Relaxed),
+            pricing: self.pricing,
+            rate_limits,
+            auto_compacted: false,
+        })
+    }
 }
 
 pub type RunStreamedResult = StreamedTurn;
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimestampedThreadItem {
+    pub item: ThreadItem,
+    pub received_at: SystemTime,
+}
+
+pub type TimestampedItemStream =
+    Pin<Box<dyn Stream<Item = Result<TimestampedThreadItem, CodexError>> + Send>>;
+
+pub type ItemSubscription = (
+    mpsc::Receiver<ThreadItem>,
+    JoinHandle<Result<Turn, CodexError>>,
+);
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum UserInput {
     Text { text: String },
     LocalImage { path: String },
+    ImageUrl { url: String },
+    ImageBytes { data: Vec<u8>, format: ImageFormat },
+    File { path: String },
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -42,6 +313,101 @@ pub enum Input {
     Structured(Vec<UserInput>),
 }
 
+const INPUT_PREVIEW_CHARS: usize = 100;
+
+const SCHEMA_CACHE_CAPACITY: usize = 16;
+
+fn preview(text: &str) -> String {
+    truncate_chars(text, INPUT_PREVIEW_CHARS)
+}
+
+fn diff_hunks(diff: &str) -> &str {
+    if diff.starts_with("@@") {
+        return diff;
+    }
+    diff.find("\n@@").map_or("", |start| &diff[start + 1..])
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+impl fmt::Display for UserInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserInput::Text { text } => write!(f, "text: {}", preview(text)),
+            UserInput::LocalImage { path } => write!(f, "image: {path}"),
+            UserInput::ImageUrl { url } => write!(f, "image_url: {url}"),
+            UserInput::ImageBytes { data, format } => {
+                write!(f, "image_bytes: {} bytes ({format})", data.len())
+            }
+            UserInput::File { path } => write!(f, "file: {path}"),
+        }
+    }
+}
+
+impl Input {
+    pub fn char_count(&self) -> usize {
+        match self {
+            Input::Text(text) => text.chars().count(),
+            Input::Structured(items) => items
+                .iter()
+                .map(|item| match item {
+                    UserInput::Text { text } => text.chars().count(),
+                    _ => 0,
+                })
+                .sum(),
+        }
+    }
+
+    pub fn truncated(&self, max_chars: usize) -> Input {
+        match self {
+            Input::Text(text) => Input::Text(truncate_chars(text, max_chars)),
+            Input::Structured(items) => {
+                let mut remaining = max_chars;
+                let items = items
+                    .iter()
+                    .filter_map(|item| match item {
+                        UserInput::Text { text: original } => {
+                            let text = truncate_chars(original, remaining);
+                            remaining -= text.chars().count();
+                            (text.is_empty() == original.is_empty())
+                                .then_some(UserInput::Text { text })
+                        }
+                        other => Some(other.clone()),
+                    })
+                    .collect();
+                Input::Structured(items)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Input::Text(text) => f.write_str(&preview(text)),
+            Input::Structured(items) => {
+                let text_parts = items
+                    .iter()
+                    .filter(|item| matches!(item, UserInput::Text { .. } | UserInput::File { .. }))
+                    .count();
+                let images = items.len() - text_parts;
+                write!(
+                    f,
+                    "[{}, {}]",
+                    plural(text_parts, "text part"),
+                    plural(images, "image")
+                )
+            }
+        }
+    }
+}
+
 impl From<&str> for Input {
     fn from(value: &str) -> Self {
         Input::Text(value.to_string())
@@ -54,12 +420,117 @@ impl From<String> for Input {
     }
 }
 
+const COMPACT_PROMPT: &str = "Summarize the conversation so far as a compact handoff: the goal, decisions made, files touched, and remaining work. Later turns will rely on this summary instead of the full history.";
+const COMPACT_SEED_PROMPT: &str = "This conversation continues an earlier one that was compacted. Its summary follows; acknowledge it briefly and wait for the next request.";
+
 #[derive(Clone, Debug)]
 pub struct Thread {
     exec: CodexExec,
     options: CodexOptions,
-    id: Arc<Mutex<Option<String>>>,
+    id: Arc<Mutex<Option<ThreadId>>>,
     thread_options: ThreadOptions,
+    running: Arc<AtomicBool>,
+    schema_cache: SchemaCache,
+    turns: Arc<Mutex<Vec<Turn>>>,
+    resume_last: bool,
+    compaction_due: Arc<AtomicBool>,
+    active_threads: ActiveThreads,
+    #[cfg(feature = "app-server")]
+    app_server: Option<AppServer>,
+}
+
+impl fmt::Display for Thread {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id = self
+            .id()
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "unstarted".to_string());
+        let model = self.thread_options.model.as_deref().unwrap_or("default");
+        let sandbox = self
+            .thread_options
+            .sandbox_mode
+            .as_ref()
+            .map(|mode| mode.as_str())
+            .unwrap_or("default");
+        write!(f, "Thread(id={id}, model={model}, sandbox={sandbox})")
+    }
+}
+
+pub(crate) type ActiveThreads = Arc<Mutex<HashMap<ThreadId, usize>>>;
+
+type SchemaCache = Arc<Mutex<VecDeque<(String, Arc<OutputSchemaFile>)>>>;
+
+struct RunningGuard {
+    running: Arc<AtomicBool>,
+    active_threads: ActiveThreads,
+    registered: Vec<ThreadId>,
+}
+
+impl RunningGuard {
+    fn new(
+        running: Arc<AtomicBool>,
+        active_threads: ActiveThreads,
+        id: Option<ThreadId>,
+    ) -> Result<Self, CodexError> {
+        if running
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err(CodexError::ConcurrentTurnNotAllowed);
+        }
+        let mut guard = Self {
+            running,
+            active_threads,
+            registered: Vec::new(),
+        };
+        if let Some(id) = id {
+            guard.register(id);
+        }
+        Ok(guard)
+    }
+
+    fn register(&mut self, id: ThreadId) {
+        if self.registered.contains(&id) {
+            return;
+        }
+        if let Ok(mut active) = self.active_threads.lock() {
+            *active.entry(id.clone()).or_default() += 1;
+        }
+        self.registered.push(id);
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Ok(mut active) = self.active_threads.lock() {
+            for id in self.registered.drain(..) {
+                if let Some(count) = active.get_mut(&id) {
+                    *count -= 1;
+                    if *count == 0 {
+                        active.remove(&id);
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct TurnGuards {
+    running: RunningGuard,
+    _schema_file: Arc<OutputSchemaFile>,
+    _image_files: ImageFiles,
+    _last_message_file: Arc<OutputLastMessageFile>,
+    _cancel_guard: Option<DropGuard>,
+}
+
+struct PreparedTurn {
+    lines: CodexLineStream,
+    approval_responder: Option<ApprovalResponder>,
+    guards: TurnGuards,
+    turn_options: TurnOptions,
+    last_message_file: Arc<OutputLastMessageFile>,
+    tracks_thread: bool,
 }
 
 impl Thread {
@@ -67,167 +538,921 @@ impl Thread {
         exec: CodexExec,
         options: CodexOptions,
         thread_options: ThreadOptions,
-        id: Option<String>,
+        id: Option<ThreadId>,
+        active_threads: ActiveThreads,
     ) -> Self {
         Self {
             exec,
             options,
             id: Arc::new(Mutex::new(id)),
             thread_options,
+            running: Arc::new(AtomicBool::new(false)),
+            schema_cache: Arc::new(Mutex::new(VecDeque::new())),
+            turns: Arc::new(Mutex::new(Vec::new())),
+            resume_last: false,
+            compaction_due: Arc::new(AtomicBool::new(false)),
+            active_threads,
+            #[cfg(feature = "app-server")]
+            app_server: None,
         }
     }
 
-    pub fn id(&self) -> Option<String> {
+    #[cfg(feature = "app-server")]
+    pub(crate) fn with_app_server(mut self, app_server: Option<AppServer>) -> Self {
+        self.app_server = app_server;
+        self
+    }
+
+    pub(crate) fn resuming_last(mut self) -> Self {
+        self.resume_last = true;
+        self
+    }
+
+    pub fn id(&self) -> Option<ThreadId> {
         self.id.lock().ok().and_then(|guard| guard.clone())
     }
 
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn thread_options(&self) -> &ThreadOptions {
+        &self.thread_options
+    }
+
+    pub fn codex_options(&self) -> &CodexOptions {
+        &self.options
+    }
+
+    pub fn fork(&self) -> Result<Thread, CodexError> {
+        log::debug!("Forking thread {:?}", self.id());
+        let fork_id = match self.id() {
+            Some(id) => Some(sessions::fork_session(
+                &sessions::resolve_codex_home(&self.options)?,
+                &id,
+            )?),
+            None if self.resume_last => {
+                return Err(CodexError::InvalidArgs(
+                    "cannot fork a resume_last thread before its first turn".to_string(),
+                ))
+            }
+            None => None,
+        };
+        #[cfg_attr(not(feature = "app-server"), allow(unused_mut))]
+        let mut fork = Thread::new(
+            self.exec.clone(),
+            self.options.clone(),
+            self.thread_options.clone(),
+            fork_id,
+            self.active_threads.clone(),
+        );
+        #[cfg(feature = "app-server")]
+        {
+            fork.app_server = self.app_server.clone();
+        }
+        Ok(fork)
+    }
+
+    pub fn delete(self) -> Result<(), CodexError> {
+        self.remove_session(SessionRemoval::Delete)
+    }
+
+    pub fn archive(self) -> Result<(), CodexError> {
+        self.remove_session(SessionRemoval::Archive)
+    }
+
+    pub fn turn_history(&self) -> Vec<Turn> {
+        self.turns
+            .lock()
+            .map(|turns| turns.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn clear_turn_history(&self) {
+        if let Ok(mut turns) = self.turns.lock() {
+            turns.clear();
+        }
+    }
+
+    fn record_turn(turns: &Mutex<Vec<Turn>>, max_turns: Option<usize>, turn: &Turn) {
+        if let Ok(mut turns) = turns.lock() {
+            turns.push(turn.clone());
+            if let Some(max_turns) = max_turns {
+                let excess = turns.len().saturating_sub(max_turns);
+                turns.drain(..excess);
+            }
+        }
+    }
+
+    pub async fn history(&self) -> Result<Vec<ThreadItem>, CodexError> {
+        let Some(id) = self.id() else {
+            return Ok(Vec::new());
+        };
+        let codex_home = sessions::resolve_codex_home(&self.options)?;
+
+        tokio::task::spawn_blocking(move || {
+            let files = sessions::find_session_files(&codex_home, &id)?;
+            log::debug!("History files for {}: {:?}", id, files);
+            sessions::read_history(&files)
+        })
+        .await
+        .map_err(|error| CodexError::Io(std::io::Error::other(error)))?
+    }
+
+    fn remove_session(&self, removal: SessionRemoval) -> Result<(), CodexError> {
+        let id = self
+            .id()
+            .ok_or_else(|| CodexError::InvalidArgs("thread has no id".to_string()))?;
+        if self.is_running() {
+            return Err(CodexError::ThreadInUse(id.into_string()));
+        }
+        sessions::remove_session(
+            &sessions::resolve_codex_home(&self.options)?,
+            &id,
+            &self.active_threads,
+            removal,
+        )
+    }
+
     pub fn run_streamed(
         &self,
         input: Input,
         turn_options: TurnOptions,
     ) -> Result<StreamedTurn, CodexError> {
-        let events = self.run_streamed_internal(input, turn_options)?;
-        Ok(StreamedTurn { events })
+        self.run_streamed_internal(input, turn_options, None)
     }
 
-    fn run_streamed_internal(
+    pub fn run_streamed_raw(
         &self,
         input: Input,
         turn_options: TurnOptions,
-    ) -> Result<ThreadEventStream, CodexError> {
+    ) -> Result<CodexLineStream, CodexError> {
+        if self
+            .thread_options
+            .approval_policy
+            .as_ref()
+            .is_some_and(ApprovalMode::is_interactive)
+        {
+            return Err(CodexError::ApprovalsUnsupported);
+        }
+        let PreparedTurn {
+            mut lines,
+            guards,
+            tracks_thread,
+            ..
+        } = self.prepare_turn(input, turn_options, None)?;
+        let thread_id_handle = self.id.clone();
+
+        let stream = try_stream! {
+            let mut guards = guards;
+            while let Some(line) = lines.next().await {
+                let line = line?;
+                if let Ok(ThreadEvent::ThreadStarted { thread_id }) = serde_json::from_str(&line) {
+                    if tracks_thread {
+                        if let Ok(mut guard) = thread_id_handle.lock() {
+                            *guard = Some(thread_id.clone());
+                        }
+                    }
+                    guards.running.register(thread_id);
+                }
+                yield line;
+            }
+        };
+        Ok(Box::pin(stream))
+    }
+
+    fn prepare_turn(
+        &self,
+        input: Input,
+        turn_options: TurnOptions,
+        subcommand: Option<ExecSubcommand>,
+    ) -> Result<PreparedTurn, CodexError> {
         log::debug!("Running thread with input: {:?}", input);
-        log::debug!("Thread options: {:?}", self.thread_options);
+        log::debug!("Thread options: {}", self.thread_options);
+        if self
+            .thread_options
+            .dangerously_bypass_approvals_and_sandbox
+            .unwrap_or(false)
+            && !self.options.allow_dangerous_bypass.unwrap_or(false)
+        {
+            return Err(CodexError::DangerousBypassNotAllowed);
+        }
+        let running_guard =
+            RunningGuard::new(self.running.clone(), self.active_threads.clone(), self.id())?;
+        let (turn_options, cancel_guard) = self.resolve_turn_options(turn_options);
 
-        let schema_file = OutputSchemaFile::new(turn_options.output_schema.as_ref())?;
+        let schema_file = self.schema_file(turn_options.output_schema.as_ref())?;
         log::debug!(
             "Output schema path: {:?}",
             schema_file.schema_path().map(|path| path.to_path_buf())
         );
+        let last_message_file = Arc::new(OutputLastMessageFile::new(
+            turn_options.output_last_message.as_ref(),
+        )?);
 
-        let (prompt, images) = Self::normalize_input(&input);
+        let limits = self
+            .thread_options
+            .file_input_limits
+            .clone()
+            .unwrap_or_default();
+        let (prompt, images, image_files) = Self::normalize_input_with_limits(&input, &limits)?;
         log::debug!("Normalized input {}, images: {}", prompt, images.len());
+        let prompt = match turn_options.preamble.as_deref() {
+            Some(preamble) if !preamble.is_empty() => format!("{preamble}\n\n{prompt}"),
+            _ => prompt,
+        };
+        let prompt = match self.thread_options.append_instructions.as_deref() {
+            Some(instructions) if !instructions.is_empty() && !prompt.is_empty() => {
+                format!("{prompt}\n\n{instructions}")
+            }
+            _ => prompt,
+        };
 
-        let thread_id = self.id();
-        log::debug!("Thread id: {:?}", thread_id);
+        let tracks_thread = subcommand.is_none();
+        let thread_id = self.id().filter(|_| tracks_thread);
+        let resume_last = tracks_thread && thread_id.is_none() && self.resume_last;
+        log::debug!("Thread id: {:?}, resume last: {}", thread_id, resume_last);
 
-        let exec_args = CodexExecArgs {
+        let mut exec_args = CodexExecArgs {
             input: prompt,
             base_url: self.options.base_url.clone(),
             api_key: self.options.api_key.clone(),
             thread_id,
+            resume_last,
             images: if images.is_empty() {
                 None
             } else {
                 Some(images)
             },
             model: self.thread_options.model.clone(),
+            model_provider: self.thread_options.model_provider.clone(),
+            oss: self.thread_options.oss,
+            local_model_server: self.thread_options.local_model_server.clone(),
+            profile: turn_options
+                .profile
+                .clone()
+                .or_else(|| self.thread_options.profile.clone()),
             sandbox_mode: self.thread_options.sandbox_mode.clone(),
             working_directory: self.thread_options.working_directory.clone(),
-            additional_directories: self.thread_options.additional_directories.clone(),
+            additional_directories: Self::merge_directories(
+                self.thread_options.additional_directories.as_ref(),
+                turn_options.additional_directories.as_ref(),
+            ),
             skip_git_repo_check: self.thread_options.skip_git_repo_check,
             output_schema_file: schema_file.schema_path().map(|path| path.to_path_buf()),
-            model_reasoning_effort: self.thread_options.model_reasoning_effort.clone(),
+            output_last_message_file: last_message_file.path().map(|path| path.to_path_buf()),
+            model_reasoning_effort: turn_options
+                .model_reasoning_effort
+                .clone()
+                .or_else(|| self.thread_options.model_reasoning_effort.clone()),
+            model_verbosity: self.thread_options.model_verbosity.clone(),
+            reasoning_summary: self.thread_options.reasoning_summary.clone(),
             cancel: turn_options.cancel.clone(),
+            #[allow(deprecated)]
             network_access_enabled: self.thread_options.network_access_enabled,
+            sandbox_workspace_write: self.thread_options.sandbox_workspace_write.clone(),
+            shell_environment_policy: self.thread_options.shell_environment_policy.clone(),
             web_search_mode: self.thread_options.web_search_mode.clone(),
             web_search_enabled: self.thread_options.web_search_enabled,
+            tools: self.thread_options.tools.clone(),
             approval_policy: self.thread_options.approval_policy.clone(),
+            base_instructions: self.thread_options.base_instructions.clone(),
+            dangerously_bypass_approvals_and_sandbox: self
+                .thread_options
+                .dangerously_bypass_approvals_and_sandbox,
+            config: HashMap::new(),
+            subcommand,
         };
+        if let Some(disable) = self.thread_options.disable_response_storage {
+            exec_args.extend_config("disable_response_storage", Value::Bool(disable));
+        }
+        if let Some(window) = self.thread_options.model_context_window {
+            exec_args.extend_config("model_context_window", Value::from(window));
+        }
         log::debug!("Exec args: {}", exec_args);
 
-        let mut lines = self.exec.run(exec_args)?;
+        let (lines, approval_responder) = self.spawn_lines(exec_args)?;
+        Ok(PreparedTurn {
+            lines,
+            approval_responder,
+            guards: TurnGuards {
+                running: running_guard,
+                _schema_file: schema_file,
+                _image_files: image_files,
+                _last_message_file: last_message_file.clone(),
+                _cancel_guard: cancel_guard,
+            },
+            turn_options,
+            last_message_file,
+            tracks_thread,
+        })
+    }
+
+    fn run_streamed_internal(
+        &self,
+        input: Input,
+        turn_options: TurnOptions,
+        subcommand: Option<ExecSubcommand>,
+    ) -> Result<StreamedTurn, CodexError> {
+        let PreparedTurn {
+            mut lines,
+            approval_responder,
+            guards,
+            turn_options,
+            last_message_file,
+            tracks_thread,
+        } = self.prepare_turn(input, turn_options, subcommand)?;
         let thread_id_handle = self.id.clone();
+        let skipped_lines = Arc::new(AtomicUsize::new(0));
+        let skipped_lines_handle = skipped_lines.clone();
+        let rate_limits = Arc::new(Mutex::new(None));
+        let rate_limits_handle = rate_limits.clone();
+        let lenient_parsing = turn_options
+            .lenient_parsing
+            .unwrap_or(self.thread_options.lenient_parsing);
+        let recover_unknown_events = turn_options.recover_unknown_events.unwrap_or(false);
+        let max_output_items = turn_options.max_output_items;
+        let usage_budget = turn_options.usage_budget;
+        let pricing = self
+            .thread_options
+            .model
+            .as_deref()
+            .and_then(|model| self.options.pricing_for(model));
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::span!(
+            tracing::Level::DEBUG,
+            "turn",
+            thread_id = tracing::field::Empty
+        );
+        #[cfg(feature = "tracing")]
+        if let Some(thread_id) = self.id() {
+            span.record("thread_id", thread_id.as_str());
+        }
 
         let stream = try_stream! {
-            let _schema_guard = schema_file;
+            let mut guards = guards;
+            let mut completed_items = 0usize;
             while let Some(line) = lines.next().await {
                 let line = line?;
-                let parsed: ThreadEvent = serde_json::from_str(&line)
-                    .map_err(|_| CodexError::InvalidEvent(line.clone()))?;
+                #[cfg(feature = "tracing")]
+                let entered = span.enter();
+                let parsed: ThreadEvent = match serde_json::from_str(&line) {
+                    Ok(event) => event,
+                    Err(_) if lenient_parsing && !Self::is_json_object(&line) => {
+                        log::warn!("Skipping non-JSON line: {}", line);
+                        skipped_lines_handle.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    }
+                    Err(_) if recover_unknown_events => {
+                        log::warn!("Skipping unrecognized event: {}", line);
+                        skipped_lines_handle.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    }
+                    Err(_) => Err(CodexError::InvalidEvent(line.clone()))?,
+                };
 
-                log::debug!("Received event: {}", Self::event_type(&parsed));
+                log::debug!("Received event: {}", parsed.event_type());
+                #[cfg(feature = "tracing")]
+                tracing::debug!(event = parsed.event_type(), "Received event");
 
                 if let ThreadEvent::ThreadStarted { thread_id } = &parsed {
-                    if let Ok(mut guard) = thread_id_handle.lock() {
-                        *guard = Some(thread_id.clone());
+                    if tracks_thread {
+                        if let Ok(mut guard) = thread_id_handle.lock() {
+                            *guard = Some(thread_id.clone());
+                        }
                     }
+                    guards.running.register(thread_id.clone());
                     log::debug!("Thread started: {}", thread_id);
+                    #[cfg(feature = "tracing")]
+                    span.record("thread_id", thread_id.as_str());
                 }
+
+                match &parsed {
+                    ThreadEvent::ItemCompleted { .. } => {
+                        completed_items += 1;
+                        if let Some(limit) = max_output_items.filter(|limit| completed_items > *limit) {
+                            log::warn!("Turn exceeded {} output items; stopping codex", limit);
+                            Err(CodexError::BudgetExceeded {
+                                kind: BudgetKind::OutputItems,
+                                limit: limit as u64,
+                                observed: completed_items as u64,
+                            })?;
+                        }
+                    }
+                    ThreadEvent::TurnCompleted { usage, rate_limits } => {
+                        if let Ok(mut guard) = rate_limits_handle.lock() {
+                            *guard = rate_limits.clone();
+                        }
+                        let observed = usage.input_tokens + usage.output_tokens;
+                        if let Some(limit) = usage_budget.filter(|limit| observed > *limit) {
+                            log::warn!("Turn used {} tokens over a budget of {}", observed, limit);
+                            Err(CodexError::BudgetExceeded {
+                                kind: BudgetKind::Tokens,
+                                limit,
+                                observed,
+                            })?;
+                        }
+                    }
+                    _ => {}
+                }
+
+                #[cfg(feature = "tracing")]
+                drop(entered);
                 yield parsed;
             }
         };
 
-        Ok(Box::pin(stream))
+        let events: ThreadEventStream = match turn_options.heartbeat {
+            Some(interval) if !interval.is_zero() => with_heartbeat(Box::pin(stream), interval),
+            _ => Box::pin(stream),
+        };
+
+        Ok(StreamedTurn {
+            events,
+            skipped_lines,
+            final_response_mode: turn_options.final_response_mode.unwrap_or_default(),
+            last_message_file,
+            output_validation_schema: turn_options
+                .output_schema
+                .filter(|_| turn_options.validate_output.unwrap_or(false)),
+            approval_responder,
+            pricing,
+            rate_limits,
+        })
+    }
+
+    fn spawn_lines(
+        &self,
+        exec_args: CodexExecArgs,
+    ) -> Result<(CodexLineStream, Option<ApprovalResponder>), CodexError> {
+        #[cfg(feature = "app-server")]
+        if let Some(app_server) = &self.app_server {
+            match AppServer::unsupported_setting(&exec_args) {
+                None => {
+                    let (lines, responder) = app_server.run(exec_args)?;
+                    return Ok((lines, Some(responder)));
+                }
+                Some(setting) => {
+                    log::debug!("{} needs codex exec; not using the app-server", setting)
+                }
+            }
+        }
+        if exec_args
+            .approval_policy
+            .as_ref()
+            .is_some_and(ApprovalMode::is_interactive)
+        {
+            return Err(CodexError::ApprovalsUnsupported);
+        }
+        Ok((self.exec.run(exec_args)?, None))
     }
 
     pub async fn run(&self, input: Input, turn_options: TurnOptions) -> Result<Turn, CodexError> {
-        let mut events = self.run_streamed_internal(input, turn_options)?;
-        let mut items = Vec::new();
-        let mut final_response = String::new();
-        let mut usage: Option<Usage> = None;
-        let mut turn_failure: Option<ThreadError> = None;
+        let auto_compacted = self.compact_if_due().await;
+        let turn = self
+            .run_streamed(input, turn_options)?
+            .collect_turn()
+            .await?;
+        let mut turn = Self::ensure_response(self.thread_options.require_response, turn)?;
+        turn.auto_compacted = auto_compacted;
+        self.check_context_usage(&turn);
+        Self::record_turn(&self.turns, self.thread_options.max_history_turns, &turn);
+        Ok(turn)
+    }
 
-        while let Some(event) = events.next().await {
-            let event = event?;
-            match event {
-                ThreadEvent::ItemCompleted { item } => {
-                    if let ThreadItem::AgentMessage { text, .. } = &item {
-                        final_response = text.clone();
+    pub async fn compact(&self) -> Result<Turn, CodexError> {
+        if self.id().is_none() && !self.resume_last {
+            return Err(CodexError::InvalidArgs(
+                "cannot compact a thread before its first turn".to_string(),
+            ));
+        }
+        log::debug!("Compacting thread {:?}", self.id());
+        let mut current = self.compaction_thread(self.id());
+        current.resume_last = self.resume_last;
+        let summary = current
+            .run_streamed(
+                Input::Text(COMPACT_PROMPT.to_string()),
+                TurnOptions::default(),
+            )?
+            .collect_turn()
+            .await?;
+        let summary = Self::ensure_response(true, summary)?;
+
+        let fresh = self.compaction_thread(None);
+        fresh
+            .run_streamed(
+                Input::Text(format!(
+                    "{COMPACT_SEED_PROMPT}\n\n{}",
+                    summary.final_response
+                )),
+                TurnOptions::default(),
+            )?
+            .collect_turn()
+            .await?;
+        let id = fresh
+            .id()
+            .ok_or_else(|| CodexError::TurnFailed("compacted thread reported no id".to_string()))?;
+        log::info!("Compacted thread {:?} into {}", self.id(), id);
+        if let Ok(mut guard) = self.id.lock() {
+            *guard = Some(id);
+        }
+        self.compaction_due.store(false, Ordering::SeqCst);
+        Self::record_turn(&self.turns, self.thread_options.max_history_turns, &summary);
+        Ok(summary)
+    }
+
+    fn compaction_thread(&self, id: Option<ThreadId>) -> Thread {
+        let mut options = self.options.clone();
+        options.default_turn_options = None;
+        let mut thread_options = self.thread_options.clone();
+        thread_options.default_turn_options = None;
+        let mut thread = Thread::new(
+            self.exec.clone(),
+            options,
+            thread_options,
+            id,
+            self.active_threads.clone(),
+        );
+        thread.running = self.running.clone();
+        #[cfg(feature = "app-server")]
+        {
+            thread.app_server = self.app_server.clone();
+        }
+        thread
+    }
+
+    async fn compact_if_due(&self) -> bool {
+        if !self.compaction_due.load(Ordering::SeqCst) {
+            return false;
+        }
+        log::info!("Context usage crossed the auto-compact threshold; compacting first");
+        match self.compact().await {
+            Ok(_) => true,
+            Err(error) => {
+                log::warn!("Auto-compaction failed; running the turn anyway: {}", error);
+                false
+            }
+        }
+    }
+
+    fn check_context_usage(&self, turn: &Turn) {
+        let (Some(threshold), Some(window), Some(usage)) = (
+            self.thread_options.auto_compact_threshold,
+            self.thread_options
+                .model_context_window
+                .filter(|window| *window > 0),
+            turn.usage.as_ref(),
+        ) else {
+            return;
+        };
+        let used = usage.input_tokens as f64 / window as f64;
+        let due = used > f64::from(threshold);
+        if due {
+            log::debug!(
+                "Context usage {:.2} exceeds auto-compact threshold {}",
+                used,
+                threshold
+            );
+        }
+        self.compaction_due.store(due, Ordering::SeqCst);
+    }
+
+    pub async fn review(
+        &self,
+        request: ReviewRequest,
+        turn_options: TurnOptions,
+    ) -> Result<ReviewResult, CodexError> {
+        log::debug!("Running review: {}", request);
+        let (prompt, subcommand) = request.to_exec()?;
+        let turn = self
+            .run_streamed_internal(Input::Text(prompt), turn_options, Some(subcommand))?
+            .collect_turn()
+            .await?;
+        Ok(ReviewResult::from_turn(turn))
+    }
+
+    pub fn run_with_broadcast(
+        &self,
+        input: Input,
+        turn_options: TurnOptions,
+        capacity: usize,
+    ) -> Result<
+        (
+            broadcast::Sender<ThreadItem>,
+            impl Future<Output = Result<Turn, CodexError>>,
+        ),
+        CodexError,
+    > {
+        if capacity == 0 {
+            return Err(CodexError::InvalidArgs(
+                "broadcast capacity must be greater than zero".to_string(),
+            ));
+        }
+
+        let streamed = self.run_streamed(input, turn_options)?;
+        let (sender, _) = broadcast::channel(capacity);
+        let items = sender.clone();
+        let require_response = self.thread_options.require_response;
+        let max_history_turns = self.thread_options.max_history_turns;
+        let turns = self.turns.clone();
+        let turn = async move {
+            let turn = streamed
+                .collect_turn_with(|item| {
+                    let _ = items.send(item.clone());
+                })
+                .await?;
+            let turn = Self::ensure_response(require_response, turn)?;
+            Self::record_turn(&turns, max_history_turns, &turn);
+            Ok(turn)
+        };
+        Ok((sender, turn))
+    }
+
+    pub fn subscribe_items(
+        &self,
+        input: Input,
+        turn_options: TurnOptions,
+        buffer: usize,
+    ) -> Result<ItemSubscription, CodexError> {
+        if buffer == 0 {
+            return Err(CodexError::InvalidArgs(
+                "subscription buffer must be greater than zero".to_string(),
+            ));
+        }
+
+        let streamed = self.run_streamed(input, turn_options)?;
+        let (sender, receiver) = mpsc::channel(buffer);
+        let require_response = self.thread_options.require_response;
+        let max_history_turns = self.thread_options.max_history_turns;
+        let turns = self.turns.clone();
+
+        let mut events = streamed.events;
+        let forwarded = try_stream! {
+            loop {
+                let event = tokio::select! {
+                    _ = sender.closed() => {
+                        log::debug!("Item receiver dropped; aborting turn");
+                        Err(CodexError::Aborted)
                     }
-                    items.push(item);
-                }
-                ThreadEvent::TurnCompleted { usage: event_usage } => {
-                    usage = Some(event_usage);
-                    log::debug!("Turn completed");
-                }
-                ThreadEvent::TurnFailed { error } => {
-                    turn_failure = Some(error);
-                    log::debug!("Turn failed");
+                    event = events.next() => Ok(event),
+                }?;
+                let Some(event) = event else {
                     break;
+                };
+                let event = event?;
+                if let ThreadEvent::ItemCompleted { item } = &event {
+                    if sender.send(item.clone()).await.is_err() {
+                        log::debug!("Item receiver dropped; aborting turn");
+                        Err(CodexError::Aborted)?;
+                    }
                 }
-                _ => {}
+                yield event;
             }
-        }
+        };
+        let streamed = StreamedTurn {
+            events: Box::pin(forwarded),
+            ..streamed
+        };
 
-        if let Some(error) = turn_failure {
-            return Err(CodexError::TurnFailed(error.message));
+        let handle = tokio::spawn(async move {
+            let turn = streamed.collect_turn().await?;
+            let turn = Self::ensure_response(require_response, turn)?;
+            Self::record_turn(&turns, max_history_turns, &turn);
+            Ok(turn)
+        });
+        Ok((receiver, handle))
+    }
+
+    pub fn run_streamed_timestamped(
+        &self,
+        input: Input,
+        turn_options: TurnOptions,
+    ) -> Result<TimestampedItemStream, CodexError> {
+        let mut events = self.run_streamed(input, turn_options)?.events;
+        let items = try_stream! {
+            let mut retrying: Option<ThreadError> = None;
+            while let Some(event) = events.next().await {
+                match event? {
+                    ThreadEvent::ItemCompleted { item } => yield TimestampedThreadItem {
+                        item,
+                        received_at: SystemTime::now(),
+                    },
+                    ThreadEvent::TurnStarted => retrying = None,
+                    ThreadEvent::TurnFailed { error } if error.is_retrying() => {
+                        retrying = Some(error);
+                    }
+                    ThreadEvent::TurnFailed { error } => {
+                        Err(CodexError::TurnFailed(error.message))?;
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(error) = retrying {
+                Err(CodexError::TurnFailed(error.message))?;
+            }
+        };
+        Ok(Box::pin(items))
+    }
+
+    fn ensure_response(require_response: bool, turn: Turn) -> Result<Turn, CodexError> {
+        if require_response && turn.final_response.is_empty() {
+            return Err(CodexError::TurnFailed(
+                "no agent response produced".to_string(),
+            ));
         }
+        Ok(turn)
+    }
 
-        Ok(Turn {
-            items,
-            final_response,
-            usage,
-        })
+    #[doc(hidden)]
+    pub fn normalize_input(input: &Input) -> Result<(String, Vec<String>, ImageFiles), CodexError> {
+        Self::normalize_input_with_limits(input, &FileInputLimits::default())
     }
 
     #[doc(hidden)]
-    pub fn normalize_input(input: &Input) -> (String, Vec<String>) {
+    pub fn normalize_input_with_limits(
+        input: &Input,
+        limits: &FileInputLimits,
+    ) -> Result<(String, Vec<String>, ImageFiles), CodexError> {
+        let mut image_files = ImageFiles::new();
+        let mut file_bytes = 0;
         match input {
-            Input::Text(text) => (text.clone(), Vec::new()),
+            Input::Text(text) => Ok((text.clone(), Vec::new(), image_files)),
             Input::Structured(items) => {
                 let mut prompt_parts = Vec::new();
                 let mut images = Vec::new();
                 for item in items {
                     match item {
                         UserInput::Text { text } => prompt_parts.push(text.clone()),
-                        UserInput::LocalImage { path } => images.push(path.clone()),
+                        UserInput::LocalImage { path } => images.push(Self::resolve_image(path)?),
+                        UserInput::ImageUrl { url } => images.push(image_files.resolve_url(url)?),
+                        UserInput::ImageBytes { data, format } => {
+                            images.push(image_files.write_bytes(data, format)?)
+                        }
+                        UserInput::File { path } => {
+                            prompt_parts.push(Self::embed_file(path, limits, &mut file_bytes)?)
+                        }
                     }
                 }
-                (prompt_parts.join("\n\n"), images)
+                Ok((prompt_parts.join("\n\n"), images, image_files))
+            }
+        }
+    }
+
+    fn resolve_turn_options(&self, turn_options: TurnOptions) -> (TurnOptions, Option<DropGuard>) {
+        let layers = [
+            self.thread_options.default_turn_options.as_ref(),
+            self.options.default_turn_options.as_ref(),
+        ];
+        let tokens: Vec<CancellationToken> = turn_options
+            .cancel
+            .iter()
+            .chain(
+                layers
+                    .iter()
+                    .flatten()
+                    .filter_map(|layer| layer.cancel.as_ref()),
+            )
+            .cloned()
+            .collect();
+
+        let mut resolved = layers
+            .into_iter()
+            .flatten()
+            .fold(turn_options, |resolved, defaults| resolved.merge(defaults));
+        let (cancel, cancel_guard) = link_cancellation(tokens);
+        resolved.cancel = cancel;
+        log::debug!("Resolved turn options: {}", resolved);
+        (resolved, cancel_guard)
+    }
+
+    fn schema_file(&self, schema: Option<&Value>) -> Result<Arc<OutputSchemaFile>, CodexError> {
+        let Some(schema) = schema else {
+            return Ok(Arc::new(OutputSchemaFile::new(None)?));
+        };
+
+        let key = OutputSchemaFile::cache_key(schema);
+        let cached = self.schema_cache.lock().ok().and_then(|mut cache| {
+            let index = cache.iter().position(|(cached, _)| *cached == key)?;
+            let entry = cache.remove(index)?;
+            let schema_file = entry.1.clone();
+            cache.push_back(entry);
+            Some(schema_file)
+        });
+        if let Some(schema_file) = cached {
+            log::debug!(
+                "Reusing cached output schema {:?}",
+                schema_file.schema_path()
+            );
+            return Ok(schema_file);
+        }
+
+        let schema_file = Arc::new(match &self.thread_options.schema_dir {
+            Some(dir) => {
+                OutputSchemaFile::at_path(dir.join(OutputSchemaFile::file_name(&key)), schema)?
+            }
+            None => OutputSchemaFile::new(Some(schema))?,
+        });
+        if let Ok(mut cache) = self.schema_cache.lock() {
+            if cache.len() >= SCHEMA_CACHE_CAPACITY {
+                cache.pop_front();
             }
+            cache.push_back((key, schema_file.clone()));
+        }
+        Ok(schema_file)
+    }
+
+    fn resolve_image(path: &str) -> Result<String, CodexError> {
+        const IMAGE_EXTENSIONS: &[&str] =
+            &["png", "jpg", "jpeg", "gif", "webp", "bmp", "tif", "tiff"];
+
+        let path_buf = PathBuf::from(path);
+        let is_file = std::fs::metadata(&path_buf)
+            .map(|metadata| metadata.is_file())
+            .unwrap_or(false);
+        if !is_file {
+            return Err(CodexError::ImageNotFound(path_buf));
+        }
+
+        let has_image_extension = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+            .unwrap_or(false);
+        if !has_image_extension {
+            return Err(CodexError::InvalidInputFile(
+                path.to_string(),
+                "not a recognized image extension".to_string(),
+            ));
         }
+
+        let resolved = std::fs::canonicalize(&path_buf)?;
+        log::debug!("Resolved image {} to {:?}", path, resolved);
+        Ok(resolved.to_string_lossy().to_string())
     }
 
-    fn event_type(event: &ThreadEvent) -> &'static str {
-        match event {
-            ThreadEvent::ThreadStarted { .. } => "thread.started",
-            ThreadEvent::TurnStarted => "turn.started",
-            ThreadEvent::TurnCompleted { .. } => "turn.completed",
-            ThreadEvent::TurnFailed { .. } => "turn.failed",
-            ThreadEvent::ItemStarted { .. } => "item.started",
-            ThreadEvent::ItemUpdated { .. } => "item.updated",
-            ThreadEvent::ItemCompleted { .. } => "item.completed",
-            ThreadEvent::ThreadErrorEvent { .. } => "error",
+    fn embed_file(
+        path: &str,
+        limits: &FileInputLimits,
+        total_bytes: &mut u64,
+    ) -> Result<String, CodexError> {
+        let size = std::fs::metadata(path)?.len();
+        if size > limits.max_file_bytes {
+            return Err(CodexError::InputTooLarge(format!(
+                "{} is {} bytes, over the {} byte per-file limit",
+                path, size, limits.max_file_bytes
+            )));
+        }
+        *total_bytes += size;
+        if *total_bytes > limits.max_total_bytes {
+            return Err(CodexError::InputTooLarge(format!(
+                "embedding {} brings file input to {} bytes, over the {} byte total limit",
+                path, total_bytes, limits.max_total_bytes
+            )));
         }
+
+        let bytes = std::fs::read(path)?;
+        let contents = match String::from_utf8(bytes) {
+            Ok(text) if !text.contains('\0') => text,
+            _ => {
+                return Err(CodexError::InvalidInputFile(
+                    path.to_string(),
+                    "binary files cannot be embedded as text".to_string(),
+                ))
+            }
+        };
+        log::debug!("Embedding file {} ({} bytes)", path, size);
+
+        let longest_run = contents
+            .split(|ch| ch != '`')
+            .map(str::len)
+            .max()
+            .unwrap_or(0);
+        let fence = "`".repeat(longest_run.max(2) + 1);
+        let newline = if contents.ends_with('\n') { "" } else { "\n" };
+        Ok(format!("{fence}path={path}\n{contents}{newline}{fence}"))
+    }
+
+    fn merge_directories(
+        thread_dirs: Option<&Vec<String>>,
+        turn_dirs: Option<&Vec<String>>,
+    ) -> Option<Vec<String>> {
+        match (thread_dirs, turn_dirs) {
+            (None, None) => None,
+            (thread_dirs, turn_dirs) => Some(
+                thread_dirs
+                    .into_iter()
+                    .chain(turn_dirs)
+                    .flatten()
+                    .cloned()
+                    .collect(),
+            ),
+        }
+    }
+
+    fn is_json_object(line: &str) -> bool {
+        serde_json::from_str::<serde_json::Value>(line)
+            .map(|value| value.is_object())
+            .unwrap_or(false)
     }
 }
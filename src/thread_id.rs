@@ -0,0 +1,73 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::CodexError;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ThreadId(String);
+
+impl ThreadId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    pub fn validate(&self) -> Result<(), CodexError> {
+        if self.0.is_empty() {
+            return Err(CodexError::InvalidThreadId(
+                "thread id must not be empty".to_string(),
+            ));
+        }
+
+        if !Self::is_uuid(&self.0) {
+            return Err(CodexError::InvalidThreadId(format!(
+                "{:?} is not a UUID",
+                self.0
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn is_uuid(value: &str) -> bool {
+        let groups: Vec<&str> = value.split('-').collect();
+        groups.len() == 5
+            && groups.iter().zip([8, 4, 4, 4, 12]).all(|(group, len)| {
+                group.len() == len && group.chars().all(|c| c.is_ascii_hexdigit())
+            })
+    }
+}
+
+impl FromStr for ThreadId {
+    type Err = CodexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let id = ThreadId(s.to_string());
+        id.validate()?;
+        Ok(id)
+    }
+}
+
+impl From<String> for ThreadId {
+    fn from(value: String) -> Self {
+        ThreadId(value)
+    }
+}
+
+impl From<&str> for ThreadId {
+    fn from(value: &str) -> Self {
+        ThreadId(value.to_string())
+    }
+}
+
+impl fmt::Display for ThreadId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
@@ -1,4 +1,12 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
 use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::turn_options::TurnOptions;
 
 #[derive(Clone, Debug)]
 pub enum ApprovalMode {
@@ -6,17 +14,53 @@ pub enum ApprovalMode {
     OnRequest,
     OnFailure,
     Untrusted,
+    Custom(String),
 }
 
 impl ApprovalMode {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             ApprovalMode::Never => "never",
             ApprovalMode::OnRequest => "on-request",
             ApprovalMode::OnFailure => "on-failure",
             ApprovalMode::Untrusted => "untrusted",
+            ApprovalMode::Custom(value) => value,
         }
     }
+
+    pub fn is_known(&self) -> bool {
+        !matches!(self, ApprovalMode::Custom(_))
+    }
+
+    pub fn is_interactive(&self) -> bool {
+        matches!(
+            self,
+            ApprovalMode::OnRequest | ApprovalMode::OnFailure | ApprovalMode::Untrusted
+        )
+    }
+}
+
+impl FromStr for ApprovalMode {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "never" => ApprovalMode::Never,
+            "on-request" => ApprovalMode::OnRequest,
+            "on-failure" => ApprovalMode::OnFailure,
+            "untrusted" => ApprovalMode::Untrusted,
+            other => ApprovalMode::Custom(other.to_string()),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ApprovalMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(value
+            .parse()
+            .unwrap_or_else(|never: Infallible| match never {}))
+    }
 }
 
 impl fmt::Display for ApprovalMode {
@@ -55,18 +99,54 @@ pub enum ModelReasoningEffort {
     Medium,
     High,
     XHigh,
+    Custom(String),
 }
 
 impl ModelReasoningEffort {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             ModelReasoningEffort::Minimal => "minimal",
             ModelReasoningEffort::Low => "low",
             ModelReasoningEffort::Medium => "medium",
             ModelReasoningEffort::High => "high",
             ModelReasoningEffort::XHigh => "xhigh",
+            ModelReasoningEffort::Custom(value) => value,
         }
     }
+
+    pub fn is_standard(&self) -> bool {
+        !matches!(self, ModelReasoningEffort::Custom(_))
+    }
+}
+
+impl FromStr for ModelReasoningEffort {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "minimal" => ModelReasoningEffort::Minimal,
+            "low" => ModelReasoningEffort::Low,
+            "medium" => ModelReasoningEffort::Medium,
+            "high" => ModelReasoningEffort::High,
+            "xhigh" => ModelReasoningEffort::XHigh,
+            other => ModelReasoningEffort::Custom(other.to_string()),
+        })
+    }
+}
+
+impl Serialize for ModelReasoningEffort {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ModelReasoningEffort {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(value
+            .parse()
+            .unwrap_or_else(|never: Infallible| match never {}))
+    }
 }
 
 impl fmt::Display for ModelReasoningEffort {
@@ -75,6 +155,123 @@ impl fmt::Display for ModelReasoningEffort {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum ModelVerbosity {
+    Low,
+    Medium,
+    High,
+    Custom(String),
+}
+
+impl ModelVerbosity {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ModelVerbosity::Low => "low",
+            ModelVerbosity::Medium => "medium",
+            ModelVerbosity::High => "high",
+            ModelVerbosity::Custom(value) => value,
+        }
+    }
+
+    pub fn is_standard(&self) -> bool {
+        !matches!(self, ModelVerbosity::Custom(_))
+    }
+}
+
+impl FromStr for ModelVerbosity {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "low" => ModelVerbosity::Low,
+            "medium" => ModelVerbosity::Medium,
+            "high" => ModelVerbosity::High,
+            other => ModelVerbosity::Custom(other.to_string()),
+        })
+    }
+}
+
+impl Serialize for ModelVerbosity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ModelVerbosity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(value
+            .parse()
+            .unwrap_or_else(|never: Infallible| match never {}))
+    }
+}
+
+impl fmt::Display for ModelVerbosity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReasoningSummaryMode {
+    Auto,
+    Concise,
+    Detailed,
+    None,
+    Custom(String),
+}
+
+impl ReasoningSummaryMode {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ReasoningSummaryMode::Auto => "auto",
+            ReasoningSummaryMode::Concise => "concise",
+            ReasoningSummaryMode::Detailed => "detailed",
+            ReasoningSummaryMode::None => "none",
+            ReasoningSummaryMode::Custom(value) => value,
+        }
+    }
+
+    pub fn is_standard(&self) -> bool {
+        !matches!(self, ReasoningSummaryMode::Custom(_))
+    }
+}
+
+impl FromStr for ReasoningSummaryMode {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "auto" => ReasoningSummaryMode::Auto,
+            "concise" => ReasoningSummaryMode::Concise,
+            "detailed" => ReasoningSummaryMode::Detailed,
+            "none" => ReasoningSummaryMode::None,
+            other => ReasoningSummaryMode::Custom(other.to_string()),
+        })
+    }
+}
+
+impl Serialize for ReasoningSummaryMode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ReasoningSummaryMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(value
+            .parse()
+            .unwrap_or_else(|never: Infallible| match never {}))
+    }
+}
+
+impl fmt::Display for ReasoningSummaryMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum WebSearchMode {
     Disabled,
@@ -98,35 +295,205 @@ impl fmt::Display for WebSearchMode {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
+pub struct FileInputLimits {
+    pub max_file_bytes: u64,
+    pub max_total_bytes: u64,
+}
+
+impl Default for FileInputLimits {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 256 * 1024,
+            max_total_bytes: 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShellEnvironmentInherit {
+    All,
+    Core,
+    None,
+}
+
+impl ShellEnvironmentInherit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShellEnvironmentInherit::All => "all",
+            ShellEnvironmentInherit::Core => "core",
+            ShellEnvironmentInherit::None => "none",
+        }
+    }
+}
+
+impl fmt::Display for ShellEnvironmentInherit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShellEnvironmentPolicy {
+    pub inherit: Option<ShellEnvironmentInherit>,
+    pub ignore_default_excludes: Option<bool>,
+    pub exclude: Option<Vec<String>>,
+    pub include_only: Option<Vec<String>>,
+    pub set: Option<HashMap<String, String>>,
+}
+
+impl fmt::Display for ShellEnvironmentPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let set = self
+            .set
+            .as_ref()
+            .map(|vars| {
+                let mut keys: Vec<&str> = vars.keys().map(String::as_str).collect();
+                keys.sort_unstable();
+                format!("Some(keys={keys:?})")
+            })
+            .unwrap_or_else(|| "None".to_string());
+
+        write!(
+            f,
+            "ShellEnvironmentPolicy {{ inherit: {}, ignore_default_excludes: {:?}, exclude: {:?}, include_only: {:?}, set: {} }}",
+            ThreadOptions::format_option(self.inherit.as_ref()),
+            self.ignore_default_excludes,
+            self.exclude,
+            self.include_only,
+            set
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tools {
+    pub web_search: Option<bool>,
+    pub view_image: Option<bool>,
+    pub plan: Option<bool>,
+    pub apply_patch: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SandboxWorkspaceWriteOptions {
+    pub network_access: Option<bool>,
+    pub exclude_tmpdir_env_var: Option<bool>,
+    pub exclude_slash_tmp: Option<bool>,
+    pub writable_roots: Option<Vec<PathBuf>>,
+}
+
+#[derive(Clone, Debug)]
 pub struct ThreadOptions {
     pub model: Option<String>,
+    pub model_provider: Option<String>,
+    pub oss: Option<bool>,
+    pub local_model_server: Option<String>,
+    pub profile: Option<String>,
     pub sandbox_mode: Option<SandboxMode>,
     pub working_directory: Option<String>,
     pub skip_git_repo_check: Option<bool>,
     pub model_reasoning_effort: Option<ModelReasoningEffort>,
+    pub model_verbosity: Option<ModelVerbosity>,
+    pub reasoning_summary: Option<ReasoningSummaryMode>,
+    #[deprecated(note = "use `sandbox_workspace_write.network_access` instead")]
     pub network_access_enabled: Option<bool>,
+    pub sandbox_workspace_write: Option<SandboxWorkspaceWriteOptions>,
+    pub shell_environment_policy: Option<ShellEnvironmentPolicy>,
     pub web_search_mode: Option<WebSearchMode>,
     pub web_search_enabled: Option<bool>,
+    pub tools: Option<Tools>,
     pub approval_policy: Option<ApprovalMode>,
+    pub base_instructions: Option<String>,
+    pub append_instructions: Option<String>,
+    pub dangerously_bypass_approvals_and_sandbox: Option<bool>,
+    pub disable_response_storage: Option<bool>,
     pub additional_directories: Option<Vec<String>>,
+    pub lenient_parsing: bool,
+    pub file_input_limits: Option<FileInputLimits>,
+    pub schema_dir: Option<PathBuf>,
+    pub default_turn_options: Option<TurnOptions>,
+    pub require_response: bool,
+    pub max_history_turns: Option<usize>,
+    pub model_context_window: Option<u64>,
+    pub auto_compact_threshold: Option<f32>,
+}
+
+#[allow(deprecated)]
+impl Default for ThreadOptions {
+    fn default() -> Self {
+        Self {
+            model: None,
+            model_provider: None,
+            oss: None,
+            local_model_server: None,
+            profile: None,
+            sandbox_mode: None,
+            working_directory: None,
+            skip_git_repo_check: None,
+            model_reasoning_effort: None,
+            model_verbosity: None,
+            reasoning_summary: None,
+            network_access_enabled: None,
+            sandbox_workspace_write: None,
+            shell_environment_policy: None,
+            web_search_mode: None,
+            web_search_enabled: None,
+            tools: None,
+            approval_policy: None,
+            base_instructions: None,
+            append_instructions: None,
+            dangerously_bypass_approvals_and_sandbox: None,
+            disable_response_storage: None,
+            additional_directories: None,
+            lenient_parsing: false,
+            file_input_limits: None,
+            schema_dir: None,
+            default_turn_options: None,
+            require_response: true,
+            max_history_turns: None,
+            model_context_window: None,
+            auto_compact_threshold: None,
+        }
+    }
 }
 
+#[allow(deprecated)]
 impl fmt::Display for ThreadOptions {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "ThreadOptions {{ model: {:?}, sandbox_mode: {}, working_directory: {:?}, skip_git_repo_check: {:?}, model_reasoning_effort: {}, network_access_enabled: {:?}, web_search_mode: {}, web_search_enabled: {:?}, approval_policy: {}, additional_directories: {:?} }}",
+            "ThreadOptions {{ model: {:?}, model_provider: {:?}, oss: {:?}, local_model_server: {:?}, profile: {:?}, sandbox_mode: {}, working_directory: {:?}, skip_git_repo_check: {:?}, model_reasoning_effort: {}, model_verbosity: {}, reasoning_summary: {}, network_access_enabled: {:?}, sandbox_workspace_write: {:?}, shell_environment_policy: {}, web_search_mode: {}, web_search_enabled: {:?}, tools: {:?}, approval_policy: {}, base_instructions_len: {:?}, append_instructions_len: {:?}, dangerously_bypass_approvals_and_sandbox: {:?}, disable_response_storage: {:?}, additional_directories: {:?}, lenient_parsing: {}, file_input_limits: {:?}, schema_dir: {:?}, default_turn_options: {}, require_response: {}, max_history_turns: {:?}, model_context_window: {:?}, auto_compact_threshold: {:?} }}",
             self.model,
+            self.model_provider,
+            self.oss,
+            self.local_model_server,
+            self.profile,
             Self::format_option(self.sandbox_mode.as_ref()),
             self.working_directory,
             self.skip_git_repo_check,
             Self::format_option(self.model_reasoning_effort.as_ref()),
+            Self::format_option(self.model_verbosity.as_ref()),
+            Self::format_option(self.reasoning_summary.as_ref()),
             self.network_access_enabled,
+            self.sandbox_workspace_write,
+            Self::format_option(self.shell_environment_policy.as_ref()),
             Self::format_option(self.web_search_mode.as_ref()),
             self.web_search_enabled,
+            self.tools,
             Self::format_option(self.approval_policy.as_ref()),
+            self.base_instructions.as_ref().map(String::len),
+            self.append_instructions.as_ref().map(String::len),
+            self.dangerously_bypass_approvals_and_sandbox,
+            self.disable_response_storage,
             self.additional_directories,
+            self.lenient_parsing,
+            self.file_input_limits,
+            self.schema_dir,
+            Self::format_option(self.default_turn_options.as_ref()),
+            self.require_response,
+            self.max_history_turns,
+            self.model_context_window,
+            self.auto_compact_threshold,
         )
     }
 }
@@ -1,12 +1,129 @@
 use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tokio_util::sync::CancellationToken;
+use tokio_util::sync::{CancellationToken, DropGuard};
+
+use crate::error::CodexError;
+use crate::thread_options::ModelReasoningEffort;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinalResponseMode {
+    #[default]
+    LastMessage,
+    FirstMessage,
+    Concatenate {
+        separator: String,
+    },
+}
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputLastMessage {
+    Path(PathBuf),
+    TempFile,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TurnOptions {
     pub output_schema: Option<Value>,
+    #[serde(skip)]
     pub cancel: Option<CancellationToken>,
+    pub additional_directories: Option<Vec<String>>,
+    pub final_response_mode: Option<FinalResponseMode>,
+    pub output_last_message: Option<OutputLastMessage>,
+    pub validate_output: Option<bool>,
+    pub profile: Option<String>,
+    pub model_reasoning_effort: Option<ModelReasoningEffort>,
+    pub preamble: Option<String>,
+    pub lenient_parsing: Option<bool>,
+    pub recover_unknown_events: Option<bool>,
+    pub heartbeat: Option<Duration>,
+    pub max_output_items: Option<usize>,
+    pub usage_budget: Option<u64>,
+}
+
+impl TurnOptions {
+    pub fn with_preamble_from_file(
+        mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<TurnOptions, CodexError> {
+        let path = path.as_ref();
+        log::debug!("Loading preamble from {}", path.display());
+        self.preamble = Some(std::fs::read_to_string(path)?);
+        Ok(self)
+    }
+
+    pub fn merge(self, defaults: &TurnOptions) -> TurnOptions {
+        TurnOptions {
+            output_schema: self
+                .output_schema
+                .or_else(|| defaults.output_schema.clone()),
+            cancel: self.cancel.or_else(|| defaults.cancel.clone()),
+            additional_directories: self
+                .additional_directories
+                .or_else(|| defaults.additional_directories.clone()),
+            final_response_mode: self
+                .final_response_mode
+                .or_else(|| defaults.final_response_mode.clone()),
+            output_last_message: self
+                .output_last_message
+                .or_else(|| defaults.output_last_message.clone()),
+            validate_output: self.validate_output.or(defaults.validate_output),
+            profile: self.profile.or_else(|| defaults.profile.clone()),
+            model_reasoning_effort: self
+                .model_reasoning_effort
+                .or_else(|| defaults.model_reasoning_effort.clone()),
+            preamble: self.preamble.or_else(|| defaults.preamble.clone()),
+            lenient_parsing: self.lenient_parsing.or(defaults.lenient_parsing),
+            recover_unknown_events: self
+                .recover_unknown_events
+                .or(defaults.recover_unknown_events),
+            heartbeat: self.heartbeat.or(defaults.heartbeat),
+            max_output_items: self.max_output_items.or(defaults.max_output_items),
+            usage_budget: self.usage_budget.or(defaults.usage_budget),
+        }
+    }
+}
+
+pub(crate) fn link_cancellation(
+    mut tokens: Vec<CancellationToken>,
+) -> (Option<CancellationToken>, Option<DropGuard>) {
+    if tokens.len() < 2 {
+        return (tokens.pop(), None);
+    }
+
+    let linked = tokens[0].child_token();
+    let parents = tokens.split_off(1);
+    if parents.iter().any(CancellationToken::is_cancelled) {
+        linked.cancel();
+        return (Some(linked), None);
+    }
+
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            let watched = linked.clone();
+            handle.spawn(async move {
+                let cancelled = futures::future::select_all(
+                    parents.iter().map(|token| Box::pin(token.cancelled())),
+                );
+                tokio::select! {
+                    _ = cancelled => watched.cancel(),
+                    _ = watched.cancelled() => {}
+                }
+            });
+        }
+        Err(_) => {
+            log::warn!("No tokio runtime available; only the first cancellation token is linked")
+        }
+    }
+
+    let guard = linked.clone().drop_guard();
+    (Some(linked), Some(guard))
 }
 
 impl fmt::Display for TurnOptions {
@@ -24,8 +141,21 @@ impl fmt::Display for TurnOptions {
 
         write!(
             f,
-            "TurnOptions {{ output_schema: {}, cancel: {} }}",
-            output_schema, cancel
+            "TurnOptions {{ output_schema: {}, cancel: {}, additional_directories: {:?}, final_response_mode: {:?}, output_last_message: {:?}, validate_output: {:?}, profile: {:?}, model_reasoning_effort: {:?}, preamble_len: {:?}, lenient_parsing: {:?}, recover_unknown_events: {:?}, heartbeat: {:?}, max_output_items: {:?}, usage_budget: {:?} }}",
+            output_schema,
+            cancel,
+            self.additional_directories,
+            self.final_response_mode,
+            self.output_last_message,
+            self.validate_output,
+            self.profile,
+            self.model_reasoning_effort,
+            self.preamble.as_ref().map(String::len),
+            self.lenient_parsing,
+            self.recover_unknown_events,
+            self.heartbeat,
+            self.max_output_items,
+            self.usage_budget
         )
     }
 }
@@ -0,0 +1,62 @@
+use crate::events::Usage;
+use crate::pricing::ModelPricing;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CostModel {
+    pub input: f64,
+    pub output: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UsageAccumulator {
+    pub total: Usage,
+    pub cost_per_million_input: f64,
+    pub cost_per_million_output: f64,
+    pub cost_per_million_cached_input: Option<f64>,
+}
+
+impl UsageAccumulator {
+    pub fn new(cost_model: CostModel) -> Self {
+        Self {
+            total: Usage::default(),
+            cost_per_million_input: cost_model.input,
+            cost_per_million_output: cost_model.output,
+            cost_per_million_cached_input: None,
+        }
+    }
+
+    pub fn with_pricing(pricing: ModelPricing) -> Self {
+        Self {
+            total: Usage::default(),
+            cost_per_million_input: pricing.input_per_million,
+            cost_per_million_output: pricing.output_per_million,
+            cost_per_million_cached_input: Some(pricing.cached_input_per_million),
+        }
+    }
+
+    pub fn add(&mut self, usage: &Usage) {
+        self.total.input_tokens += usage.input_tokens;
+        self.total.cached_input_tokens += usage.cached_input_tokens;
+        self.total.output_tokens += usage.output_tokens;
+    }
+
+    pub fn total_cost(&self) -> f64 {
+        let input = match self.cost_per_million_cached_input {
+            Some(cached_rate) => {
+                let uncached = self
+                    .total
+                    .input_tokens
+                    .saturating_sub(self.total.cached_input_tokens);
+                uncached as f64 * self.cost_per_million_input
+                    + self.total.cached_input_tokens as f64 * cached_rate
+            }
+            None => self.total.input_tokens as f64 * self.cost_per_million_input,
+        };
+        let output = self.total.output_tokens as f64 * self.cost_per_million_output;
+        (input + output) / 1_000_000.0
+    }
+
+    pub fn exceeds_budget(&self, budget: f64) -> bool {
+        self.total_cost() > budget
+    }
+}
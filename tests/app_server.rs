@@ -0,0 +1,428 @@
+#![cfg(all(unix, feature = "app-server"))]
+
+mod common;
+
+use futures::StreamExt;
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+
+use codex_sdk::{
+    ApprovalDecision, ApprovalKind, ApprovalMode, Codex, CodexError, CodexExecArgs, CodexOptions,
+    ExecBackend, ItemDelta, RateLimitSnapshot, ThreadEvent, ThreadItem, ThreadOptions, TurnOptions,
+    Usage,
+};
+
+const APP_SERVER_TURN: &str = include_str!("fixtures/app_server_turn.jsonl");
+const APP_SERVER_APPROVAL: &str = include_str!("fixtures/app_server_approval.jsonl");
+const THREAD_ID: &str = "0199a213-81c0-7800-8aa1-bbab2a035a53";
+
+fn fake_server(crash_first_turn: bool) -> common::FakeCodex {
+    let lines: Vec<&str> = APP_SERVER_TURN.lines().collect();
+    common::fake_app_server(THREAD_ID, &lines, crash_first_turn)
+}
+
+fn codex_for(fake: &common::FakeCodex) -> Codex {
+    Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        backend: Some(ExecBackend::AppServer),
+        ..CodexOptions::default()
+    })
+    .expect("codex")
+}
+
+fn requests(fake: &common::FakeCodex) -> Vec<Value> {
+    std::fs::read_to_string(fake.dir().join("requests.jsonl"))
+        .expect("read requests")
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("request json"))
+        .collect()
+}
+
+fn methods(fake: &common::FakeCodex) -> Vec<String> {
+    requests(fake)
+        .iter()
+        .map(|request| request["method"].as_str().unwrap_or_default().to_string())
+        .collect()
+}
+
+fn spawn_count(fake: &common::FakeCodex) -> usize {
+    std::fs::read_to_string(fake.dir().join("spawns.txt"))
+        .expect("read spawns")
+        .lines()
+        .count()
+}
+
+#[tokio::test]
+async fn turn_is_translated_from_app_server_notifications() {
+    let fake = fake_server(false);
+    let thread = codex_for(&fake).start_thread(ThreadOptions {
+        model: Some("gpt-5-codex".to_string()),
+        ..ThreadOptions::default()
+    });
+
+    let turn = thread
+        .run("list the files".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    assert_eq!(fake.recorded_args(), vec!["app-server"]);
+    assert_eq!(
+        methods(&fake),
+        vec!["initialize", "initialized", "thread/start", "turn/start"]
+    );
+    let requests = requests(&fake);
+    assert_eq!(requests[2]["params"]["model"], json!("gpt-5-codex"));
+    assert_eq!(
+        requests[3]["params"]["input"],
+        json!([{ "type": "text", "text": "list the files" }])
+    );
+    assert_eq!(requests[3]["params"]["threadId"], json!(THREAD_ID));
+
+    assert_eq!(thread.id().map(|id| id.to_string()), Some(THREAD_ID.into()));
+    assert_eq!(turn.final_response, "Done.");
+    assert!(matches!(
+        &turn.items[0],
+        ThreadItem::CommandExecution { command, aggregated_output, exit_code: Some(0), .. }
+            if command == "ls" && aggregated_output == "Cargo.toml\n"
+    ));
+    assert_eq!(
+        turn.usage,
+        Some(Usage {
+            input_tokens: 12,
+            cached_input_tokens: 4,
+            output_tokens: 3,
+        })
+    );
+}
+
+#[tokio::test]
+async fn account_rate_limit_updates_reach_the_completed_turn() {
+    let rate_limits = r#"{"method":"account/rateLimits/updated","params":{"rateLimits":{"primary":{"usedPercent":12.5,"windowDurationMins":300,"resetsAt":1760620800},"secondary":{"usedPercent":40.0,"windowDurationMins":10080,"resetsAt":1761004800}}}}"#;
+    let mut lines: Vec<&str> = APP_SERVER_TURN.lines().collect();
+    lines.insert(lines.len() - 1, rate_limits);
+    let fake = common::fake_app_server(THREAD_ID, &lines, false);
+    let thread = codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let turn = thread
+        .run("list the files".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    assert_eq!(
+        turn.rate_limits,
+        Some(RateLimitSnapshot {
+            primary_used_percent: Some(12.5),
+            secondary_used_percent: Some(40.0),
+            resets_at: Some(1760620800),
+            secondary_resets_at: Some(1761004800),
+        })
+    );
+}
+
+#[tokio::test]
+async fn one_process_serves_multiple_turns() {
+    let fake = fake_server(false);
+    let thread = codex_for(&fake).start_thread(ThreadOptions::default());
+
+    for prompt in ["first", "second"] {
+        thread
+            .run(prompt.into(), TurnOptions::default())
+            .await
+            .expect("turn");
+    }
+
+    assert_eq!(spawn_count(&fake), 1);
+    assert_eq!(
+        methods(&fake),
+        vec![
+            "initialize",
+            "initialized",
+            "thread/start",
+            "turn/start",
+            "turn/start"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn resumed_thread_is_loaded_with_thread_resume() {
+    let fake = fake_server(false);
+    let thread = codex_for(&fake).resume_thread(THREAD_ID, ThreadOptions::default());
+
+    thread
+        .run("continue".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    assert_eq!(
+        methods(&fake),
+        vec!["initialize", "initialized", "thread/resume", "turn/start"]
+    );
+    assert_eq!(requests(&fake)[2]["params"]["threadId"], json!(THREAD_ID));
+}
+
+#[tokio::test]
+async fn crashed_process_is_restarted_on_next_turn() {
+    let fake = fake_server(true);
+    let thread = codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let first = thread.run("first".into(), TurnOptions::default()).await;
+    assert!(matches!(first, Err(CodexError::AppServer(_))));
+
+    let turn = thread
+        .run("second".into(), TurnOptions::default())
+        .await
+        .expect("turn after restart");
+
+    assert_eq!(turn.final_response, "Done.");
+    assert_eq!(spawn_count(&fake), 2);
+    assert_eq!(
+        methods(&fake),
+        vec![
+            "initialize",
+            "initialized",
+            "thread/start",
+            "turn/start",
+            "initialize",
+            "initialized",
+            "thread/resume",
+            "turn/start"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn unsupported_settings_fall_back_to_exec() {
+    let fake = fake_server(false);
+    let thread = codex_for(&fake).start_thread(ThreadOptions {
+        additional_directories: Some(vec!["/tmp".to_string()]),
+        ..ThreadOptions::default()
+    });
+
+    let _ = thread.run("hello".into(), TurnOptions::default()).await;
+
+    assert_eq!(fake.recorded_args()[..2], ["exec", "--experimental-json"]);
+}
+
+fn on_request_thread(fake: &common::FakeCodex) -> codex_sdk::Thread {
+    codex_for(fake).start_thread(ThreadOptions {
+        approval_policy: Some(ApprovalMode::OnRequest),
+        ..ThreadOptions::default()
+    })
+}
+
+fn approval_reply(fake: &common::FakeCodex) -> Option<Value> {
+    requests(fake)
+        .into_iter()
+        .find(|request| request["id"] == json!("approval-1"))
+}
+
+#[tokio::test]
+async fn approval_requests_are_surfaced_and_answered() {
+    let lines: Vec<&str> = APP_SERVER_APPROVAL.lines().collect();
+    let fake = common::fake_app_server(THREAD_ID, &lines, false);
+    let thread = on_request_thread(&fake);
+
+    let mut streamed = thread
+        .run_streamed("clean the build".into(), TurnOptions::default())
+        .expect("streamed");
+    let mut requested = Vec::new();
+    let mut completed = false;
+    while let Some(event) = streamed.events.next().await {
+        match event.expect("event") {
+            ThreadEvent::ApprovalRequested {
+                id,
+                kind,
+                command,
+                paths,
+            } => {
+                streamed
+                    .respond_approval(&id, ApprovalDecision::Approve)
+                    .expect("respond");
+                requested.push((id, kind, command, paths));
+            }
+            ThreadEvent::TurnCompleted { .. } => completed = true,
+            _ => {}
+        }
+    }
+
+    assert!(completed);
+    assert_eq!(
+        requested,
+        vec![(
+            "approval-1".to_string(),
+            ApprovalKind::CommandExecution,
+            Some("rm -rf target".to_string()),
+            Vec::new(),
+        )]
+    );
+    assert_eq!(
+        requests(&fake)[3]["params"]["approvalPolicy"],
+        json!("on-request")
+    );
+    assert_eq!(
+        approval_reply(&fake),
+        Some(json!({ "id": "approval-1", "result": { "decision": "accept" } }))
+    );
+}
+
+#[tokio::test]
+async fn legacy_approval_requests_are_declined() {
+    let legacy = format!(
+        r#"{{"id":"legacy-1","method":"execCommandApproval","params":{{"threadId":"{THREAD_ID}","conversationId":"{THREAD_ID}","callId":"call-1","command":["rm","-rf","target"],"cwd":"/tmp"}}}}"#
+    );
+    let lines: Vec<&str> = APP_SERVER_APPROVAL
+        .lines()
+        .map(|line| {
+            if line.contains("requestApproval") {
+                legacy.as_str()
+            } else {
+                line
+            }
+        })
+        .collect();
+    let fake = common::fake_app_server(THREAD_ID, &lines, false);
+    let thread = on_request_thread(&fake);
+
+    let mut streamed = thread
+        .run_streamed("clean the build".into(), TurnOptions::default())
+        .expect("streamed");
+    while let Some(event) = streamed.events.next().await {
+        assert!(!matches!(
+            event.expect("event"),
+            ThreadEvent::ApprovalRequested { .. }
+        ));
+    }
+
+    let mut reply = None;
+    for _ in 0..100 {
+        reply = requests(&fake)
+            .into_iter()
+            .find(|request| request["id"] == json!("legacy-1"));
+        if reply.is_some() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let reply = reply.expect("legacy reply");
+    assert_eq!(reply["error"]["code"], json!(-32601));
+}
+
+#[test]
+fn raw_streams_cannot_answer_approvals() {
+    let lines: Vec<&str> = APP_SERVER_APPROVAL.lines().collect();
+    let fake = common::fake_app_server(THREAD_ID, &lines, false);
+    let thread = on_request_thread(&fake);
+
+    let err = thread
+        .run_streamed_raw("clean the build".into(), TurnOptions::default())
+        .err()
+        .expect("approvals unsupported");
+    assert!(matches!(err, CodexError::ApprovalsUnsupported));
+    assert!(!thread.is_running());
+}
+
+#[tokio::test]
+async fn collected_turns_deny_unanswered_approvals() {
+    let lines: Vec<&str> = APP_SERVER_APPROVAL.lines().collect();
+    let fake = common::fake_app_server(THREAD_ID, &lines, false);
+    let thread = on_request_thread(&fake);
+
+    let turn = thread
+        .run("clean the build".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    assert_eq!(turn.final_response, "Cleaned.");
+    assert_eq!(
+        approval_reply(&fake),
+        Some(json!({ "id": "approval-1", "result": { "decision": "decline" } }))
+    );
+}
+
+#[tokio::test]
+async fn agent_message_deltas_are_translated() {
+    let fake = fake_server(false);
+    let thread = codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let events = thread
+        .run_streamed("list the files".into(), TurnOptions::default())
+        .expect("streamed")
+        .collect()
+        .await
+        .expect("events");
+
+    assert!(events.contains(&ThreadEvent::ItemDelta {
+        item_id: "item_1".to_string(),
+        delta: ItemDelta::AgentMessageText("Do".to_string()),
+    }));
+}
+
+#[tokio::test]
+async fn unset_approval_policy_matches_the_exec_backend() {
+    let fake = fake_server(false);
+    let thread = codex_for(&fake).start_thread(ThreadOptions::default());
+
+    thread
+        .run("list the files".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    let exec_policy = CodexExecArgs::default().effective_approval_policy();
+    assert!(matches!(exec_policy, ApprovalMode::Never));
+    let requests = requests(&fake);
+    assert_eq!(
+        requests[2]["params"]["approvalPolicy"],
+        json!(exec_policy.as_str())
+    );
+    assert_eq!(
+        requests[3]["params"]["approvalPolicy"],
+        json!(exec_policy.as_str())
+    );
+}
+
+#[tokio::test]
+async fn dropping_the_stream_interrupts_the_turn() {
+    let fake = fake_server(false);
+    let thread = codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let mut events = thread
+        .run_streamed("list the files".into(), TurnOptions::default())
+        .expect("streamed")
+        .events;
+    while let Some(event) = events.next().await {
+        if event.expect("event") == ThreadEvent::TurnStarted {
+            break;
+        }
+    }
+    drop(events);
+
+    let mut interrupt = None;
+    for _ in 0..100 {
+        interrupt = requests(&fake)
+            .into_iter()
+            .find(|request| request["method"] == json!("turn/interrupt"));
+        if interrupt.is_some() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let interrupt = interrupt.expect("turn/interrupt request");
+    assert!(interrupt["id"].is_u64());
+    assert_eq!(interrupt["params"]["threadId"], json!(THREAD_ID));
+    assert_eq!(interrupt["params"]["turnId"], json!("turn-1"));
+}
+
+#[tokio::test]
+async fn completed_turns_are_not_interrupted() {
+    let fake = fake_server(false);
+    let thread = codex_for(&fake).start_thread(ThreadOptions::default());
+
+    thread
+        .run("list the files".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    assert!(!methods(&fake).contains(&"turn/interrupt".to_string()));
+}
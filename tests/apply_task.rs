@@ -0,0 +1,187 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::collections::HashMap;
+
+use pretty_assertions::assert_eq;
+use tokio_util::sync::CancellationToken;
+
+use codex_sdk::{ApplyOptions, ApplyStatus, CodexError};
+
+const CLEAN_STDERR: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/apply_clean.stderr"
+);
+const CONFLICTS_STDERR: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/apply_conflicts.stderr"
+);
+
+#[tokio::test]
+async fn clean_apply_lists_changed_files() {
+    let fake = common::fake_codex(&[
+        "Applied patch to src/lib.rs cleanly.",
+        "Applied patch to README.md cleanly.",
+        "Successfully applied diff",
+    ]);
+
+    let outcome = common::codex_for(&fake)
+        .apply_task("task_e_68f1c2a9b4", ApplyOptions::default())
+        .await
+        .expect("apply");
+
+    assert_eq!(fake.recorded_args(), vec!["apply", "task_e_68f1c2a9b4"]);
+    assert_eq!(outcome.status, ApplyStatus::Applied);
+    assert!(outcome.is_clean());
+    assert_eq!(outcome.changed_files, vec!["src/lib.rs", "README.md"]);
+    assert!(outcome.conflicts.is_empty());
+}
+
+#[tokio::test]
+async fn clean_apply_reads_git_output_from_stderr() {
+    let fake = common::fake_codex_script(&format!(
+        "cat '{CLEAN_STDERR}' >&2\necho 'Successfully applied diff'\n"
+    ));
+
+    let outcome = common::codex_for(&fake)
+        .apply_task("task_e_68f1c2a9b4", ApplyOptions::default())
+        .await
+        .expect("apply");
+
+    assert_eq!(outcome.status, ApplyStatus::Applied);
+    assert_eq!(
+        outcome.changed_files,
+        vec!["docs/naïve guide.md", "src/lib.rs", "src/main.rs"]
+    );
+    assert!(outcome.output.starts_with("Successfully applied diff"));
+}
+
+#[tokio::test]
+async fn conflicting_apply_parses_git_three_way_output() {
+    let fake = common::fake_codex_script(&format!("cat '{CONFLICTS_STDERR}' >&2\nexit 1\n"));
+
+    let outcome = common::codex_for(&fake)
+        .apply_task("task_e_68f1c2a9b4", ApplyOptions::default())
+        .await
+        .expect("apply");
+
+    assert_eq!(outcome.status, ApplyStatus::Conflicted);
+    assert_eq!(
+        outcome.changed_files,
+        vec!["docs/naïve guide.md", "src/lib.rs", "src/main.rs"]
+    );
+    assert_eq!(
+        outcome.conflicts,
+        vec!["docs/naïve guide.md", "src/main.rs"]
+    );
+}
+
+#[tokio::test]
+async fn c_quoted_conflict_paths_are_unquoted() {
+    let fake = common::fake_codex_script(
+        "printf '%s\\n' 'U \"docs/na\\303\\257ve\\tguide \\\"v2\\\".md\"' >&2\nexit 1\n",
+    );
+
+    let outcome = common::codex_for(&fake)
+        .apply_task("task_e_68f1c2a9b4", ApplyOptions::default())
+        .await
+        .expect("apply");
+
+    assert_eq!(outcome.conflicts, vec!["docs/naïve\tguide \"v2\".md"]);
+}
+
+#[tokio::test]
+async fn conflicting_apply_reports_conflicts_from_stdout_and_stderr() {
+    let fake = common::fake_codex_script(
+        "echo 'Applied patch to src/lib.rs with conflicts.'\n\
+         echo 'U src/lib.rs'\n\
+         echo 'error: patch failed: src/main.rs:12' >&2\n\
+         echo 'error: src/main.rs: patch does not apply' >&2\n\
+         exit 1\n",
+    );
+
+    let outcome = common::codex_for(&fake)
+        .apply_task("task_e_68f1c2a9b4", ApplyOptions::default())
+        .await
+        .expect("apply");
+
+    assert_eq!(outcome.status, ApplyStatus::Conflicted);
+    assert_eq!(outcome.changed_files, vec!["src/lib.rs"]);
+    assert_eq!(outcome.conflicts, vec!["src/lib.rs", "src/main.rs"]);
+    assert!(outcome.output.contains("patch does not apply"));
+}
+
+#[tokio::test]
+async fn failed_apply_without_conflicts_is_an_error() {
+    let fake = common::fake_codex_script("echo 'Error: task not found' >&2\nexit 1\n");
+
+    let result = common::codex_for(&fake)
+        .apply_task("task_missing", ApplyOptions::default())
+        .await;
+
+    assert!(matches!(result, Err(CodexError::ExecFailed(code, stderr))
+        if code == "code 1" && stderr.contains("task not found")));
+}
+
+#[tokio::test]
+async fn apply_passes_extra_env() {
+    let fake = common::fake_codex_script("echo \"Applied patch to $APPLY_TARGET cleanly.\"\n");
+
+    let outcome = common::codex_for(&fake)
+        .apply_task(
+            "task_e_68f1c2a9b4",
+            ApplyOptions {
+                env: Some(HashMap::from([(
+                    "APPLY_TARGET".to_string(),
+                    "docs/guide.md".to_string(),
+                )])),
+                ..ApplyOptions::default()
+            },
+        )
+        .await
+        .expect("apply");
+
+    assert_eq!(outcome.changed_files, vec!["docs/guide.md"]);
+}
+
+#[tokio::test]
+async fn cancelled_apply_is_aborted() {
+    let fake = common::fake_codex(&["Applied patch to src/lib.rs cleanly."]);
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+
+    let result = common::codex_for(&fake)
+        .apply_task(
+            "task_e_68f1c2a9b4",
+            ApplyOptions {
+                cancel: Some(cancel),
+                ..ApplyOptions::default()
+            },
+        )
+        .await;
+
+    assert!(matches!(result, Err(CodexError::Aborted)));
+}
+
+#[tokio::test]
+async fn apply_rejects_invalid_task_ids() {
+    let fake = common::fake_codex(&[]);
+
+    let result = common::codex_for(&fake)
+        .apply_task("task 1; rm -rf", ApplyOptions::default())
+        .await;
+
+    assert!(matches!(result, Err(CodexError::InvalidArgs(_))));
+}
+
+#[tokio::test]
+async fn apply_rejects_task_ids_that_look_like_flags() {
+    let fake = common::fake_codex(&[]);
+
+    let result = common::codex_for(&fake)
+        .apply_task("--help", ApplyOptions::default())
+        .await;
+
+    assert!(matches!(result, Err(CodexError::InvalidArgs(_))));
+}
@@ -0,0 +1,61 @@
+#![cfg(unix)]
+
+mod common;
+
+use codex_sdk::{ApprovalDecision, ApprovalMode, CodexError, ThreadOptions, TurnOptions};
+
+#[test]
+fn interactive_approval_modes_fail_fast_on_exec() {
+    let fake = common::fake_codex(&[r#"{"type":"turn.started"}"#]);
+    let codex = common::codex_for(&fake);
+
+    for mode in [
+        ApprovalMode::OnRequest,
+        ApprovalMode::OnFailure,
+        ApprovalMode::Untrusted,
+    ] {
+        let thread = codex.start_thread(ThreadOptions {
+            approval_policy: Some(mode),
+            ..ThreadOptions::default()
+        });
+        let err = thread
+            .run_streamed("hello".into(), TurnOptions::default())
+            .err()
+            .expect("approvals unsupported");
+        assert!(matches!(err, CodexError::ApprovalsUnsupported));
+        assert!(!thread.is_running());
+    }
+}
+
+#[tokio::test]
+async fn exec_turns_cannot_answer_approvals() {
+    let fake = common::fake_codex(&[r#"{"type":"turn.started"}"#]);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        approval_policy: Some(ApprovalMode::Never),
+        ..ThreadOptions::default()
+    });
+
+    let streamed = thread
+        .run_streamed("hello".into(), TurnOptions::default())
+        .expect("streamed");
+    let err = streamed
+        .respond_approval("approval-1", ApprovalDecision::Approve)
+        .expect_err("no responder");
+    assert!(matches!(err, CodexError::ApprovalsUnsupported));
+}
+
+#[test]
+fn raw_streams_reject_interactive_approval_modes() {
+    let fake = common::fake_codex(&[r#"{"type":"turn.started"}"#]);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        approval_policy: Some(ApprovalMode::OnRequest),
+        ..ThreadOptions::default()
+    });
+
+    let err = thread
+        .run_streamed_raw("hello".into(), TurnOptions::default())
+        .err()
+        .expect("approvals unsupported");
+    assert!(matches!(err, CodexError::ApprovalsUnsupported));
+    assert!(!thread.is_running());
+}
@@ -0,0 +1,144 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::sync::Mutex;
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{AuthMethod, AuthStatus, CodexError};
+
+static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct CaptureLogger;
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        RECORDS
+            .lock()
+            .expect("records")
+            .push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+async fn status_for(script: &str) -> Result<AuthStatus, CodexError> {
+    let fake = common::fake_codex_script(script);
+    let status = common::codex_for(&fake).auth_status().await;
+    assert_eq!(fake.recorded_args(), vec!["login", "status"]);
+    status
+}
+
+#[tokio::test]
+async fn chatgpt_login_is_authenticated() {
+    let status = status_for("echo 'Logged in using ChatGPT' >&2\n")
+        .await
+        .expect("status");
+
+    assert_eq!(
+        status,
+        AuthStatus::Authenticated {
+            method: AuthMethod::ChatGpt,
+            plan: None,
+        }
+    );
+    assert!(status.is_authenticated());
+}
+
+#[tokio::test]
+async fn chatgpt_login_reports_plan() {
+    let status = status_for("echo 'Logged in using ChatGPT (Pro plan)'\n")
+        .await
+        .expect("status");
+
+    assert_eq!(
+        status,
+        AuthStatus::Authenticated {
+            method: AuthMethod::ChatGpt,
+            plan: Some("Pro".to_string()),
+        }
+    );
+}
+
+#[tokio::test]
+async fn api_key_login_is_authenticated() {
+    let status = status_for("echo 'Logged in using an API key - sk-proj-***ABCD' >&2\n")
+        .await
+        .expect("status");
+
+    assert_eq!(
+        status,
+        AuthStatus::Authenticated {
+            method: AuthMethod::ApiKey,
+            plan: None,
+        }
+    );
+}
+
+#[tokio::test]
+async fn not_logged_in_is_not_authenticated() {
+    let status = status_for("echo 'Not logged in' >&2\nexit 1\n")
+        .await
+        .expect("status");
+
+    assert_eq!(status, AuthStatus::NotAuthenticated);
+    assert!(!status.is_authenticated());
+}
+
+#[tokio::test]
+async fn unrecognized_failure_is_an_exec_error() {
+    let result = status_for("echo 'error: unrecognized subcommand status' >&2\nexit 2\n").await;
+
+    assert!(matches!(result, Err(CodexError::ExecFailed(code, stderr))
+        if code == "code 2" && stderr.contains("unrecognized subcommand")));
+}
+
+#[tokio::test]
+async fn login_with_api_key_passes_key_without_logging_it() {
+    log::set_logger(&CaptureLogger).expect("logger");
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let fake = common::fake_codex_script("echo 'Successfully logged in' >&2\n");
+    common::codex_for(&fake)
+        .login_with_api_key("sk-test-login-secret")
+        .await
+        .expect("login");
+
+    assert_eq!(
+        fake.recorded_args(),
+        vec!["login", "--api-key", "sk-test-login-secret"]
+    );
+    let records = RECORDS.lock().expect("records");
+    assert!(records
+        .iter()
+        .any(|message| message.contains("\"--api-key\", \"[redacted]\"")));
+    assert!(!records
+        .iter()
+        .any(|message| message.contains("sk-test-login-secret")));
+}
+
+#[tokio::test]
+async fn failed_login_redacts_key_from_stderr() {
+    let fake = common::fake_codex_script("echo \"invalid key: $3\" >&2\nexit 1\n");
+
+    let result = common::codex_for(&fake)
+        .login_with_api_key("sk-bad-key")
+        .await;
+
+    assert!(matches!(result, Err(CodexError::ExecFailed(_, stderr))
+        if stderr.contains("invalid key: [redacted]") && !stderr.contains("sk-bad-key")));
+}
+
+#[tokio::test]
+async fn login_rejects_empty_key() {
+    let fake = common::fake_codex(&[]);
+
+    let result = common::codex_for(&fake).login_with_api_key("").await;
+
+    assert!(matches!(result, Err(CodexError::InvalidArgs(_))));
+}
@@ -0,0 +1,89 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::time::Duration;
+
+use codex_sdk::{BudgetKind, CodexError, ThreadOptions, TurnOptions};
+
+fn item_line(index: usize) -> String {
+    format!(
+        r#"{{"type":"item.completed","item":{{"type":"command_execution","id":"item_{index}","command":"ls","aggregated_output":"","exit_code":0,"status":"completed"}}}}"#
+    )
+}
+
+#[tokio::test]
+async fn runaway_item_counts_stop_the_turn() {
+    let mut script =
+        String::from("cat > /dev/null\nprintf '%s\\n' '{\"type\":\"turn.started\"}'\n");
+    for index in 0..4 {
+        script.push_str(&format!("printf '%s\\n' '{}'\n", item_line(index)));
+    }
+    script.push_str("exec sleep 30\n");
+    let fake = common::fake_codex_script(&script);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(5),
+        thread.run(
+            "loop forever".into(),
+            TurnOptions {
+                max_output_items: Some(3),
+                ..TurnOptions::default()
+            },
+        ),
+    )
+    .await
+    .expect("turn stopped without waiting for codex");
+
+    assert!(matches!(
+        result,
+        Err(CodexError::BudgetExceeded {
+            kind: BudgetKind::OutputItems,
+            limit: 3,
+            observed: 4,
+        })
+    ));
+    assert!(!thread.is_running());
+}
+
+#[tokio::test]
+async fn usage_over_budget_fails_at_turn_completion() {
+    let lines = [
+        r#"{"type":"turn.started"}"#.to_string(),
+        item_line(0),
+        r#"{"type":"turn.completed","usage":{"input_tokens":10,"cached_input_tokens":4,"output_tokens":5}}"#
+            .to_string(),
+    ];
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let fake = common::fake_codex(&lines);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        require_response: false,
+        ..ThreadOptions::default()
+    });
+    let budget = |usage_budget| TurnOptions {
+        usage_budget: Some(usage_budget),
+        max_output_items: Some(1),
+        ..TurnOptions::default()
+    };
+
+    let over = thread.run("hello".into(), budget(12)).await;
+    assert!(matches!(
+        over,
+        Err(CodexError::BudgetExceeded {
+            kind: BudgetKind::Tokens,
+            limit: 12,
+            observed: 15,
+        })
+    ));
+
+    let within = common::codex_for(&fake)
+        .start_thread(ThreadOptions {
+            require_response: false,
+            ..ThreadOptions::default()
+        })
+        .run("hello".into(), budget(15))
+        .await
+        .expect("within budget");
+    assert_eq!(within.items.len(), 1);
+}
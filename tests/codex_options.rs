@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+use codex_sdk::{
+    CodexError, CodexOptions, ColorMode, ExecBackend, FinalResponseMode, HistoryPersistence,
+    ModelProviderConfig, ModelReasoningEffort, OutputLastMessage, TurnOptions, WebSearchFlagStyle,
+    WireApi,
+};
+
+fn base() -> CodexOptions {
+    CodexOptions {
+        codex_path_override: Some("/usr/local/bin/codex".into()),
+        base_url: Some("https://base.example.com".to_string()),
+        api_key: Some("base-key".to_string()),
+        config: Some(json!({
+            "approval_policy": "never",
+            "sandbox_workspace_write": { "network_access": false, "writable_roots": ["/tmp"] },
+        })),
+        env: Some(HashMap::from([("A".to_string(), "1".to_string())])),
+        ..CodexOptions::default()
+    }
+}
+
+#[test]
+fn empty_patch_leaves_base_unchanged() {
+    let patched = base().apply_patch(CodexOptions::default());
+
+    assert_eq!(patched.codex_path_override, base().codex_path_override);
+    assert_eq!(patched.base_url, base().base_url);
+    assert_eq!(patched.api_key, base().api_key);
+    assert_eq!(patched.config, base().config);
+    assert_eq!(patched.env, base().env);
+}
+
+#[test]
+fn patch_fields_replace_base_fields() {
+    let patched = base().apply_patch(CodexOptions {
+        api_key: Some("user-key".to_string()),
+        env: Some(HashMap::from([("B".to_string(), "2".to_string())])),
+        ..CodexOptions::default()
+    });
+
+    assert_eq!(patched.api_key, Some("user-key".to_string()));
+    assert_eq!(
+        patched.env,
+        Some(HashMap::from([("B".to_string(), "2".to_string())]))
+    );
+    assert_eq!(patched.base_url, base().base_url);
+}
+
+#[test]
+fn dangerous_bypass_patch_wins_when_set() {
+    let allowed = || CodexOptions {
+        allow_dangerous_bypass: Some(true),
+        ..CodexOptions::default()
+    };
+
+    let revoked = allowed().apply_patch(CodexOptions {
+        allow_dangerous_bypass: Some(false),
+        ..CodexOptions::default()
+    });
+    let kept = allowed().apply_patch(CodexOptions::default());
+
+    assert_eq!(revoked.allow_dangerous_bypass, Some(false));
+    assert_eq!(kept.allow_dangerous_bypass, Some(true));
+}
+
+#[test]
+fn config_patch_uses_merge_patch_semantics() {
+    let patched = base().apply_patch(CodexOptions {
+        config: Some(json!({
+            "approval_policy": null,
+            "sandbox_workspace_write": { "network_access": true },
+            "model": "gpt-5",
+        })),
+        ..CodexOptions::default()
+    });
+
+    assert_eq!(
+        patched.config,
+        Some(json!({
+            "sandbox_workspace_write": { "network_access": true, "writable_roots": ["/tmp"] },
+            "model": "gpt-5",
+        }))
+    );
+}
+
+#[test]
+fn config_patch_applies_without_base_config() {
+    let patched = CodexOptions::default().apply_patch(CodexOptions {
+        config: Some(json!({ "model": "gpt-5" })),
+        ..CodexOptions::default()
+    });
+
+    assert_eq!(patched.config, Some(json!({ "model": "gpt-5" })));
+}
+
+#[test]
+fn config_patch_without_base_config_drops_nulls() {
+    let patched = CodexOptions::default().apply_patch(CodexOptions {
+        config: Some(json!({
+            "approval_policy": null,
+            "sandbox_workspace_write": { "network_access": null, "writable_roots": ["/tmp"] },
+        })),
+        ..CodexOptions::default()
+    });
+
+    assert_eq!(
+        patched.config,
+        Some(json!({ "sandbox_workspace_write": { "writable_roots": ["/tmp"] } }))
+    );
+}
+
+#[test]
+fn with_env_collects_exactly_the_supplied_vars() {
+    let options = CodexOptions::default().with_env([("A", "1"), ("B", "2")]);
+
+    assert_eq!(
+        options.env,
+        Some(HashMap::from([
+            ("A".to_string(), "1".to_string()),
+            ("B".to_string(), "2".to_string()),
+        ]))
+    );
+}
+
+#[test]
+fn with_env_replaces_existing_vars() {
+    let options = base().with_env(vec![("B".to_string(), "2".to_string())]);
+
+    assert_eq!(
+        options.env,
+        Some(HashMap::from([("B".to_string(), "2".to_string())]))
+    );
+}
+
+#[test]
+fn add_env_var_extends_or_creates_env() {
+    let created = CodexOptions::default().add_env_var("A", "1");
+    let extended = base()
+        .add_env_var("B", String::from("2"))
+        .add_env_var("A", "3");
+
+    assert_eq!(
+        created.env,
+        Some(HashMap::from([("A".to_string(), "1".to_string())]))
+    );
+    assert_eq!(
+        extended.env,
+        Some(HashMap::from([
+            ("A".to_string(), "3".to_string()),
+            ("B".to_string(), "2".to_string()),
+        ]))
+    );
+}
+
+#[test]
+fn merge_config_deep_merges_into_existing_config() {
+    let options = CodexOptions {
+        config: Some(json!({ "a": 0, "b": 2, "nested": { "x": 1 } })),
+        ..CodexOptions::default()
+    }
+    .merge_config(json!({ "a": 1, "nested": { "y": 2 } }))
+    .expect("merge");
+
+    assert_eq!(
+        options.config,
+        Some(json!({ "a": 1, "b": 2, "nested": { "x": 1, "y": 2 } }))
+    );
+}
+
+#[test]
+fn merge_config_without_existing_config_uses_extra() {
+    let options = CodexOptions::default()
+        .merge_config(json!({ "a": 1 }))
+        .expect("merge");
+
+    assert_eq!(options.config, Some(json!({ "a": 1 })));
+}
+
+#[test]
+fn merge_config_without_existing_config_drops_nulls() {
+    let options = CodexOptions::default()
+        .merge_config(json!({ "a": 1, "b": null, "nested": { "c": null } }))
+        .expect("merge");
+
+    assert_eq!(options.config, Some(json!({ "a": 1, "nested": {} })));
+}
+
+#[test]
+fn merge_config_rejects_non_object_values() {
+    let err = CodexOptions::default()
+        .merge_config(json!(["a"]))
+        .expect_err("array config");
+
+    assert!(matches!(err, CodexError::InvalidConfigRoot));
+}
+
+#[cfg(not(feature = "app-server"))]
+#[test]
+fn app_server_backend_requires_feature() {
+    let result = codex_sdk::Codex::new(CodexOptions {
+        backend: Some(codex_sdk::ExecBackend::AppServer),
+        ..CodexOptions::default()
+    });
+
+    assert!(matches!(result, Err(CodexError::InvalidArgs(_))));
+}
+
+#[test]
+fn json_round_trip_preserves_every_field() {
+    let options = CodexOptions {
+        codex_home: Some("/home/me/.codex".into()),
+        default_turn_options: Some(TurnOptions {
+            output_schema: Some(json!({ "type": "object" })),
+            additional_directories: Some(vec!["/srv".to_string()]),
+            final_response_mode: Some(FinalResponseMode::Concatenate {
+                separator: "\n".to_string(),
+            }),
+            output_last_message: Some(OutputLastMessage::TempFile),
+            validate_output: Some(true),
+            profile: Some("ci".to_string()),
+            model_reasoning_effort: Some(ModelReasoningEffort::High),
+            preamble: Some("Be brief.".to_string()),
+            ..TurnOptions::default()
+        }),
+        allow_dangerous_bypass: Some(true),
+        model_providers: Some(HashMap::from([(
+            "local".to_string(),
+            ModelProviderConfig {
+                name: "Local".to_string(),
+                base_url: "http://localhost:8080/v1".to_string(),
+                env_key: Some("LOCAL_KEY".to_string()),
+                wire_api: Some(WireApi::Chat),
+                query_params: Some(HashMap::from([(
+                    "api-version".to_string(),
+                    "2025-01-01".to_string(),
+                )])),
+            },
+        )])),
+        notify_command: Some(vec!["notify-send".to_string(), "codex".to_string()]),
+        history: Some(HistoryPersistence::None),
+        web_search_flag_style: Some(WebSearchFlagStyle::Config),
+        color: Some(ColorMode::Never),
+        backend: Some(ExecBackend::AppServer),
+        ..base()
+    };
+
+    let encoded = options.to_json_str().expect("encode");
+    let decoded = CodexOptions::from_json_str(&encoded).expect("decode");
+
+    assert_eq!(decoded.codex_path_override, options.codex_path_override);
+    assert_eq!(decoded.codex_home, options.codex_home);
+    assert_eq!(decoded.base_url, options.base_url);
+    assert_eq!(decoded.api_key, Some("base-key".to_string()));
+    assert_eq!(decoded.config, options.config);
+    assert_eq!(decoded.env, options.env);
+    assert_eq!(decoded.allow_dangerous_bypass, Some(true));
+    assert_eq!(decoded.model_providers, options.model_providers);
+    assert_eq!(decoded.notify_command, options.notify_command);
+    assert_eq!(decoded.history, options.history);
+    assert_eq!(decoded.web_search_flag_style, options.web_search_flag_style);
+    assert_eq!(decoded.color, options.color);
+    assert_eq!(decoded.backend, options.backend);
+
+    let turn = decoded.default_turn_options.expect("default turn options");
+    let expected = options.default_turn_options.expect("default turn options");
+    assert_eq!(turn.output_schema, expected.output_schema);
+    assert_eq!(turn.additional_directories, expected.additional_directories);
+    assert_eq!(turn.final_response_mode, expected.final_response_mode);
+    assert_eq!(turn.output_last_message, expected.output_last_message);
+    assert_eq!(turn.validate_output, Some(true));
+    assert_eq!(turn.profile, expected.profile);
+    assert_eq!(
+        turn.model_reasoning_effort.map(|effort| effort.to_string()),
+        Some("high".to_string())
+    );
+    assert_eq!(turn.preamble, expected.preamble);
+}
+
+#[test]
+fn from_json_str_rejects_malformed_input() {
+    let err = CodexOptions::from_json_str("{\"api_key\": 42}").expect_err("invalid");
+
+    assert!(matches!(err, CodexError::Json(_)));
+}
@@ -0,0 +1,90 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serde_json::json;
+
+use codex_sdk::{Codex, CodexOptions, ShellEnvironmentPolicy, ThreadOptions, TurnOptions};
+
+static RECORDS: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+
+struct CaptureLogger;
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Debug
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            RECORDS
+                .lock()
+                .expect("records")
+                .push((record.level(), record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[tokio::test]
+async fn log_commands_env_logs_the_command_at_info_level() {
+    log::set_logger(&CaptureLogger).expect("logger");
+    log::set_max_level(log::LevelFilter::Debug);
+    std::env::set_var("CODEX_SDK_LOG_COMMANDS", "1");
+
+    let fake = common::fake_codex(&[
+        r#"{"type":"item.completed","item":{"type":"agent_message","id":"item-0","text":"done"}}"#,
+    ]);
+    let codex = Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        api_key: Some("sk-very-secret".to_string()),
+        config: Some(
+            json!({ "mcp_servers": { "github": { "bearer_token": "ghp-config-secret" } } }),
+        ),
+        ..CodexOptions::default()
+    })
+    .expect("codex");
+    codex
+        .start_thread(ThreadOptions {
+            model: Some("gpt-5-codex".to_string()),
+            base_instructions: Some("confidential base instructions".to_string()),
+            shell_environment_policy: Some(ShellEnvironmentPolicy {
+                set: Some(HashMap::from([(
+                    "DEPLOY_TOKEN".to_string(),
+                    "ghp-policy-secret".to_string(),
+                )])),
+                ..ShellEnvironmentPolicy::default()
+            }),
+            ..ThreadOptions::default()
+        })
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    let records = RECORDS.lock().expect("records");
+    let command = records
+        .iter()
+        .find(|(level, message)| {
+            *level == log::Level::Info && message.starts_with("Running codex command")
+        })
+        .map(|(_, message)| message)
+        .expect("command log");
+    assert!(command.contains("\"--model\", \"gpt-5-codex\""));
+    assert!(command.contains("CODEX_API_KEY"));
+    assert!(command.contains("base_instructions=[redacted]"));
+    for secret in [
+        "sk-very-secret",
+        "ghp-config-secret",
+        "ghp-policy-secret",
+        "confidential base instructions",
+    ] {
+        assert!(
+            records.iter().all(|(_, message)| !message.contains(secret)),
+            "{secret} was logged"
+        );
+    }
+}
@@ -0,0 +1,120 @@
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+use codex_sdk::{Codex, CodexOptions};
+
+pub const THREAD_ID: &str = "5f0c1a52-8d7e-4b3a-9c61-2e4f7a9b0d13";
+
+pub struct FakeCodex {
+    pub path: PathBuf,
+    args_path: PathBuf,
+    _dir: TempDir,
+}
+
+impl FakeCodex {
+    pub fn recorded_args(&self) -> Vec<String> {
+        fs::read_to_string(&self.args_path)
+            .expect("read recorded args")
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn dir(&self) -> &Path {
+        self._dir.path()
+    }
+}
+
+pub fn codex_for(fake: &FakeCodex) -> Codex {
+    Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        ..CodexOptions::default()
+    })
+    .expect("codex")
+}
+
+pub fn fake_codex(lines: &[&str]) -> FakeCodex {
+    let mut script = String::from("cat > /dev/null\n");
+    for line in lines {
+        script.push_str(&format!("printf '%s\\n' {}\n", quote(line)));
+    }
+    fake_codex_script(&script)
+}
+
+pub fn fake_codex_script(body: &str) -> FakeCodex {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::Builder::new()
+        .prefix("codex-fake-")
+        .tempdir()
+        .expect("temp dir");
+    let path = dir.path().join("codex");
+    let args_path = dir.path().join("args.txt");
+    let script = format!(
+        "#!/bin/sh\nprintf '%s\\n' \"$@\" > {}\n{}",
+        quote(&args_path.to_string_lossy()),
+        body
+    );
+    fs::write(&path, script).expect("write fake codex");
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("chmod fake codex");
+    FakeCodex {
+        path,
+        args_path,
+        _dir: dir,
+    }
+}
+
+pub fn fake_app_server(thread_id: &str, turn_lines: &[&str], crash_first_turn: bool) -> FakeCodex {
+    let split = turn_lines
+        .iter()
+        .position(|line| line.contains("requestApproval"))
+        .map(|index| index + 1)
+        .unwrap_or(turn_lines.len());
+    let mut emit_turn = String::new();
+    for (name, lines) in [
+        ("emit_turn", &turn_lines[..split]),
+        ("emit_after_reply", &turn_lines[split..]),
+    ] {
+        emit_turn.push_str(&format!("{name}() {{\n  :\n"));
+        for line in lines {
+            emit_turn.push_str(&format!("  printf '%s\\n' {}\n", quote(line)));
+        }
+        emit_turn.push_str("}\n");
+    }
+
+    let crash = if crash_first_turn {
+        "if [ \"$spawns\" -eq 1 ]; then exit 1; fi"
+    } else {
+        ":"
+    };
+    let thread = format!("{{\"thread\":{{\"id\":\"{thread_id}\"}}}}");
+    fake_codex_script(&format!(
+        r#"dir=$(dirname "$0")
+echo spawn >> "$dir/spawns.txt"
+spawns=$(wc -l < "$dir/spawns.txt")
+{emit_turn}while IFS= read -r line; do
+  printf '%s\n' "$line" >> "$dir/requests.jsonl"
+  id=$(printf '%s' "$line" | sed -n 's/^{{"id":\([0-9]*\),.*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*) printf '{{"id":%s,"result":{{}}}}\n' "$id" ;;
+    *'"method":"thread/start"'*|*'"method":"thread/resume"'*) printf '{{"id":%s,"result":%s}}\n' "$id" '{thread}' ;;
+    *'"method":"turn/interrupt"'*) printf '{{"id":%s,"result":{{}}}}\n' "$id" ;;
+    *'"method":"turn/start"'*)
+      printf '{{"id":%s,"result":{{"turn":{{"id":"turn-1"}}}}}}\n' "$id"
+      emit_turn | head -n 1
+      {crash}
+      emit_turn | tail -n +2 ;;
+    *'"result"'*) emit_after_reply ;;
+  esac
+done
+"#
+    ))
+}
+
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
@@ -0,0 +1,185 @@
+#![cfg(unix)]
+
+mod common;
+
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+use codex_sdk::{CodexError, ThreadOptions, TurnOptions};
+
+const SESSION_PREFIX: &str = "0199a213-81c0-7800-8aa1-";
+
+fn script(fail_compaction: bool) -> String {
+    let on_summarize = if fail_compaction {
+        "exit 1"
+    } else {
+        "text=Summary."
+    };
+    format!(
+        r#"prompt=$(cat)
+dir=$(dirname "$0")
+printf '%s\n' "$prompt" >> "$dir/prompts.txt"
+session=""
+for arg in "$@"; do
+  case "$arg" in {SESSION_PREFIX}*) session=$arg ;; esac
+done
+if [ -z "$session" ]; then
+  echo session >> "$dir/sessions.txt"
+  session=$(printf '{SESSION_PREFIX}%012d' "$(wc -l < "$dir/sessions.txt")")
+fi
+echo turn >> "$dir/$session.turns"
+turns=$(wc -l < "$dir/$session.turns")
+text=Done.
+case "$prompt" in
+  Summarize*) {on_summarize} ;;
+esac
+printf '{{"type":"thread.started","thread_id":"%s"}}\n' "$session"
+printf '%s\n' '{{"type":"turn.started"}}'
+printf '{{"type":"item.completed","item":{{"id":"item_0","type":"agent_message","text":"%s"}}}}\n' "$text"
+printf '{{"type":"turn.completed","usage":{{"input_tokens":%s,"cached_input_tokens":0,"output_tokens":50}}}}\n' "$((turns * 30000))"
+"#
+    )
+}
+
+fn session(index: u32) -> String {
+    format!("{SESSION_PREFIX}{index:012}")
+}
+
+fn prompts(fake: &common::FakeCodex) -> Vec<String> {
+    std::fs::read_to_string(fake.dir().join("prompts.txt"))
+        .expect("read prompts")
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn auto_compacting(threshold: f32) -> ThreadOptions {
+    ThreadOptions {
+        model_context_window: Some(100_000),
+        auto_compact_threshold: Some(threshold),
+        ..ThreadOptions::default()
+    }
+}
+
+#[tokio::test]
+async fn compact_moves_the_thread_to_a_session_seeded_with_the_summary() {
+    let fake = common::fake_codex_script(&script(false));
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+    thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    let summary = thread.compact().await.expect("compact");
+
+    assert_eq!(summary.final_response, "Summary.");
+    assert_eq!(thread.id().map(|id| id.to_string()), Some(session(2)));
+    let prompts = prompts(&fake);
+    assert_eq!(prompts[0], "hello");
+    assert!(prompts[1].starts_with("Summarize the conversation so far"));
+    assert!(prompts[2].starts_with("This conversation continues an earlier one"));
+    assert_eq!(prompts[3], "Summary.");
+    assert!(!fake.recorded_args().contains(&session(1)));
+}
+
+#[tokio::test]
+async fn compact_requires_a_started_thread() {
+    let fake = common::fake_codex_script(&script(false));
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let result = thread.compact().await;
+
+    assert!(matches!(result, Err(CodexError::InvalidArgs(_))));
+}
+
+#[tokio::test]
+async fn compact_ignores_the_default_output_schema() {
+    let fake = common::fake_codex_script(&script(false));
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        default_turn_options: Some(TurnOptions {
+            output_schema: Some(json!({ "type": "object" })),
+            ..TurnOptions::default()
+        }),
+        require_response: false,
+        ..ThreadOptions::default()
+    });
+    thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+    assert!(fake
+        .recorded_args()
+        .contains(&"--output-schema".to_string()));
+
+    thread.compact().await.expect("compact");
+
+    assert!(!fake
+        .recorded_args()
+        .contains(&"--output-schema".to_string()));
+}
+
+#[tokio::test]
+async fn high_context_usage_compacts_once_before_the_next_run() {
+    let fake = common::fake_codex_script(&script(false));
+    let thread = common::codex_for(&fake).start_thread(auto_compacting(0.8));
+
+    let mut turns = Vec::new();
+    for prompt in ["first", "second", "third", "fourth", "fifth"] {
+        turns.push(
+            thread
+                .run(prompt.into(), TurnOptions::default())
+                .await
+                .expect("turn"),
+        );
+    }
+
+    let compacted: Vec<bool> = turns.iter().map(|turn| turn.auto_compacted).collect();
+    assert_eq!(compacted, vec![false, false, false, true, false]);
+    let input_tokens: Vec<u64> = turns
+        .iter()
+        .map(|turn| turn.usage.as_ref().expect("usage").input_tokens)
+        .collect();
+    assert_eq!(input_tokens, vec![30_000, 60_000, 90_000, 60_000, 90_000]);
+    assert_eq!(thread.id().map(|id| id.to_string()), Some(session(2)));
+    assert!(fake
+        .recorded_args()
+        .contains(&"model_context_window=100000".to_string()));
+}
+
+#[tokio::test]
+async fn usage_below_the_threshold_does_not_compact() {
+    let fake = common::fake_codex_script(&script(false));
+    let thread = common::codex_for(&fake).start_thread(auto_compacting(0.95));
+
+    for prompt in ["first", "second", "third"] {
+        let turn = thread
+            .run(prompt.into(), TurnOptions::default())
+            .await
+            .expect("turn");
+        assert!(!turn.auto_compacted);
+    }
+
+    assert_eq!(prompts(&fake), vec!["first", "second", "third"]);
+}
+
+#[tokio::test]
+async fn failed_auto_compaction_still_runs_the_prompt() {
+    let fake = common::fake_codex_script(&script(true));
+    let thread = common::codex_for(&fake).start_thread(auto_compacting(0.5));
+    for prompt in ["first", "second"] {
+        thread
+            .run(prompt.into(), TurnOptions::default())
+            .await
+            .expect("turn");
+    }
+
+    let turn = thread
+        .run("third".into(), TurnOptions::default())
+        .await
+        .expect("turn after failed compaction");
+
+    assert!(!turn.auto_compacted);
+    assert_eq!(turn.final_response, "Done.");
+    assert_eq!(prompts(&fake).last().map(String::as_str), Some("third"));
+}
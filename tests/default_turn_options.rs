@@ -0,0 +1,177 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::time::Duration;
+
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tokio_util::sync::CancellationToken;
+
+use codex_sdk::{Codex, CodexError, CodexOptions, ThreadOptions, TurnOptions};
+
+const EVENTS: &[&str] = &[
+    r#"{"type":"thread.started","thread_id":"thread-1"}"#,
+    r#"{"type":"item.completed","item":{"type":"agent_message","id":"item-0","text":"done"}}"#,
+    r#"{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":1}}"#,
+];
+
+fn dirs(dir: &str) -> TurnOptions {
+    TurnOptions {
+        additional_directories: Some(vec![dir.to_string()]),
+        ..TurnOptions::default()
+    }
+}
+
+async fn add_dirs(
+    client: Option<TurnOptions>,
+    thread: Option<TurnOptions>,
+    turn: TurnOptions,
+) -> Vec<String> {
+    let fake = common::fake_codex(EVENTS);
+    let codex = Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        default_turn_options: client,
+        ..CodexOptions::default()
+    })
+    .expect("codex");
+    let thread = codex.start_thread(ThreadOptions {
+        default_turn_options: thread,
+        ..ThreadOptions::default()
+    });
+
+    thread.run("hello".into(), turn).await.expect("turn");
+
+    let args = fake.recorded_args();
+    args.windows(2)
+        .filter(|pair| pair[0] == "--add-dir")
+        .map(|pair| pair[1].clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn client_defaults_apply_when_nothing_else_is_set() {
+    assert_eq!(
+        add_dirs(Some(dirs("/client")), None, TurnOptions::default()).await,
+        vec!["/client"]
+    );
+}
+
+#[tokio::test]
+async fn thread_defaults_override_client_defaults() {
+    assert_eq!(
+        add_dirs(
+            Some(dirs("/client")),
+            Some(dirs("/thread")),
+            TurnOptions::default()
+        )
+        .await,
+        vec!["/thread"]
+    );
+}
+
+#[tokio::test]
+async fn turn_options_override_thread_and_client_defaults() {
+    assert_eq!(
+        add_dirs(Some(dirs("/client")), Some(dirs("/thread")), dirs("/turn")).await,
+        vec!["/turn"]
+    );
+    assert_eq!(
+        add_dirs(Some(dirs("/client")), None, dirs("/turn")).await,
+        vec!["/turn"]
+    );
+}
+
+#[test]
+fn merge_is_field_wise() {
+    let defaults = TurnOptions {
+        output_schema: Some(json!({ "type": "object" })),
+        additional_directories: Some(vec!["/thread".to_string()]),
+        ..TurnOptions::default()
+    };
+    let turn = TurnOptions {
+        cancel: Some(CancellationToken::new()),
+        validate_output: Some(true),
+        ..TurnOptions::default()
+    };
+
+    let merged = turn.merge(&defaults);
+
+    assert_eq!(merged.output_schema, Some(json!({ "type": "object" })));
+    assert_eq!(
+        merged.additional_directories,
+        Some(vec!["/thread".to_string()])
+    );
+    assert_eq!(merged.cancel.is_some(), true);
+    assert_eq!(merged.validate_output, Some(true));
+}
+
+#[test]
+fn merge_prefers_the_turn_cancel_token() {
+    let turn_token = CancellationToken::new();
+    let default_token = CancellationToken::new();
+    let merged = TurnOptions {
+        cancel: Some(turn_token.clone()),
+        ..TurnOptions::default()
+    }
+    .merge(&TurnOptions {
+        cancel: Some(default_token.clone()),
+        ..TurnOptions::default()
+    });
+
+    turn_token.cancel();
+    assert_eq!(merged.cancel.expect("token").is_cancelled(), true);
+    assert_eq!(default_token.is_cancelled(), false);
+}
+
+#[test]
+fn explicit_false_overrides_a_default_true() {
+    let merged = TurnOptions {
+        validate_output: Some(false),
+        ..TurnOptions::default()
+    }
+    .merge(&TurnOptions {
+        validate_output: Some(true),
+        ..TurnOptions::default()
+    });
+
+    assert_eq!(merged.validate_output, Some(false));
+}
+
+#[tokio::test]
+async fn thread_default_cancel_aborts_turn_with_its_own_token() {
+    let fake = common::fake_codex_script("cat > /dev/null\nsleep 5\n");
+    let thread_token = CancellationToken::new();
+    let codex = Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        ..CodexOptions::default()
+    })
+    .expect("codex");
+    let thread = codex.start_thread(ThreadOptions {
+        default_turn_options: Some(TurnOptions {
+            cancel: Some(thread_token.clone()),
+            ..TurnOptions::default()
+        }),
+        ..ThreadOptions::default()
+    });
+
+    let canceller = {
+        let thread_token = thread_token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            thread_token.cancel();
+        })
+    };
+    let result = thread
+        .run(
+            "hello".into(),
+            TurnOptions {
+                cancel: Some(CancellationToken::new()),
+                ..TurnOptions::default()
+            },
+        )
+        .await;
+    canceller.await.expect("canceller");
+
+    assert!(matches!(result, Err(CodexError::Aborted)));
+}
@@ -0,0 +1,125 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{Codex, CodexError, CodexOptions, ThreadOptions, TurnOptions};
+
+const OTHER_ID: &str = "9a3e6b10-47c2-4f8d-a5e1-0b7d2c4e6f89";
+
+fn write_session(home: &Path, id: &str) -> PathBuf {
+    let dir = home.join("sessions").join("2025").join("01").join("02");
+    fs::create_dir_all(&dir).expect("session dir");
+    let path = dir.join(format!("rollout-2025-01-02T09-00-00-{id}.jsonl"));
+    fs::write(
+        &path,
+        format!(
+            "{{\"type\":\"session_meta\",\"payload\":{{\"id\":\"{id}\",\"timestamp\":\"2025-01-02T09:00:00.000Z\",\"cwd\":\"/repo\"}}}}\n"
+        ),
+    )
+    .expect("write session");
+    path
+}
+
+fn codex_for(fake: &common::FakeCodex, home: &Path) -> Codex {
+    Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        codex_home: Some(home.to_path_buf()),
+        ..CodexOptions::default()
+    })
+    .expect("codex")
+}
+
+#[test]
+fn delete_thread_removes_rollout_files() {
+    let fake = common::fake_codex(&[]);
+    let home = tempfile::tempdir().expect("tempdir");
+    let session = write_session(home.path(), common::THREAD_ID);
+    let other = write_session(home.path(), OTHER_ID);
+
+    codex_for(&fake, home.path())
+        .delete_thread(common::THREAD_ID)
+        .expect("delete");
+
+    assert_eq!(session.exists(), false);
+    assert_eq!(other.exists(), true);
+}
+
+#[test]
+fn thread_delete_removes_its_own_session() {
+    let fake = common::fake_codex(&[]);
+    let home = tempfile::tempdir().expect("tempdir");
+    let session = write_session(home.path(), common::THREAD_ID);
+    let thread =
+        codex_for(&fake, home.path()).resume_thread(common::THREAD_ID, ThreadOptions::default());
+
+    thread.delete().expect("delete");
+    assert_eq!(session.exists(), false);
+}
+
+#[test]
+fn archive_moves_rollout_files() {
+    let fake = common::fake_codex(&[]);
+    let home = tempfile::tempdir().expect("tempdir");
+    let session = write_session(home.path(), common::THREAD_ID);
+
+    codex_for(&fake, home.path())
+        .archive_thread(common::THREAD_ID)
+        .expect("archive");
+
+    let archived = home
+        .path()
+        .join("archived_sessions")
+        .join(session.file_name().expect("file name"));
+    assert_eq!(session.exists(), false);
+    assert_eq!(archived.exists(), true);
+}
+
+#[test]
+fn delete_of_unknown_thread_is_not_found() {
+    let fake = common::fake_codex(&[]);
+    let home = tempfile::tempdir().expect("tempdir");
+    write_session(home.path(), OTHER_ID);
+
+    let result = codex_for(&fake, home.path()).delete_thread(common::THREAD_ID);
+    assert!(matches!(result, Err(CodexError::ThreadNotFound(id)) if id == common::THREAD_ID));
+}
+
+#[test]
+fn delete_refuses_running_threads() {
+    let fake = common::fake_codex(&[]);
+    let home = tempfile::tempdir().expect("tempdir");
+    let session = write_session(home.path(), common::THREAD_ID);
+    let codex = codex_for(&fake, home.path());
+    let thread = codex.resume_thread(common::THREAD_ID, ThreadOptions::default());
+
+    let turn = thread
+        .run_streamed("hello".into(), TurnOptions::default())
+        .expect("streamed");
+
+    let result = codex.delete_thread(common::THREAD_ID);
+    assert!(matches!(result, Err(CodexError::ThreadInUse(id)) if id == common::THREAD_ID));
+    let result = thread.clone().delete();
+    assert!(matches!(result, Err(CodexError::ThreadInUse(_))));
+    assert_eq!(session.exists(), true);
+
+    drop(turn);
+    codex
+        .delete_thread(common::THREAD_ID)
+        .expect("delete after turn");
+    assert_eq!(session.exists(), false);
+}
+
+#[test]
+fn delete_without_id_is_rejected() {
+    let fake = common::fake_codex(&[]);
+    let home = tempfile::tempdir().expect("tempdir");
+    let thread = codex_for(&fake, home.path()).start_thread(ThreadOptions::default());
+
+    let result = thread.delete();
+    assert!(matches!(result, Err(CodexError::InvalidArgs(_))));
+}
@@ -0,0 +1,24 @@
+use std::error::Error;
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::CodexError;
+
+#[test]
+fn contextual_errors_expose_their_cause_chain() {
+    let error = CodexError::Io(std::io::Error::other("disk full"))
+        .context("writing output schema")
+        .context("starting turn");
+
+    let mut chain = Vec::new();
+    let mut current: Option<&(dyn Error + 'static)> = Some(&error);
+    while let Some(error) = current {
+        chain.push(error.to_string());
+        current = error.source();
+    }
+
+    assert_eq!(
+        chain,
+        vec!["starting turn", "writing output schema", "disk full"]
+    );
+}
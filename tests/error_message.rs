@@ -0,0 +1,184 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{BudgetKind, CodexError};
+
+fn cases() -> Vec<(CodexError, &'static str)> {
+    vec![
+        (
+            CodexError::UnsupportedPlatform("plan9".to_string(), "mips".to_string()),
+            "unsupported platform",
+        ),
+        (
+            CodexError::InvalidConfigRoot,
+            "codex config overrides must be a plain object",
+        ),
+        (
+            CodexError::InvalidConfigKey,
+            "codex config override keys must be non-empty strings",
+        ),
+        (
+            CodexError::InvalidConfigNumber("retries".to_string()),
+            "codex config override must be a finite number",
+        ),
+        (
+            CodexError::InvalidConfigNull("retries".to_string()),
+            "codex config override cannot be null",
+        ),
+        (
+            CodexError::InvalidConfigValue("retries".to_string(), "[]".to_string()),
+            "unsupported codex config override value",
+        ),
+        (
+            CodexError::InvalidOutputSchema("not an object".to_string()),
+            "not an object",
+        ),
+        (
+            CodexError::OutputSchemaViolation {
+                errors: vec!["missing answer".to_string(), "extra field".to_string()],
+                raw: "{}".to_string(),
+            },
+            "missing answer",
+        ),
+        (
+            CodexError::OutputSchemaViolation {
+                errors: Vec::new(),
+                raw: "{}".to_string(),
+            },
+            "output does not match schema",
+        ),
+        (
+            CodexError::InvalidEvent("{\"type\":".to_string()),
+            "failed to parse event",
+        ),
+        (
+            CodexError::InvalidUtf8("invalid byte 0xff".to_string()),
+            "invalid byte 0xff",
+        ),
+        (
+            CodexError::ExecFailed("code 1".to_string(), "boom\n".to_string()),
+            "boom",
+        ),
+        (
+            CodexError::ExecFailed("signal".to_string(), String::new()),
+            "codex exec failed",
+        ),
+        (CodexError::Aborted, "codex exec aborted"),
+        (CodexError::Timeout, "timed out waiting for codex output"),
+        (
+            CodexError::InvalidTemplate("unclosed {{".to_string()),
+            "unclosed {{",
+        ),
+        (
+            CodexError::MissingTemplateVariable("name".to_string()),
+            "missing prompt template variable",
+        ),
+        (
+            CodexError::InvalidArgs("input must not be empty".to_string()),
+            "input must not be empty",
+        ),
+        (
+            CodexError::DangerousBypassNotAllowed,
+            "dangerously_bypass_approvals_and_sandbox requires CodexOptions::allow_dangerous_bypass",
+        ),
+        (
+            CodexError::InputTooLarge("20 MiB".to_string()),
+            "20 MiB",
+        ),
+        (
+            CodexError::InvalidInputFile("a.bin".to_string(), "not UTF-8".to_string()),
+            "not UTF-8",
+        ),
+        (
+            CodexError::ImageNotFound(PathBuf::from("/tmp/missing.png")),
+            "image not found",
+        ),
+        (
+            CodexError::ImageDownload("https://x".to_string(), "404".to_string()),
+            "404",
+        ),
+        (
+            CodexError::InvalidThreadId("\"x\" is not a UUID".to_string()),
+            "\"x\" is not a UUID",
+        ),
+        (
+            CodexError::ThreadNotFound("thread-1".to_string()),
+            "thread not found",
+        ),
+        (
+            CodexError::ThreadInUse("thread-1".to_string()),
+            "thread is running",
+        ),
+        (
+            CodexError::ConcurrentTurnNotAllowed,
+            "a turn is already running on this thread",
+        ),
+        (
+            CodexError::TurnFailed("rate limited".to_string()),
+            "rate limited",
+        ),
+        (
+            CodexError::MissingChildStream("stdout"),
+            "child process missing stream",
+        ),
+        (
+            CodexError::AppServer("process exited".to_string()),
+            "process exited",
+        ),
+        (
+            CodexError::ApprovalsUnsupported,
+            "interactive approvals need the app-server backend; codex exec cannot answer them",
+        ),
+        (
+            CodexError::BudgetExceeded {
+                kind: BudgetKind::OutputItems,
+                limit: 3,
+                observed: 4,
+            },
+            "budget exceeded",
+        ),
+        (
+            CodexError::Aborted.context("running review"),
+            "running review",
+        ),
+        (
+            CodexError::Io(std::io::Error::other("disk full")),
+            "I/O error",
+        ),
+        (
+            CodexError::Json(serde_json::from_str::<u8>("x").expect_err("invalid json")),
+            "JSON error",
+        ),
+    ]
+}
+
+#[test]
+fn message_returns_the_human_readable_part() {
+    for (error, expected) in cases() {
+        assert_eq!(error.message(), expected, "{error:?}");
+    }
+}
+
+#[test]
+fn message_cow_matches_display() {
+    for (error, _) in cases() {
+        assert_eq!(error.message_cow(), error.to_string(), "{error:?}");
+    }
+}
+
+#[test]
+fn message_cow_borrows_fixed_messages() {
+    for error in [
+        CodexError::Aborted,
+        CodexError::ApprovalsUnsupported,
+        CodexError::TurnFailed("x".to_string()).context("while testing"),
+    ] {
+        assert!(matches!(error.message_cow(), Cow::Borrowed(_)), "{error:?}");
+    }
+    assert!(matches!(
+        CodexError::TurnFailed("x".to_string()).message_cow(),
+        Cow::Owned(_)
+    ));
+}
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+use codex_sdk::{BudgetKind, CodexError};
+
+fn round_trip(error: CodexError) -> CodexError {
+    let encoded = serde_json::to_string(&error).expect("serialize");
+    serde_json::from_str(&encoded).expect("deserialize")
+}
+
+#[test]
+fn errors_round_trip_through_json() {
+    let errors = vec![
+        CodexError::UnsupportedPlatform("plan9".to_string(), "mips".to_string()),
+        CodexError::InvalidConfigRoot,
+        CodexError::OutputSchemaViolation {
+            errors: vec!["missing answer".to_string()],
+            raw: "{}".to_string(),
+        },
+        CodexError::ExecFailed("exit status: 1".to_string(), "boom".to_string()),
+        CodexError::Aborted,
+        CodexError::ImageNotFound(PathBuf::from("/tmp/missing.png")),
+        CodexError::ThreadNotFound("thread-1".to_string()),
+        CodexError::MissingChildStream("stdout"),
+        CodexError::AppServer("process exited".to_string()),
+        CodexError::ApprovalsUnsupported,
+        CodexError::BudgetExceeded {
+            kind: BudgetKind::Tokens,
+            limit: 1000,
+            observed: 1200,
+        },
+    ];
+
+    for error in errors {
+        let expected = error.to_string();
+        assert_eq!(round_trip(error).to_string(), expected);
+    }
+}
+
+#[test]
+fn serialized_shape_has_kind_fields_and_message() {
+    let value = serde_json::to_value(CodexError::InvalidInputFile(
+        "notes.bin".to_string(),
+        "binary".to_string(),
+    ))
+    .expect("serialize");
+
+    assert_eq!(
+        value,
+        json!({
+            "kind": "invalid_input_file",
+            "path": "notes.bin",
+            "reason": "binary",
+            "message": "input file notes.bin cannot be embedded: binary",
+        })
+    );
+}
+
+#[test]
+fn transparent_errors_keep_their_message() {
+    let io = CodexError::Io(std::io::Error::other("disk full"));
+    let json = CodexError::Json(serde_json::from_str::<u8>("nope").expect_err("invalid json"));
+    let json_message = json.to_string();
+
+    let io = round_trip(io);
+    let json = round_trip(json);
+
+    assert!(matches!(&io, CodexError::Io(_)));
+    assert_eq!(io.to_string(), "disk full");
+    assert!(matches!(&json, CodexError::Json(_)));
+    assert_eq!(json.to_string(), json_message);
+}
+
+#[test]
+fn contextual_errors_round_trip_with_their_source() {
+    let error = CodexError::ThreadNotFound("thread-1".to_string()).context("resuming thread");
+
+    let value = serde_json::to_value(&error).expect("serialize");
+    assert_eq!(
+        value,
+        json!({
+            "kind": "contextual",
+            "context": "resuming thread",
+            "source": {
+                "kind": "thread_not_found",
+                "id": "thread-1",
+                "message": "thread not found: thread-1",
+            },
+            "message": "resuming thread",
+        })
+    );
+
+    match serde_json::from_value(value).expect("deserialize") {
+        CodexError::Contextual { context, source } => {
+            assert_eq!(context, "resuming thread");
+            assert!(matches!(*source, CodexError::ThreadNotFound(id) if id == "thread-1"));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
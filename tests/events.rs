@@ -0,0 +1,137 @@
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{ItemDelta, ThreadError, ThreadEvent, ThreadItem, Usage};
+
+fn item() -> ThreadItem {
+    ThreadItem::AgentMessage {
+        id: "item-1".to_string(),
+        text: "hello".to_string(),
+    }
+}
+
+fn all_events() -> Vec<(ThreadEvent, bool, bool)> {
+    vec![
+        (
+            ThreadEvent::ThreadStarted {
+                thread_id: "thread-1".into(),
+            },
+            false,
+            false,
+        ),
+        (ThreadEvent::TurnStarted, false, false),
+        (
+            ThreadEvent::TurnCompleted {
+                usage: Usage {
+                    input_tokens: 1,
+                    cached_input_tokens: 0,
+                    output_tokens: 1,
+                },
+                rate_limits: None,
+            },
+            true,
+            false,
+        ),
+        (
+            ThreadEvent::TurnFailed {
+                error: ThreadError {
+                    message: "boom".to_string(),
+                    will_retry: None,
+                    attempt: None,
+                },
+            },
+            true,
+            false,
+        ),
+        (ThreadEvent::ItemStarted { item: item() }, false, true),
+        (ThreadEvent::ItemUpdated { item: item() }, false, true),
+        (ThreadEvent::ItemCompleted { item: item() }, false, true),
+        (
+            ThreadEvent::ItemDelta {
+                item_id: "item-1".to_string(),
+                delta: ItemDelta::AgentMessageText("hel".to_string()),
+            },
+            false,
+            false,
+        ),
+        (ThreadEvent::Heartbeat { elapsed_ms: 1500 }, false, false),
+        (
+            ThreadEvent::ThreadErrorEvent {
+                message: "stream error".to_string(),
+            },
+            true,
+            false,
+        ),
+    ]
+}
+
+#[test]
+fn is_terminal_matches_turn_end_and_error_events() {
+    for (event, terminal, _) in all_events() {
+        assert_eq!(event.is_terminal(), terminal, "{event:?}");
+    }
+}
+
+#[test]
+fn is_item_event_matches_item_lifecycle_events() {
+    for (event, _, item_event) in all_events() {
+        assert_eq!(event.is_item_event(), item_event, "{event:?}");
+    }
+}
+
+#[test]
+fn display_is_a_single_readable_line() {
+    let rendered: Vec<String> = all_events()
+        .into_iter()
+        .map(|(event, _, _)| event.to_string())
+        .collect();
+
+    assert_eq!(
+        rendered,
+        vec![
+            "[thread.started] thread_id=thread-1",
+            "[turn.started]",
+            "[turn.completed] tokens=2",
+            "[turn.failed] boom",
+            "[item.started] agent_message(id=item-1, len=5)",
+            "[item.updated] agent_message(id=item-1, len=5)",
+            "[item.completed] agent_message(id=item-1, len=5)",
+            "[item.delta] agent_message_text(id=item-1, len=3)",
+            "[heartbeat] elapsed_ms=1500",
+            "[error] stream error",
+        ]
+    );
+}
+
+#[test]
+fn display_summarizes_each_item_type() {
+    let items: Vec<ThreadItem> = serde_json::from_value(serde_json::json!([
+        { "type": "reasoning", "id": "r", "text": "think" },
+        { "type": "command_execution", "id": "c", "command": "git status", "aggregated_output": "", "exit_code": 0, "status": "completed" },
+        { "type": "command_execution", "id": "p", "command": "cargo test", "aggregated_output": "", "exit_code": null, "status": "in_progress" },
+        { "type": "file_change", "id": "f", "changes": [{ "path": "a.rs", "kind": "update" }], "status": "completed" },
+        { "type": "mcp_tool_call", "id": "m", "server": "docs", "tool": "search", "arguments": {}, "result": null, "error": null, "status": "completed" },
+        { "type": "web_search", "id": "w", "query": "rust sdk" },
+        { "type": "todo_list", "id": "t", "items": [{ "text": "a", "completed": false }] },
+        { "type": "error", "id": "e", "message": "oops" },
+    ]))
+    .expect("items");
+
+    let rendered: Vec<String> = items
+        .into_iter()
+        .map(|item| ThreadEvent::ItemCompleted { item }.to_string())
+        .collect();
+
+    assert_eq!(
+        rendered,
+        vec![
+            "[item.completed] reasoning(id=r, len=5)",
+            "[item.completed] command_execution(id=c, exit=0)",
+            "[item.completed] command_execution(id=p, running)",
+            "[item.completed] file_change(id=f, files=1)",
+            "[item.completed] mcp_tool_call(id=m, tool=docs/search)",
+            "[item.completed] web_search(id=w, query=\"rust sdk\")",
+            "[item.completed] todo_list(id=t, items=1)",
+            "[item.completed] error(id=e, message=\"oops\")",
+        ]
+    );
+}
@@ -1,7 +1,14 @@
+use std::collections::HashMap;
+
 use pretty_assertions::assert_eq;
 use serde_json::json;
 
-use codex_sdk::{CodexExec, CodexExecArgs};
+use codex_sdk::{
+    CodexError, CodexExec, CodexExecArgs, ColorMode, CommandSpec, ExecSubcommand,
+    ModelReasoningEffort, ModelVerbosity, ReasoningSummaryMode, SandboxMode,
+    SandboxWorkspaceWriteOptions, ShellEnvironmentInherit, ShellEnvironmentPolicy, Tools,
+    WebSearchFlagStyle, WebSearchMode,
+};
 
 #[test]
 fn config_overrides_become_toml_flags() {
@@ -42,7 +49,7 @@ fn resume_args_come_before_images() {
     let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
     let args = CodexExecArgs {
         input: "hello".to_string(),
-        thread_id: Some("thread-id".to_string()),
+        thread_id: Some("thread-id".into()),
         images: Some(vec!["img.png".to_string()]),
         ..Default::default()
     };
@@ -56,6 +63,230 @@ fn resume_args_come_before_images() {
     assert!(resume_index < image_index);
 }
 
+#[test]
+fn resume_last_comes_before_images() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        resume_last: true,
+        images: Some(vec!["img.png".to_string()]),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    let resume_index = spec
+        .args
+        .windows(2)
+        .position(|pair| pair == ["resume", "--last"]);
+    let image_index = spec.args.iter().position(|arg| arg == "--image");
+
+    assert_eq!(resume_index.is_some(), true);
+    assert_eq!(image_index.is_some(), true);
+    assert!(resume_index < image_index);
+}
+
+#[test]
+fn resume_last_with_thread_id_is_rejected() {
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        thread_id: Some("5f0c1a52-8d7e-4b3a-9c61-2e4f7a9b0d13".into()),
+        resume_last: true,
+        ..Default::default()
+    };
+
+    assert!(matches!(args.validate(), Err(CodexError::InvalidArgs(_))));
+}
+
+#[test]
+fn profile_comes_before_config_overrides() {
+    let exec = CodexExec::new(None, None, Some(json!({ "model": "o3" }))).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        profile: Some("fast".to_string()),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    let profile_index = spec
+        .args
+        .windows(2)
+        .position(|pair| pair == ["--profile", "fast"]);
+    let config_index = spec.args.iter().position(|arg| arg == "--config");
+
+    assert_eq!(profile_index, Some(2));
+    assert!(profile_index < config_index);
+}
+
+#[test]
+fn duplicate_additional_directories_are_collapsed() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        additional_directories: Some(vec![
+            "/repo/shared".to_string(),
+            "/repo/docs".to_string(),
+            "/repo/shared".to_string(),
+        ]),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert_eq!(add_dirs(&spec.args), vec!["/repo/shared", "/repo/docs"]);
+}
+
+#[test]
+fn relative_additional_directories_resolve_against_working_directory() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        working_directory: Some("/work/app".to_string()),
+        additional_directories: Some(vec![
+            "../lib".to_string(),
+            "/work/lib/../lib".to_string(),
+            "/abs/tools".to_string(),
+        ]),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert_eq!(
+        add_dirs(&spec.args),
+        vec!["/work/app/../lib", "/work/lib/../lib", "/abs/tools"]
+    );
+}
+
+#[test]
+fn output_last_message_file_becomes_flag() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        output_last_message_file: Some("/tmp/last.txt".into()),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert_pair(&spec.args, "--output-last-message", "/tmp/last.txt");
+}
+
+#[test]
+fn validate_accepts_minimal_args() {
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        base_url: Some("https://api.example.com/v1".to_string()),
+        web_search_mode: Some(WebSearchMode::Live),
+        web_search_enabled: Some(false),
+        ..Default::default()
+    };
+
+    assert!(args.validate().is_ok());
+}
+
+#[test]
+fn validate_rejects_empty_input() {
+    let args = CodexExecArgs::default();
+    assert!(matches!(args.validate(), Err(CodexError::InvalidArgs(_))));
+}
+
+#[test]
+fn validate_rejects_non_http_base_url() {
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        base_url: Some("api.example.com".to_string()),
+        ..Default::default()
+    };
+
+    assert!(matches!(args.validate(), Err(CodexError::InvalidArgs(_))));
+}
+
+#[test]
+fn duplicate_images_are_passed_once() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        images: Some(vec![
+            "a.png".to_string(),
+            "b.png".to_string(),
+            "a.png".to_string(),
+        ]),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        args.deduplicated_images(),
+        Some(vec!["a.png".to_string(), "b.png".to_string()])
+    );
+
+    let spec = exec.build_command(&args).expect("command spec");
+    let images: Vec<&str> = spec
+        .args
+        .windows(2)
+        .filter(|pair| pair[0] == "--image")
+        .map(|pair| pair[1].as_str())
+        .collect();
+    assert_eq!(images, vec!["a.png", "b.png"]);
+}
+
+#[test]
+fn validate_rejects_empty_image_path() {
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        images: Some(vec!["img.png".to_string(), String::new()]),
+        ..Default::default()
+    };
+
+    assert!(matches!(args.validate(), Err(CodexError::InvalidArgs(_))));
+}
+
+#[test]
+fn run_validates_before_spawning() {
+    let exec = CodexExec::new(Some("/nonexistent/codex".into()), None, None).expect("exec");
+    let result = exec.run(CodexExecArgs::default());
+    assert!(matches!(result, Err(CodexError::InvalidArgs(_))));
+}
+
+#[test]
+fn convenience_constructors_match_new() {
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        ..Default::default()
+    };
+    let expected = CodexExec::new(None, None, None)
+        .expect("exec")
+        .build_command(&args)
+        .expect("command spec");
+
+    for exec in [
+        CodexExec::default_exec().expect("default exec"),
+        CodexExec::from_executable("/usr/local/bin/codex").expect("exec"),
+    ] {
+        assert_eq!(exec.build_command(&args).expect("command spec"), expected);
+    }
+}
+
+#[test]
+fn codex_home_is_exported_to_the_cli() {
+    let exec = CodexExec::new(Some("codex".into()), None, None)
+        .expect("exec")
+        .with_codex_home("/tmp/codex-home");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert_eq!(
+        spec.env.get("CODEX_HOME").map(String::as_str),
+        Some("/tmp/codex-home")
+    );
+}
+
+fn add_dirs(args: &[String]) -> Vec<&str> {
+    args.windows(2)
+        .filter(|pair| pair[0] == "--add-dir")
+        .map(|pair| pair[1].as_str())
+        .collect()
+}
+
 fn assert_pair(args: &[String], key: &str, value: &str) {
     let mut found = false;
     for i in 0..args.len().saturating_sub(1) {
@@ -66,3 +297,539 @@ fn assert_pair(args: &[String], key: &str, value: &str) {
     }
     assert!(found, "pair {key} {value} missing");
 }
+
+#[test]
+fn extend_config_adds_per_call_config_flags() {
+    let exec = CodexExec::new(
+        Some("codex".into()),
+        None,
+        Some(json!({ "approval_policy": "never" })),
+    )
+    .expect("exec");
+    let mut args = CodexExecArgs {
+        input: "hello".to_string(),
+        ..Default::default()
+    };
+    args.extend_config("model_verbosity", json!("low"))
+        .extend_config(
+            "sandbox_workspace_write",
+            json!({ "network_access": false }),
+        );
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert_pair(&spec.args, "--config", "approval_policy=\"never\"");
+    assert_pair(&spec.args, "--config", "model_verbosity=\"low\"");
+    assert_pair(
+        &spec.args,
+        "--config",
+        "sandbox_workspace_write.network_access=false",
+    );
+}
+
+#[test]
+fn extend_config_rejects_null_values() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let mut args = CodexExecArgs {
+        input: "hello".to_string(),
+        ..Default::default()
+    };
+    args.extend_config("model", serde_json::Value::Null);
+
+    let err = exec.build_command(&args).expect_err("null config");
+    assert!(matches!(err, CodexError::InvalidConfigNull(path) if path == "model"));
+}
+
+#[test]
+fn dangerous_bypass_emits_flag() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        dangerously_bypass_approvals_and_sandbox: Some(true),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert!(spec
+        .args
+        .iter()
+        .any(|arg| arg == "--dangerously-bypass-approvals-and-sandbox"));
+}
+
+#[test]
+fn dangerous_bypass_conflicts_with_sandbox_mode() {
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        sandbox_mode: Some(SandboxMode::WorkspaceWrite),
+        dangerously_bypass_approvals_and_sandbox: Some(true),
+        ..Default::default()
+    };
+
+    let err = args.validate().expect_err("conflicting sandbox settings");
+    assert!(matches!(err, CodexError::InvalidArgs(message) if message.contains("sandbox_mode")));
+}
+
+#[test]
+fn base_instructions_become_a_config_flag() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        base_instructions: Some("Be terse.\nNo emoji.".to_string()),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert_pair(
+        &spec.args,
+        "--config",
+        "base_instructions=\"Be terse.\\nNo emoji.\"",
+    );
+}
+
+#[test]
+fn display_shows_only_the_base_instructions_length() {
+    let instructions = "x".repeat(20 * 1024);
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        base_instructions: Some(instructions.clone()),
+        ..Default::default()
+    };
+
+    let rendered = args.to_string();
+    assert!(rendered.contains("base_instructions_len: Some(20480)"));
+    assert!(!rendered.contains(&instructions));
+}
+
+#[test]
+fn sandbox_workspace_write_options_become_toml_flags() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        sandbox_workspace_write: Some(SandboxWorkspaceWriteOptions {
+            network_access: Some(true),
+            exclude_tmpdir_env_var: Some(true),
+            exclude_slash_tmp: Some(false),
+            writable_roots: Some(vec!["/work/my repo".into(), "/cache".into()]),
+        }),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert_pair(
+        &spec.args,
+        "--config",
+        "sandbox_workspace_write.network_access=true",
+    );
+    assert_pair(
+        &spec.args,
+        "--config",
+        "sandbox_workspace_write.exclude_tmpdir_env_var=true",
+    );
+    assert_pair(
+        &spec.args,
+        "--config",
+        "sandbox_workspace_write.exclude_slash_tmp=false",
+    );
+    assert_pair(
+        &spec.args,
+        "--config",
+        "sandbox_workspace_write.writable_roots=[\"/work/my repo\", \"/cache\"]",
+    );
+}
+
+#[test]
+fn typed_network_access_overrides_the_legacy_flag() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        network_access_enabled: Some(true),
+        sandbox_workspace_write: Some(SandboxWorkspaceWriteOptions {
+            network_access: Some(false),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    let network_flags: Vec<&String> = spec
+        .args
+        .iter()
+        .filter(|arg| arg.starts_with("sandbox_workspace_write.network_access="))
+        .collect();
+    assert_eq!(
+        network_flags,
+        vec!["sandbox_workspace_write.network_access=false"]
+    );
+}
+
+#[test]
+fn shell_environment_policy_becomes_toml_flags() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        shell_environment_policy: Some(ShellEnvironmentPolicy {
+            inherit: Some(ShellEnvironmentInherit::Core),
+            ignore_default_excludes: Some(true),
+            exclude: Some(vec!["AWS_*".to_string(), "*_TOKEN".to_string()]),
+            include_only: Some(vec!["PATH".to_string(), "HOME".to_string()]),
+            set: Some(
+                [
+                    ("CI".to_string(), "1".to_string()),
+                    ("GREETING".to_string(), "say \"hi\"".to_string()),
+                    ("my.var".to_string(), "x".to_string()),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+        }),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    let policy_flags: Vec<&String> = spec
+        .args
+        .iter()
+        .filter(|arg| arg.starts_with("shell_environment_policy."))
+        .collect();
+    assert_eq!(
+        policy_flags,
+        vec![
+            "shell_environment_policy.inherit=\"core\"",
+            "shell_environment_policy.ignore_default_excludes=true",
+            "shell_environment_policy.exclude=[\"AWS_*\", \"*_TOKEN\"]",
+            "shell_environment_policy.include_only=[\"PATH\", \"HOME\"]",
+            "shell_environment_policy.set={CI = \"1\", GREETING = \"say \\\"hi\\\"\", \"my.var\" = \"x\"}",
+        ]
+    );
+}
+
+#[test]
+fn empty_shell_environment_policy_emits_no_flags() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        shell_environment_policy: Some(ShellEnvironmentPolicy::default()),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert!(!spec
+        .args
+        .iter()
+        .any(|arg| arg.starts_with("shell_environment_policy")));
+}
+
+#[test]
+fn display_hides_shell_environment_policy_values() {
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        shell_environment_policy: Some(ShellEnvironmentPolicy {
+            set: Some(
+                [("API_TOKEN".to_string(), "secret".to_string())]
+                    .into_iter()
+                    .collect(),
+            ),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let rendered = args.to_string();
+    assert!(rendered.contains("set: Some(keys=[\"API_TOKEN\"])"));
+    assert!(!rendered.contains("secret"));
+}
+
+#[test]
+fn nested_config_keys_are_quoted_when_needed() {
+    let exec = CodexExec::new(
+        Some("codex".into()),
+        None,
+        Some(json!({ "model_providers": { "local.vllm": { "name": "vLLM" } } })),
+    )
+    .expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert_pair(
+        &spec.args,
+        "--config",
+        "model_providers.\"local.vllm\".name=\"vLLM\"",
+    );
+}
+
+#[test]
+fn oss_emits_flag_and_local_server_env() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        oss: Some(true),
+        local_model_server: Some("http://gpu-box:11434/v1".to_string()),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert!(spec.args.iter().any(|arg| arg == "--oss"));
+    assert_eq!(
+        spec.env.get("CODEX_OSS_BASE_URL").map(String::as_str),
+        Some("http://gpu-box:11434/v1")
+    );
+}
+
+#[test]
+fn explicit_model_provider_takes_precedence_over_oss() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        oss: Some(true),
+        local_model_server: Some("http://gpu-box:11434/v1".to_string()),
+        model_provider: Some("azure".to_string()),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert!(!spec.args.iter().any(|arg| arg == "--oss"));
+    assert_pair(&spec.args, "--config", "model_provider=\"azure\"");
+    assert_eq!(spec.env.get("CODEX_OSS_BASE_URL"), None);
+}
+
+#[test]
+fn tools_become_config_flags() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        tools: Some(Tools {
+            web_search: Some(true),
+            view_image: Some(false),
+            plan: Some(true),
+            apply_patch: Some(false),
+        }),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert_pair(&spec.args, "--config", "tools.web_search=true");
+    assert_pair(&spec.args, "--config", "tools.view_image=false");
+    assert_pair(&spec.args, "--config", "include_plan_tool=true");
+    assert_pair(&spec.args, "--config", "include_apply_patch_tool=false");
+}
+
+#[test]
+fn web_search_mode_takes_precedence_over_tools_web_search() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        web_search_mode: Some(WebSearchMode::Disabled),
+        tools: Some(Tools {
+            web_search: Some(true),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert_pair(&spec.args, "--config", "web_search=\"disabled\"");
+    assert!(!spec
+        .args
+        .iter()
+        .any(|arg| arg.starts_with("tools.web_search")));
+}
+
+#[test]
+fn verbosity_and_reasoning_summary_become_config_flags() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        model_verbosity: Some(ModelVerbosity::Low),
+        reasoning_summary: Some(ReasoningSummaryMode::None),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert_pair(&spec.args, "--config", "model_verbosity=\"low\"");
+    assert_pair(&spec.args, "--config", "model_reasoning_summary=\"none\"");
+}
+
+#[test]
+fn reasoning_effort_is_emitted_once() {
+    let exec = CodexExec::new(
+        Some("codex".into()),
+        None,
+        Some(json!({ "model_reasoning_effort": "low", "approval_policy": "never" })),
+    )
+    .expect("exec");
+    let mut args = CodexExecArgs {
+        input: "hello".to_string(),
+        model_reasoning_effort: Some(ModelReasoningEffort::High),
+        ..Default::default()
+    };
+    args.extend_config("model_reasoning_effort", json!("medium"));
+
+    let spec = exec.build_command(&args).expect("command spec");
+    let efforts: Vec<&String> = spec
+        .args
+        .iter()
+        .filter(|arg| arg.starts_with("model_reasoning_effort="))
+        .collect();
+    assert_eq!(efforts, vec!["model_reasoning_effort=\"high\""]);
+    assert_pair(&spec.args, "--config", "approval_policy=\"never\"");
+}
+
+fn web_search_args(exec: CodexExec, mode: WebSearchMode) -> Vec<String> {
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        web_search_mode: Some(mode),
+        ..Default::default()
+    };
+    exec.build_command(&args).expect("command spec").args
+}
+
+#[test]
+fn flag_style_emits_search_flag_for_live_search() {
+    let exec = || {
+        CodexExec::new(Some("codex".into()), None, None)
+            .expect("exec")
+            .with_web_search_flag_style(WebSearchFlagStyle::Flag)
+    };
+
+    let args = web_search_args(exec(), WebSearchMode::Live);
+    assert!(args.iter().any(|arg| arg == "--search"));
+    assert!(!args.iter().any(|arg| arg.starts_with("web_search=")));
+
+    let args = web_search_args(exec(), WebSearchMode::Cached);
+    assert!(!args.iter().any(|arg| arg == "--search"));
+    assert_pair(&args, "--config", "web_search=\"cached\"");
+}
+
+#[test]
+fn config_style_always_uses_the_config_override() {
+    let exec = CodexExec::new(Some("codex".into()), None, None)
+        .expect("exec")
+        .with_web_search_flag_style(WebSearchFlagStyle::Config)
+        .with_cli_version("codex-cli 0.60.0");
+
+    let args = web_search_args(exec, WebSearchMode::Live);
+    assert!(!args.iter().any(|arg| arg == "--search"));
+    assert_pair(&args, "--config", "web_search=\"live\"");
+}
+
+#[test]
+fn auto_style_picks_the_flag_from_the_cli_version() {
+    let live_args = |version: Option<&str>| {
+        let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+        let exec = match version {
+            Some(version) => exec.with_cli_version(version),
+            None => exec,
+        };
+        web_search_args(exec, WebSearchMode::Live)
+    };
+
+    assert!(live_args(Some("codex-cli 0.60.0"))
+        .iter()
+        .any(|arg| arg == "--search"));
+    assert_pair(
+        &live_args(Some("codex-cli 0.30.1")),
+        "--config",
+        "web_search=\"live\"",
+    );
+    assert_pair(&live_args(None), "--config", "web_search=\"live\"");
+}
+
+#[test]
+fn color_mode_emits_flag() {
+    let exec = CodexExec::new(Some("codex".into()), None, None)
+        .expect("exec")
+        .with_color(ColorMode::Always);
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert_pair(&spec.args, "--color", "always");
+    assert_eq!(spec.env.get("NO_COLOR"), None);
+}
+
+#[test]
+fn color_never_sets_no_color_env() {
+    let exec = CodexExec::new(Some("codex".into()), None, None)
+        .expect("exec")
+        .with_color(ColorMode::Never);
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert_pair(&spec.args, "--color", "never");
+    assert_eq!(spec.env.get("NO_COLOR").map(String::as_str), Some("1"));
+}
+
+#[test]
+fn subcommand_is_emitted_after_flags() {
+    let exec = CodexExec::new(Some("codex".into()), None, None).expect("exec");
+    let args = CodexExecArgs {
+        model: Some("gpt-5-codex".to_string()),
+        subcommand: Some(ExecSubcommand::Review {
+            args: vec!["--base".to_string(), "main".to_string()],
+        }),
+        ..Default::default()
+    };
+
+    let spec = exec.build_command(&args).expect("command spec");
+    assert_eq!(
+        spec.args,
+        vec![
+            "exec",
+            "--experimental-json",
+            "--model",
+            "gpt-5-codex",
+            "review",
+            "--base",
+            "main"
+        ]
+    );
+    assert!(args.validate().is_ok());
+}
+
+#[test]
+fn subcommand_cannot_resume_a_thread() {
+    let args = CodexExecArgs {
+        input: "hello".to_string(),
+        resume_last: true,
+        subcommand: Some(ExecSubcommand::Review { args: Vec::new() }),
+        ..Default::default()
+    };
+
+    assert!(matches!(args.validate(), Err(CodexError::InvalidArgs(_))));
+}
+
+#[test]
+fn masked_env_redacts_sensitive_values() {
+    let spec = CommandSpec {
+        args: Vec::new(),
+        env: HashMap::from([
+            ("CODEX_API_KEY".to_string(), "sk-live".to_string()),
+            ("github_token".to_string(), "ghp_123".to_string()),
+            ("Db_Password".to_string(), "hunter2".to_string()),
+            ("AWS_SECRET_ACCESS_KEY".to_string(), "aws".to_string()),
+            ("PATH".to_string(), "/usr/bin".to_string()),
+            ("CODEX_HOME".to_string(), "/home/me/.codex".to_string()),
+        ]),
+    };
+
+    let masked = spec.masked_env();
+
+    for key in [
+        "CODEX_API_KEY",
+        "github_token",
+        "Db_Password",
+        "AWS_SECRET_ACCESS_KEY",
+    ] {
+        assert_eq!(masked[key], "[redacted]", "{key}");
+    }
+    assert_eq!(masked["PATH"], "/usr/bin");
+    assert_eq!(masked["CODEX_HOME"], "/home/me/.codex");
+    assert_eq!(spec.env["CODEX_API_KEY"], "sk-live");
+}
@@ -0,0 +1,219 @@
+#![cfg(unix)]
+
+mod common;
+
+use pretty_assertions::assert_eq;
+
+use std::path::Path;
+
+use codex_sdk::{FinalResponseMode, OutputLastMessage, ThreadOptions, TurnOptions};
+
+const TRANSCRIPT: &[&str] = &[
+    r#"{"type":"thread.started","thread_id":"thread-1"}"#,
+    r#"{"type":"turn.started"}"#,
+    r#"{"type":"item.completed","item":{"type":"agent_message","id":"item-0","text":"Plan: inspect the tests"}}"#,
+    r#"{"type":"item.completed","item":{"type":"reasoning","id":"item-1","text":"thinking"}}"#,
+    r#"{"type":"item.completed","item":{"type":"agent_message","id":"item-2","text":"The tests pass"}}"#,
+    r#"{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":1}}"#,
+];
+
+const SILENT_TRANSCRIPT: &[&str] = &[
+    r#"{"type":"thread.started","thread_id":"thread-1"}"#,
+    r#"{"type":"turn.started"}"#,
+    r#"{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":1}}"#,
+];
+
+fn writes_last_message(transcript: &[&str], contents: &str) -> common::FakeCodex {
+    let mut script = format!(
+        "prev=''\nfor arg in \"$@\"; do\n  if [ \"$prev\" = '--output-last-message' ]; then printf '%s' '{contents}' > \"$arg\"; fi\n  prev=\"$arg\"\ndone\ncat > /dev/null\n"
+    );
+    for line in transcript {
+        script.push_str(&format!("printf '%s\\n' '{line}'\n"));
+    }
+    common::fake_codex_script(&script)
+}
+
+async fn final_response(mode: FinalResponseMode) -> String {
+    let fake = common::fake_codex(TRANSCRIPT);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    thread
+        .run(
+            "hello".into(),
+            TurnOptions {
+                final_response_mode: Some(mode),
+                ..TurnOptions::default()
+            },
+        )
+        .await
+        .expect("turn")
+        .final_response
+}
+
+#[tokio::test]
+async fn last_message_is_the_default() {
+    assert_eq!(
+        final_response(FinalResponseMode::default()).await,
+        "The tests pass"
+    );
+}
+
+#[tokio::test]
+async fn first_message_mode_keeps_the_first_message() {
+    assert_eq!(
+        final_response(FinalResponseMode::FirstMessage).await,
+        "Plan: inspect the tests"
+    );
+}
+
+#[tokio::test]
+async fn concatenate_mode_joins_all_messages() {
+    assert_eq!(
+        final_response(FinalResponseMode::Concatenate {
+            separator: "\n---\n".to_string(),
+        })
+        .await,
+        "Plan: inspect the tests\n---\nThe tests pass"
+    );
+}
+
+#[tokio::test]
+async fn last_message_temp_file_is_used_without_agent_messages_and_cleaned_up() {
+    let fake = writes_last_message(SILENT_TRANSCRIPT, "The full, untruncated answer");
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let turn = thread
+        .run(
+            "hello".into(),
+            TurnOptions {
+                output_last_message: Some(OutputLastMessage::TempFile),
+                ..TurnOptions::default()
+            },
+        )
+        .await
+        .expect("turn");
+    assert_eq!(turn.final_response, "The full, untruncated answer");
+
+    let args = fake.recorded_args();
+    let index = args
+        .iter()
+        .position(|arg| arg == "--output-last-message")
+        .expect("flag");
+    assert_eq!(Path::new(&args[index + 1]).exists(), false);
+}
+
+#[tokio::test]
+async fn last_message_path_is_read_and_kept() {
+    let fake = writes_last_message(SILENT_TRANSCRIPT, "Answer from file");
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir.path().join("answer.txt");
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let turn = thread
+        .run(
+            "hello".into(),
+            TurnOptions {
+                output_last_message: Some(OutputLastMessage::Path(path.clone())),
+                ..TurnOptions::default()
+            },
+        )
+        .await
+        .expect("turn");
+    assert_eq!(turn.final_response, "Answer from file");
+    assert_eq!(path.exists(), true);
+}
+
+#[tokio::test]
+async fn explicit_last_message_overrides_a_thread_default() {
+    let fake = common::fake_codex(TRANSCRIPT);
+    let codex = common::codex_for(&fake);
+    let thread_options = || ThreadOptions {
+        default_turn_options: Some(TurnOptions {
+            final_response_mode: Some(FinalResponseMode::FirstMessage),
+            ..TurnOptions::default()
+        }),
+        ..ThreadOptions::default()
+    };
+
+    let turn = codex
+        .start_thread(thread_options())
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+    assert_eq!(turn.final_response, "Plan: inspect the tests");
+
+    let turn = codex
+        .start_thread(thread_options())
+        .run(
+            "hello".into(),
+            TurnOptions {
+                final_response_mode: Some(FinalResponseMode::LastMessage),
+                ..TurnOptions::default()
+            },
+        )
+        .await
+        .expect("turn");
+    assert_eq!(turn.final_response, "The tests pass");
+}
+
+#[tokio::test]
+async fn last_message_file_does_not_override_the_response_mode() {
+    let fake = writes_last_message(TRANSCRIPT, "The tests pass");
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let turn = thread
+        .run(
+            "hello".into(),
+            TurnOptions {
+                final_response_mode: Some(FinalResponseMode::FirstMessage),
+                output_last_message: Some(OutputLastMessage::TempFile),
+                ..TurnOptions::default()
+            },
+        )
+        .await
+        .expect("turn");
+    assert_eq!(turn.final_response, "Plan: inspect the tests");
+}
+
+#[tokio::test]
+async fn stale_last_message_path_is_removed_before_the_turn() {
+    let fake = common::fake_codex(SILENT_TRANSCRIPT);
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir.path().join("answer.txt");
+    std::fs::write(&path, "Answer from an earlier turn").expect("write stale file");
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        require_response: false,
+        ..ThreadOptions::default()
+    });
+
+    let turn = thread
+        .run(
+            "hello".into(),
+            TurnOptions {
+                output_last_message: Some(OutputLastMessage::Path(path.clone())),
+                ..TurnOptions::default()
+            },
+        )
+        .await
+        .expect("turn");
+    assert_eq!(turn.final_response, "");
+    assert_eq!(path.exists(), false);
+}
+
+#[tokio::test]
+async fn missing_last_message_file_falls_back_to_agent_message() {
+    let fake = common::fake_codex(TRANSCRIPT);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let turn = thread
+        .run(
+            "hello".into(),
+            TurnOptions {
+                output_last_message: Some(OutputLastMessage::TempFile),
+                ..TurnOptions::default()
+            },
+        )
+        .await
+        .expect("turn");
+    assert_eq!(turn.final_response, "The tests pass");
+}
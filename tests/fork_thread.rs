@@ -0,0 +1,125 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{Codex, CodexError, CodexOptions, ThreadOptions, TurnOptions};
+
+const SCRIPT: &str = r#"prompt=$(cat)
+id=5f0c1a52-8d7e-4b3a-9c61-2e4f7a9b0d13
+prev=''
+for arg in "$@"; do
+  if [ "$prev" = resume ]; then id=$arg; fi
+  prev=$arg
+done
+dir="$CODEX_HOME/sessions/2025/01/02"
+file="$dir/rollout-2025-01-02T09-00-00-$id.jsonl"
+if [ ! -f "$file" ]; then
+  mkdir -p "$dir"
+  printf '%s\n' "{\"type\":\"session_meta\",\"payload\":{\"id\":\"$id\",\"timestamp\":\"2025-01-02T09:00:00.000Z\",\"cwd\":\"/repo\"}}" > "$file"
+fi
+printf '%s\n' "{\"type\":\"response_item\",\"payload\":{\"type\":\"message\",\"role\":\"user\",\"content\":[{\"type\":\"input_text\",\"text\":\"$prompt\"}]}}" >> "$file"
+printf '%s\n' "{\"type\":\"thread.started\",\"thread_id\":\"$id\"}"
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","id":"item-0","text":"done"}}'
+printf '%s\n' '{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":1}}'
+"#;
+const ORIGINAL_ID: &str = "5f0c1a52-8d7e-4b3a-9c61-2e4f7a9b0d13";
+
+fn session_path(home: &Path, id: &str) -> PathBuf {
+    home.join("sessions")
+        .join("2025")
+        .join("01")
+        .join("02")
+        .join(format!("rollout-2025-01-02T09-00-00-{id}.jsonl"))
+}
+
+fn codex_for(fake: &common::FakeCodex, home: &Path) -> Codex {
+    Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        codex_home: Some(home.to_path_buf()),
+        ..CodexOptions::default()
+    })
+    .expect("codex")
+}
+
+#[tokio::test]
+async fn fork_copies_the_session_under_a_new_id() {
+    let fake = common::fake_codex_script(SCRIPT);
+    let home = tempfile::tempdir().expect("codex home");
+    let thread = codex_for(&fake, home.path()).start_thread(ThreadOptions::default());
+    thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("first turn");
+
+    let fork = thread.fork().expect("fork");
+    let fork_id = fork.id().expect("fork id");
+    assert_ne!(fork_id.as_str(), ORIGINAL_ID);
+    fork_id.validate().expect("fork id is a UUID");
+
+    let copied = fs::read_to_string(session_path(home.path(), fork_id.as_str())).expect("copy");
+    let meta: serde_json::Value =
+        serde_json::from_str(copied.lines().next().expect("meta")).expect("meta json");
+    assert_eq!(meta["payload"]["id"], fork_id.as_str());
+    assert!(copied.contains("hello"));
+}
+
+#[tokio::test]
+async fn fork_turns_stay_out_of_the_parent_session() {
+    let fake = common::fake_codex_script(SCRIPT);
+    let home = tempfile::tempdir().expect("codex home");
+    let thread = codex_for(&fake, home.path()).start_thread(ThreadOptions::default());
+    thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("first turn");
+
+    let fork = thread.fork().expect("fork");
+    let fork_id = fork.id().expect("fork id");
+    fork.run("try another approach".into(), TurnOptions::default())
+        .await
+        .expect("fork turn");
+
+    let args = fake.recorded_args();
+    assert!(args
+        .windows(2)
+        .any(|pair| pair == ["resume", fork_id.as_str()]));
+    assert_eq!(fork.id(), Some(fork_id.clone()));
+    assert_eq!(thread.id(), Some(ORIGINAL_ID.into()));
+
+    let parent = fs::read_to_string(session_path(home.path(), ORIGINAL_ID)).expect("parent");
+    let forked = fs::read_to_string(session_path(home.path(), fork_id.as_str())).expect("fork");
+    assert!(!parent.contains("try another approach"));
+    assert!(forked.contains("hello"));
+    assert!(forked.contains("try another approach"));
+}
+
+#[tokio::test]
+async fn fork_of_unstarted_thread_starts_fresh() {
+    let fake = common::fake_codex_script(SCRIPT);
+    let home = tempfile::tempdir().expect("codex home");
+    let thread = codex_for(&fake, home.path()).start_thread(ThreadOptions::default());
+
+    let fork = thread.fork().expect("fork");
+    fork.run("hello".into(), TurnOptions::default())
+        .await
+        .expect("fork turn");
+
+    assert_eq!(fork.id(), Some(ORIGINAL_ID.into()));
+    assert_eq!(thread.id(), None);
+}
+
+#[test]
+fn fork_of_a_missing_session_fails() {
+    let fake = common::fake_codex_script(SCRIPT);
+    let home = tempfile::tempdir().expect("codex home");
+    let thread = codex_for(&fake, home.path()).resume_thread(ORIGINAL_ID, ThreadOptions::default());
+
+    let result = thread.fork();
+
+    assert!(matches!(result, Err(CodexError::ThreadNotFound(_))));
+}
@@ -0,0 +1,136 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::path::PathBuf;
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{
+    Codex, CodexError, CodexOptions, HealthCheckResult, ThreadOptions, TurnOptions,
+    WebSearchFlagStyle, WebSearchMode,
+};
+
+fn codex_at(path: PathBuf) -> Codex {
+    Codex::new(CodexOptions {
+        codex_path_override: Some(path),
+        ..CodexOptions::default()
+    })
+    .expect("codex")
+}
+
+#[tokio::test]
+async fn health_check_reports_version_and_path() {
+    let fake = common::fake_codex(&["codex-cli 0.60.1"]);
+
+    let health = codex_at(fake.path.clone())
+        .health_check()
+        .await
+        .expect("health");
+
+    assert_eq!(
+        health,
+        HealthCheckResult {
+            version: "0.60.1".to_string(),
+            executable_path: fake.path.clone(),
+        }
+    );
+    assert_eq!(fake.recorded_args(), vec!["--version"]);
+}
+
+#[tokio::test]
+async fn health_check_falls_back_to_version_subcommand() {
+    let fake = common::fake_codex_script(
+        "if [ \"$1\" = --version ]; then echo \"error: unexpected argument '--version'\" >&2; exit 2; fi\necho 'codex v0.39.0'\n",
+    );
+
+    let health = codex_at(fake.path.clone())
+        .health_check()
+        .await
+        .expect("health");
+
+    assert_eq!(health.version, "0.39.0");
+    assert_eq!(fake.recorded_args(), vec!["version"]);
+}
+
+#[tokio::test]
+async fn health_check_reports_non_zero_exit() {
+    let fake = common::fake_codex_script("echo 'broken install' >&2\nexit 3\n");
+
+    let result = codex_at(fake.path.clone()).health_check().await;
+
+    assert!(matches!(result, Err(CodexError::ExecFailed(code, stderr))
+        if code == "code 3" && stderr.contains("broken install")));
+}
+
+#[tokio::test]
+async fn health_check_reports_missing_binary() {
+    let dir = tempfile::tempdir().expect("temp dir");
+
+    let result = codex_at(dir.path().join("codex")).health_check().await;
+
+    assert!(matches!(result, Err(CodexError::Io(error))
+        if error.kind() == std::io::ErrorKind::NotFound));
+}
+
+#[tokio::test]
+async fn health_check_version_selects_the_auto_web_search_flag() {
+    let fake = common::fake_codex_script(concat!(
+        "if [ \"$1\" = --version ]; then echo 'codex-cli 0.60.0'; exit 0; fi\n",
+        "cat > /dev/null\n",
+        "printf '%s\\n' '{\"type\":\"thread.started\",\"thread_id\":\"5f0c1a52-8d7e-4b3a-9c61-2e4f7a9b0d13\"}'\n",
+        "printf '%s\\n' '{\"type\":\"item.completed\",\"item\":{\"type\":\"agent_message\",\"id\":\"item-0\",\"text\":\"done\"}}'\n",
+        "printf '%s\\n' '{\"type\":\"turn.completed\",\"usage\":{\"input_tokens\":1,\"cached_input_tokens\":0,\"output_tokens\":1}}'\n",
+    ));
+    let codex = Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        web_search_flag_style: Some(WebSearchFlagStyle::Auto),
+        ..CodexOptions::default()
+    })
+    .expect("codex");
+    let thread = codex.start_thread(ThreadOptions {
+        web_search_mode: Some(WebSearchMode::Live),
+        ..ThreadOptions::default()
+    });
+
+    thread
+        .run("before".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+    assert!(!fake.recorded_args().iter().any(|arg| arg == "--search"));
+
+    codex.health_check().await.expect("health");
+    thread
+        .run("after".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+    assert!(fake.recorded_args().iter().any(|arg| arg == "--search"));
+}
+
+#[tokio::test]
+async fn health_check_rejects_output_without_a_version() {
+    let fake = common::fake_codex(&["codex-cli dev build"]);
+
+    let result = codex_at(fake.path.clone()).health_check().await;
+
+    assert!(matches!(result, Err(CodexError::ExecFailed(reason, output))
+        if reason == "no version in output" && output.contains("dev build")));
+}
+
+#[tokio::test]
+async fn health_check_reports_the_executable_found_on_path() {
+    let fake = common::fake_codex(&["codex-cli 0.60.1"]);
+    let codex = Codex::new(CodexOptions {
+        codex_path_override: Some(PathBuf::from("codex")),
+        env: Some(std::collections::HashMap::from([(
+            "PATH".to_string(),
+            format!("{}:/usr/bin:/bin", fake.dir().display()),
+        )])),
+        ..CodexOptions::default()
+    })
+    .expect("codex");
+
+    let health = codex.health_check().await.expect("health");
+
+    assert_eq!(health.executable_path, fake.path);
+}
@@ -0,0 +1,87 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::time::Duration;
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{ThreadEvent, ThreadOptions, TurnOptions};
+
+fn slow_fake() -> common::FakeCodex {
+    common::fake_codex_script(concat!(
+        "cat > /dev/null\n",
+        "printf '%s\\n' '{\"type\":\"turn.started\"}'\n",
+        "sleep 0.5\n",
+        "printf '%s\\n' '{\"type\":\"item.completed\",\"item\":{\"type\":\"agent_message\",\"id\":\"item_0\",\"text\":\"done\"}}'\n",
+        "printf '%s\\n' '{\"type\":\"turn.completed\",\"usage\":{\"input_tokens\":1,\"cached_input_tokens\":0,\"output_tokens\":1}}'\n",
+        "sleep 0.5\n",
+    ))
+}
+
+fn heartbeat(interval_ms: u64) -> TurnOptions {
+    TurnOptions {
+        heartbeat: Some(Duration::from_millis(interval_ms)),
+        ..TurnOptions::default()
+    }
+}
+
+#[tokio::test]
+async fn heartbeats_fill_silent_periods_until_turn_completion() {
+    let fake = slow_fake();
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let events = thread
+        .run_streamed("hello".into(), heartbeat(100))
+        .expect("streamed")
+        .collect()
+        .await
+        .expect("events");
+
+    let types: Vec<&str> = events.iter().map(ThreadEvent::event_type).collect();
+    let heartbeats: Vec<u64> = events
+        .iter()
+        .filter_map(|event| match event {
+            ThreadEvent::Heartbeat { elapsed_ms } => Some(*elapsed_ms),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(types.first(), Some(&"turn.started"));
+    assert_eq!(types.last(), Some(&"turn.completed"));
+    assert!(heartbeats.len() >= 2, "{types:?}");
+    assert!(heartbeats.len() <= 5, "{types:?}");
+    assert!(heartbeats[0] >= 100, "{heartbeats:?}");
+    assert!(heartbeats.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[tokio::test]
+async fn heartbeats_do_not_reach_collected_turns() {
+    let fake = slow_fake();
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let turn = thread
+        .run("hello".into(), heartbeat(100))
+        .await
+        .expect("turn");
+
+    assert_eq!(turn.items.len(), 1);
+    assert_eq!(turn.final_response, "done");
+}
+
+#[tokio::test]
+async fn no_heartbeats_without_the_option() {
+    let fake = slow_fake();
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let events = thread
+        .run_streamed("hello".into(), TurnOptions::default())
+        .expect("streamed")
+        .collect()
+        .await
+        .expect("events");
+
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, ThreadEvent::Heartbeat { .. })));
+}
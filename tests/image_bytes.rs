@@ -0,0 +1,77 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::path::Path;
+
+use futures::StreamExt;
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{
+    Codex, CodexOptions, ImageFormat, Input, Thread, ThreadOptions, TurnOptions, UserInput,
+};
+
+#[test]
+fn image_bytes_are_written_to_a_temp_file() {
+    let input = Input::Structured(vec![
+        UserInput::Text {
+            text: "What is in this screenshot?".to_string(),
+        },
+        UserInput::ImageBytes {
+            data: b"fake-png".to_vec(),
+            format: ImageFormat::Png,
+        },
+    ]);
+
+    let (_, images, files) = Thread::normalize_input(&input).expect("normalize");
+    assert_eq!(images.len(), 1);
+    assert_eq!(files.len(), 1);
+    assert!(images[0].ends_with(".png"));
+    assert_eq!(std::fs::read(&images[0]).expect("image"), b"fake-png");
+
+    drop(files);
+    assert_eq!(Path::new(&images[0]).exists(), false);
+}
+
+#[tokio::test]
+async fn image_bytes_file_lives_until_the_stream_finishes() {
+    let fake = common::fake_codex(&[
+        r#"{"type":"thread.started","thread_id":"thread-1"}"#,
+        r#"{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":1}}"#,
+    ]);
+    let codex = Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        ..CodexOptions::default()
+    })
+    .expect("codex");
+    let thread = codex.start_thread(ThreadOptions::default());
+
+    let input = Input::Structured(vec![
+        UserInput::Text {
+            text: "Describe".to_string(),
+        },
+        UserInput::ImageBytes {
+            data: b"fake-jpeg".to_vec(),
+            format: ImageFormat::Jpeg,
+        },
+    ]);
+    let mut events = thread
+        .run_streamed(input, TurnOptions::default())
+        .expect("streamed")
+        .events;
+
+    events.next().await.expect("event").expect("ok");
+    let args = fake.recorded_args();
+    let image_index = args
+        .iter()
+        .position(|arg| arg == "--image")
+        .expect("image flag");
+    let image_path = args[image_index + 1].clone();
+    assert!(image_path.ends_with(".jpeg"));
+    assert_eq!(Path::new(&image_path).exists(), true);
+
+    while let Some(event) = events.next().await {
+        event.expect("ok");
+    }
+    assert_eq!(Path::new(&image_path).exists(), false);
+}
@@ -0,0 +1,106 @@
+#![cfg(unix)]
+
+mod common;
+
+use futures::StreamExt;
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{ItemDelta, ThreadEvent, ThreadItem, ThreadOptions, TurnOptions};
+
+const ITEM_DELTAS: &str = include_str!("fixtures/item_deltas.jsonl");
+
+fn fixture_lines() -> Vec<&'static str> {
+    ITEM_DELTAS.lines().collect()
+}
+
+#[tokio::test]
+async fn deltas_are_streamed_in_order() {
+    let fake = common::fake_codex(&fixture_lines());
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let mut streamed = thread
+        .run_streamed("run the tests".into(), TurnOptions::default())
+        .expect("streamed");
+    let mut deltas = Vec::new();
+    while let Some(event) = streamed.events.next().await {
+        if let ThreadEvent::ItemDelta { item_id, delta } = event.expect("event") {
+            deltas.push((item_id, delta));
+        }
+    }
+
+    assert_eq!(
+        deltas,
+        vec![
+            (
+                "item_0".to_string(),
+                ItemDelta::CommandOutput("running 2 tests\n".to_string())
+            ),
+            (
+                "item_1".to_string(),
+                ItemDelta::AgentMessageText("All ".to_string())
+            ),
+            (
+                "item_0".to_string(),
+                ItemDelta::CommandOutput("test result: ok\n".to_string())
+            ),
+            ("item_1".to_string(), ItemDelta::Other),
+            (
+                "item_1".to_string(),
+                ItemDelta::AgentMessageText("tests pass.".to_string())
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn collected_turn_is_unchanged_by_deltas() {
+    let with_deltas = common::fake_codex(&fixture_lines());
+    let without_deltas: Vec<&str> = fixture_lines()
+        .into_iter()
+        .filter(|line| !line.contains("\"item.delta\""))
+        .collect();
+    let without_deltas = common::fake_codex(&without_deltas);
+
+    let mut turns = Vec::new();
+    for fake in [&with_deltas, &without_deltas] {
+        let thread = common::codex_for(fake).start_thread(ThreadOptions::default());
+        turns.push(
+            thread
+                .run("run the tests".into(), TurnOptions::default())
+                .await
+                .expect("turn"),
+        );
+    }
+
+    assert_eq!(turns[0], turns[1]);
+    assert_eq!(turns[0].final_response, "All tests pass.");
+}
+
+#[tokio::test]
+async fn deltas_fill_items_completed_without_text() {
+    let lines: Vec<String> = fixture_lines()
+        .into_iter()
+        .map(|line| {
+            line.replace(
+                "\"aggregated_output\":\"running 2 tests\\ntest result: ok\\n\"",
+                "\"aggregated_output\":\"\"",
+            )
+            .replace("\"text\":\"All tests pass.\"", "\"text\":\"\"")
+        })
+        .collect();
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let fake = common::fake_codex(&lines);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let turn = thread
+        .run("run the tests".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    assert_eq!(turn.final_response, "All tests pass.");
+    assert!(matches!(
+        &turn.items[0],
+        ThreadItem::CommandExecution { aggregated_output, .. }
+            if aggregated_output == "running 2 tests\ntest result: ok\n"
+    ));
+}
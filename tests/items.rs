@@ -0,0 +1,506 @@
+use pretty_assertions::assert_eq;
+
+use serde_json::json;
+
+use codex_sdk::{
+    AgentMessageItem, CodeBlock, CommandExecutionItem, FileChangeItem, FileUpdateChange,
+    McpToolCallItem, PatchApplyStatus, PatchChangeKind, ThreadItem, WebSearchHit, WebSearchItem,
+    WebSearchResult,
+};
+
+fn message(text: &str) -> AgentMessageItem {
+    AgentMessageItem {
+        id: "item-1".to_string(),
+        kind: "agent_message".to_string(),
+        text: text.to_string(),
+    }
+}
+
+#[test]
+fn code_blocks_are_extracted_with_languages() {
+    let item = message(
+        "Run this first:\n\n```bash\ncargo test\n```\n\nThen update the code:\n\n```rust title=lib.rs\nfn main() {\n    println!(\"hi\");\n}\n```\n\nAnd the notes:\n\n~~~\nplain text\n~~~\n",
+    );
+
+    assert_eq!(
+        item.code_blocks(),
+        vec![
+            CodeBlock {
+                language: Some("bash".to_string()),
+                source: "cargo test".to_string(),
+            },
+            CodeBlock {
+                language: Some("rust".to_string()),
+                source: "fn main() {\n    println!(\"hi\");\n}".to_string(),
+            },
+            CodeBlock {
+                language: None,
+                source: "plain text".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn nested_shorter_fences_stay_inside_the_block() {
+    let item = message("````markdown\n```python\nprint(1)\n```\n````");
+
+    assert_eq!(
+        item.code_blocks(),
+        vec![CodeBlock {
+            language: Some("markdown".to_string()),
+            source: "```python\nprint(1)\n```".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn unclosed_block_runs_to_the_end() {
+    let item = message("Partial:\n```json\n{\"a\": 1}");
+
+    assert_eq!(
+        item.code_blocks(),
+        vec![CodeBlock {
+            language: Some("json".to_string()),
+            source: "{\"a\": 1}".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn text_without_fences_has_no_blocks() {
+    assert_eq!(message("Just `inline` code").code_blocks(), Vec::new());
+}
+
+#[test]
+fn web_search_item_without_result_still_parses() {
+    let item: WebSearchItem = serde_json::from_value(json!({
+        "id": "item-1",
+        "type": "web_search",
+        "query": "tokio select",
+    }))
+    .expect("web search item");
+
+    assert_eq!(item.query, "tokio select");
+    assert_eq!(item.result, None);
+}
+
+#[test]
+fn web_search_item_with_result_parses() {
+    let item: WebSearchItem = serde_json::from_value(json!({
+        "id": "item-1",
+        "type": "web_search",
+        "query": "tokio select",
+        "result": {
+            "urls": ["https://docs.rs/tokio"],
+            "snippet": "Waits on multiple concurrent branches",
+        },
+    }))
+    .expect("web search item");
+
+    assert_eq!(
+        item.result,
+        Some(WebSearchResult {
+            urls: vec!["https://docs.rs/tokio".to_string()],
+            snippet: Some("Waits on multiple concurrent branches".to_string()),
+        })
+    );
+}
+
+#[test]
+fn web_search_thread_item_tolerates_missing_result() {
+    let item: ThreadItem = serde_json::from_value(json!({
+        "type": "web_search",
+        "id": "item-1",
+        "query": "tokio select",
+    }))
+    .expect("thread item");
+
+    assert_eq!(
+        item,
+        ThreadItem::WebSearch {
+            id: "item-1".to_string(),
+            query: "tokio select".to_string(),
+            result: None,
+            results: Vec::new(),
+        }
+    );
+}
+
+#[test]
+fn web_search_results_parse_as_hits() {
+    let item: WebSearchItem = serde_json::from_value(json!({
+        "id": "item-1",
+        "type": "web_search",
+        "query": "tokio select",
+        "results": [
+            { "url": "https://docs.rs/tokio", "title": "tokio - Rust", "snippet": "An async runtime" },
+            { "url": "https://tokio.rs" },
+        ],
+    }))
+    .expect("web search item");
+
+    assert_eq!(item.result, None);
+    assert_eq!(
+        item.results,
+        vec![
+            WebSearchHit {
+                url: "https://docs.rs/tokio".to_string(),
+                title: Some("tokio - Rust".to_string()),
+                snippet: Some("An async runtime".to_string()),
+            },
+            WebSearchHit {
+                url: "https://tokio.rs".to_string(),
+                title: None,
+                snippet: None,
+            },
+        ]
+    );
+}
+
+fn agent_message(text: &str) -> ThreadItem {
+    ThreadItem::AgentMessage {
+        id: "item-1".to_string(),
+        text: text.to_string(),
+    }
+}
+
+#[test]
+fn display_truncates_long_messages_at_sixty_chars() {
+    let exact = "a".repeat(60);
+    let long = "b".repeat(61);
+    let wide = "é".repeat(61);
+
+    assert_eq!(
+        agent_message(&exact).to_string(),
+        format!("agent_message: {exact}")
+    );
+    assert_eq!(
+        agent_message(&long).to_string(),
+        format!("agent_message: {}…", "b".repeat(59))
+    );
+    assert_eq!(
+        agent_message(&wide).to_string(),
+        format!("agent_message: {}…", "é".repeat(59))
+    );
+    assert_eq!(
+        agent_message("line one\nline two").to_string(),
+        "agent_message: line one line two"
+    );
+    assert_eq!(agent_message("").to_string(), "agent_message: ");
+}
+
+#[test]
+fn display_summarizes_each_item_variant() {
+    let items: Vec<ThreadItem> = serde_json::from_value(json!([
+        { "type": "reasoning", "id": "r", "text": "Looking at the tests" },
+        { "type": "command_execution", "id": "c", "command": "git status", "aggregated_output": "", "exit_code": 0, "status": "completed" },
+        { "type": "command_execution", "id": "p", "command": "cargo test", "aggregated_output": "", "exit_code": null, "status": "in_progress" },
+        { "type": "file_change", "id": "f", "changes": [
+            { "path": "a.rs", "kind": "update" },
+            { "path": "b.rs", "kind": "add" },
+            { "path": "c.rs", "kind": "delete" },
+        ], "status": "completed" },
+        { "type": "file_change", "id": "g", "changes": [{ "path": "a.rs", "kind": "update" }], "status": "failed" },
+        { "type": "mcp_tool_call", "id": "m", "server": "docs", "tool": "search", "arguments": {}, "result": null, "error": null, "status": "in_progress" },
+        { "type": "web_search", "id": "w", "query": "rust sdk" },
+        { "type": "todo_list", "id": "t", "items": [
+            { "text": "a", "completed": true },
+            { "text": "b", "completed": false },
+        ] },
+        { "type": "error", "id": "e", "message": "sandbox denied" },
+    ]))
+    .expect("items");
+
+    let rendered: Vec<String> = items.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        rendered,
+        vec![
+            "reasoning: Looking at the tests",
+            "command_execution: git status (exit=0)",
+            "command_execution: cargo test (in_progress)",
+            "file_change: 3 files (completed)",
+            "file_change: 1 file (failed)",
+            "mcp_tool_call: docs/search (in_progress)",
+            "web_search: rust sdk",
+            "todo_list: 1/2 done",
+            "error: sandbox denied",
+        ]
+    );
+}
+
+fn file_change() -> FileChangeItem {
+    let change = |path: &str, kind| FileUpdateChange {
+        path: path.to_string(),
+        kind,
+        diff: None,
+    };
+    FileChangeItem {
+        id: "item-2".to_string(),
+        kind: "file_change".to_string(),
+        changes: vec![
+            change("src/lib.rs", PatchChangeKind::Update),
+            change("src/net/client.rs", PatchChangeKind::Add),
+            change("docs/old.md", PatchChangeKind::Delete),
+            change("src2/main.rs", PatchChangeKind::Update),
+        ],
+        status: PatchApplyStatus::Completed,
+    }
+}
+
+#[test]
+fn file_changes_match_glob_patterns() {
+    let item = file_change();
+    let matching = |pattern: &str| -> Vec<&str> {
+        item.changes
+            .iter()
+            .filter(|change| change.matches_glob(pattern))
+            .map(|change| change.path.as_str())
+            .collect()
+    };
+
+    assert_eq!(matching("src/*.rs"), vec!["src/lib.rs"]);
+    assert_eq!(
+        matching("src/**/*.rs"),
+        vec!["src/lib.rs", "src/net/client.rs"]
+    );
+    assert_eq!(matching("docs/*.md"), vec!["docs/old.md"]);
+    assert_eq!(matching("tests/**"), Vec::<&str>::new());
+    assert_eq!(matching("[invalid"), Vec::<&str>::new());
+
+    assert!(item.any_change_matches("**/client.rs"));
+    assert!(!item.any_change_matches("*.toml"));
+}
+
+#[test]
+fn changes_under_matches_whole_path_components() {
+    let item = file_change();
+
+    let under_src: Vec<&str> = item
+        .changes_under("src")
+        .map(|change| change.path.as_str())
+        .collect();
+    assert_eq!(under_src, vec!["src/lib.rs", "src/net/client.rs"]);
+    assert_eq!(item.changes_under("docs/").count(), 1);
+}
+
+#[test]
+fn command_execution_without_stream_fields_still_parses() {
+    let item: CommandExecutionItem = serde_json::from_value(json!({
+        "id": "c",
+        "type": "command_execution",
+        "command": "cargo build",
+        "aggregated_output": "Finished",
+        "exit_code": 0,
+        "status": "completed",
+    }))
+    .expect("command item");
+
+    assert_eq!(item.stdout, None);
+    assert_eq!(item.stderr, None);
+    assert!(!item.stderr_has_errors());
+}
+
+#[test]
+fn command_execution_captures_per_stream_output() {
+    let item: CommandExecutionItem = serde_json::from_value(json!({
+        "id": "c",
+        "type": "command_execution",
+        "command": "cargo build",
+        "aggregated_output": "Compiling\nerror[E0308]",
+        "stdout": "Compiling",
+        "stderr": "error[E0308]",
+        "exit_code": 101,
+        "status": "failed",
+    }))
+    .expect("command item");
+
+    assert_eq!(item.stdout.as_deref(), Some("Compiling"));
+    assert_eq!(item.stderr.as_deref(), Some("error[E0308]"));
+    assert!(item.stderr_has_errors());
+
+    let quiet = CommandExecutionItem {
+        stderr: Some(String::new()),
+        ..item
+    };
+    assert!(!quiet.stderr_has_errors());
+}
+
+#[test]
+fn thread_item_command_execution_accepts_stream_fields() {
+    let with_streams: ThreadItem = serde_json::from_value(json!({
+        "type": "command_execution",
+        "id": "c",
+        "command": "ls",
+        "aggregated_output": "a\nb",
+        "stdout": "a",
+        "stderr": "b",
+        "exit_code": 0,
+        "status": "completed",
+    }))
+    .expect("item");
+    let without_streams: ThreadItem = serde_json::from_value(json!({
+        "type": "command_execution",
+        "id": "c",
+        "command": "ls",
+        "aggregated_output": "a\nb",
+        "exit_code": 0,
+        "status": "completed",
+    }))
+    .expect("item");
+
+    assert!(matches!(
+        with_streams,
+        ThreadItem::CommandExecution { stdout: Some(ref out), stderr: Some(ref err), .. }
+            if out == "a" && err == "b"
+    ));
+    assert!(matches!(
+        without_streams,
+        ThreadItem::CommandExecution {
+            stdout: None,
+            stderr: None,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn command_execution_timing_fields_are_optional() {
+    let old_shape: ThreadItem = serde_json::from_value(json!({
+        "type": "command_execution",
+        "id": "c",
+        "command": "ls",
+        "aggregated_output": "",
+        "exit_code": 0,
+        "status": "completed",
+    }))
+    .expect("old item");
+    let new_shape: CommandExecutionItem = serde_json::from_value(json!({
+        "type": "command_execution",
+        "id": "c",
+        "command": "ls",
+        "aggregated_output": "",
+        "exit_code": 0,
+        "status": "completed",
+        "started_at": "2025-10-01T12:00:00Z",
+        "duration_ms": 1250,
+        "cwd": "/repo",
+    }))
+    .expect("new item");
+
+    assert!(matches!(
+        old_shape,
+        ThreadItem::CommandExecution {
+            started_at: None,
+            duration_ms: None,
+            cwd: None,
+            ..
+        }
+    ));
+    assert_eq!(
+        new_shape.started_at.as_deref(),
+        Some("2025-10-01T12:00:00Z")
+    );
+    assert_eq!(new_shape.duration_ms, Some(1250));
+    assert_eq!(new_shape.cwd.as_deref(), Some("/repo"));
+}
+
+#[test]
+fn file_change_diff_is_optional() {
+    let item: FileChangeItem = serde_json::from_value(json!({
+        "type": "file_change",
+        "id": "f",
+        "changes": [
+            { "path": "a.rs", "kind": "update" },
+            { "path": "b.rs", "kind": "add", "diff": "--- /dev/null\n+++ b/b.rs\n@@ -0,0 +1 @@\n+fn b() {}\n" },
+        ],
+        "status": "completed",
+    }))
+    .expect("file change");
+
+    assert_eq!(item.changes[0].diff, None);
+    assert_eq!(
+        item.changes[1].diff.as_deref(),
+        Some("--- /dev/null\n+++ b/b.rs\n@@ -0,0 +1 @@\n+fn b() {}\n")
+    );
+}
+
+#[test]
+fn patch_change_kinds_display_and_sort() {
+    let mut kinds = vec![
+        PatchChangeKind::Update,
+        PatchChangeKind::Add,
+        PatchChangeKind::Delete,
+        PatchChangeKind::Update,
+        PatchChangeKind::Add,
+    ];
+
+    kinds.sort();
+
+    assert_eq!(
+        kinds,
+        vec![
+            PatchChangeKind::Add,
+            PatchChangeKind::Add,
+            PatchChangeKind::Delete,
+            PatchChangeKind::Update,
+            PatchChangeKind::Update,
+        ]
+    );
+    let names: Vec<String> = kinds.iter().map(ToString::to_string).collect();
+    assert_eq!(names, ["add", "add", "delete", "update", "update"]);
+}
+
+#[test]
+fn mcp_tool_call_outcome_covers_status_and_error() {
+    let cases = [
+        ("in_progress", false, (false, true, false)),
+        ("in_progress", true, (false, true, true)),
+        ("completed", false, (true, false, false)),
+        ("completed", true, (false, false, true)),
+        ("failed", false, (false, false, true)),
+        ("failed", true, (false, false, true)),
+    ];
+
+    for (status, has_error, expected) in cases {
+        let value = json!({
+            "type": "mcp_tool_call",
+            "id": "mcp-1",
+            "server": "docs",
+            "tool": "search",
+            "arguments": {},
+            "result": null,
+            "error": if has_error { json!({ "message": "timed out" }) } else { json!(null) },
+            "status": status,
+        });
+        let item: McpToolCallItem = serde_json::from_value(value.clone()).expect("mcp item");
+        let thread_item: ThreadItem = serde_json::from_value(value).expect("thread item");
+
+        assert_eq!(
+            (item.is_successful(), item.is_pending(), item.is_failed()),
+            expected,
+            "{status} with error={has_error}"
+        );
+        assert_eq!(
+            (
+                thread_item.is_mcp_call_successful(),
+                thread_item.is_mcp_call_pending(),
+                thread_item.is_mcp_call_failed()
+            ),
+            expected,
+            "{status} with error={has_error}"
+        );
+    }
+}
+
+#[test]
+fn non_mcp_items_have_no_tool_call_outcome() {
+    let item = ThreadItem::AgentMessage {
+        id: "msg-1".to_string(),
+        text: "hello".to_string(),
+    };
+
+    assert!(!item.is_mcp_call_successful());
+    assert!(!item.is_mcp_call_pending());
+    assert!(!item.is_mcp_call_failed());
+}
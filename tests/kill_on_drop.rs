@@ -0,0 +1,46 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+
+use codex_sdk::{CodexExec, CodexExecArgs};
+
+fn is_running(pid: &str) -> bool {
+    match std::fs::read_to_string(format!("/proc/{pid}/stat")) {
+        Ok(stat) => !stat
+            .rsplit_once(')')
+            .is_some_and(|(_, rest)| rest.trim_start().starts_with('Z')),
+        Err(_) => false,
+    }
+}
+
+#[tokio::test]
+async fn dropping_the_stream_early_kills_the_process() {
+    let fake = common::fake_codex_script(
+        "cat > /dev/null\necho $$ > \"$(dirname \"$0\")/pid\"\nprintf '%s\\n' '{\"type\":\"turn.started\"}'\nexec sleep 30\n",
+    );
+    let exec = CodexExec::new(Some(fake.path.clone()), None, None).expect("exec");
+
+    let mut lines = exec
+        .run(CodexExecArgs {
+            input: "hello".to_string(),
+            ..CodexExecArgs::default()
+        })
+        .expect("stream");
+    let first = lines.next().await.expect("line").expect("ok");
+    assert_eq!(first, r#"{"type":"turn.started"}"#);
+    let pid = std::fs::read_to_string(fake.dir().join("pid")).expect("pid");
+    let pid = pid.trim();
+    assert!(is_running(pid));
+
+    drop(lines);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while is_running(pid) && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(!is_running(pid), "codex process {pid} outlived its stream");
+}
@@ -0,0 +1,120 @@
+#![cfg(unix)]
+
+mod common;
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{CodexError, ThreadOptions, TurnOptions};
+
+const EVENTS: &[&str] = &[
+    "codex banner v1.2.3",
+    r#"{"type":"thread.started","thread_id":"thread-1"}"#,
+    "WARNING: mcp server wrote to stdout",
+    r#"{"type":"turn.started"}"#,
+    r#"{"type":"item.completed","item":{"type":"agent_message","id":"item-1","text":"done"}}"#,
+    "",
+    r#"{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":2}}"#,
+];
+
+#[tokio::test]
+async fn lenient_parsing_skips_noise_lines() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        lenient_parsing: true,
+        ..ThreadOptions::default()
+    });
+
+    let turn = thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    assert_eq!(turn.final_response, "done");
+    assert_eq!(turn.skipped_lines, 3);
+    assert_eq!(thread.id(), Some("thread-1".into()));
+}
+
+#[tokio::test]
+async fn strict_parsing_fails_on_noise_lines() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let result = thread.run("hello".into(), TurnOptions::default()).await;
+    assert!(matches!(result, Err(CodexError::InvalidEvent(line)) if line == "codex banner v1.2.3"));
+}
+
+#[tokio::test]
+async fn lenient_parsing_still_rejects_unknown_json_events() {
+    let fake = common::fake_codex(&["noise", r#"{"type":"something.new","payload":1}"#]);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        lenient_parsing: true,
+        ..ThreadOptions::default()
+    });
+
+    let result = thread.run("hello".into(), TurnOptions::default()).await;
+    assert!(matches!(result, Err(CodexError::InvalidEvent(_))));
+}
+
+#[tokio::test]
+async fn turn_override_disables_thread_lenient_parsing() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        lenient_parsing: true,
+        ..ThreadOptions::default()
+    });
+
+    let result = thread
+        .run(
+            "hello".into(),
+            TurnOptions {
+                lenient_parsing: Some(false),
+                ..TurnOptions::default()
+            },
+        )
+        .await;
+    assert!(matches!(result, Err(CodexError::InvalidEvent(line)) if line == "codex banner v1.2.3"));
+}
+
+#[tokio::test]
+async fn recover_unknown_events_keeps_streaming_after_invalid_events() {
+    use futures::StreamExt;
+
+    let fake = common::fake_codex(&[
+        r#"{"type":"thread.started","thread_id":"thread-1"}"#,
+        r#"{"type":"something.new","payload":1}"#,
+        r#"{"type":"turn.started"}"#,
+        r#"{"type":"item.completed","item":"#,
+        r#"{"type":"item.completed","item":{"type":"agent_message","id":"item-1","text":"done"}}"#,
+        r#"{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":2}}"#,
+    ]);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+    let options = || TurnOptions {
+        recover_unknown_events: Some(true),
+        ..TurnOptions::default()
+    };
+
+    let events: Vec<String> = thread
+        .run_streamed("hello".into(), options())
+        .expect("streamed")
+        .events
+        .map(|event| event.expect("event").event_type().to_string())
+        .collect()
+        .await;
+    assert_eq!(
+        events,
+        vec![
+            "thread.started",
+            "turn.started",
+            "item.completed",
+            "turn.completed"
+        ]
+    );
+
+    let turn = common::codex_for(&fake)
+        .start_thread(ThreadOptions::default())
+        .run("hello".into(), options())
+        .await
+        .expect("turn");
+    assert_eq!(turn.final_response, "done");
+    assert_eq!(turn.skipped_lines, 2);
+}
@@ -0,0 +1,219 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{Codex, CodexOptions, ThreadListFilter, ThreadListOrder};
+
+const OLD_ID: &str = "0a6e4f1c-3b2d-4e5f-8a7b-9c0d1e2f3a4b";
+const NEW_ID: &str = "7d1f2e3c-4b5a-4697-a8b9-c0d1e2f3a4b5";
+const OTHER_ID: &str = "c3b2a190-8f7e-4d6c-9b5a-4f3e2d1c0b9a";
+
+fn write_session(home: &Path, day: &str, id: &str, timestamp: &str, cwd: &str, message: &str) {
+    let dir = home.join("sessions").join("2025").join("01").join(day);
+    fs::create_dir_all(&dir).expect("session dir");
+    let meta = serde_json::json!({
+        "timestamp": timestamp,
+        "type": "session_meta",
+        "payload": { "id": id, "timestamp": timestamp, "cwd": cwd, "originator": "codex_exec" },
+    });
+    let context = serde_json::json!({
+        "type": "response_item",
+        "payload": {
+            "type": "message",
+            "role": "user",
+            "content": [{ "type": "input_text", "text": "<environment_context>...</environment_context>" }],
+        },
+    });
+    let user = serde_json::json!({
+        "type": "event_msg",
+        "payload": { "type": "user_message", "message": message },
+    });
+    fs::write(
+        dir.join(format!("rollout-{timestamp}-{id}.jsonl")),
+        format!("{meta}\n{context}\n{user}\n"),
+    )
+    .expect("write session");
+}
+
+fn fixture_home() -> tempfile::TempDir {
+    let home = tempfile::tempdir().expect("tempdir");
+    write_session(
+        home.path(),
+        "01",
+        OLD_ID,
+        "2025-01-01T09:00:00.000Z",
+        "/repo",
+        "Fix the flaky test",
+    );
+    write_session(
+        home.path(),
+        "02",
+        NEW_ID,
+        "2025-01-02T09:00:00.000Z",
+        "/repo",
+        &"x".repeat(200),
+    );
+    write_session(
+        home.path(),
+        "02",
+        OTHER_ID,
+        "2025-01-02T10:00:00.000Z",
+        "/elsewhere",
+        "Unrelated",
+    );
+    home
+}
+
+fn codex_for(home: &Path) -> Codex {
+    Codex::new(CodexOptions {
+        codex_home: Some(home.to_path_buf()),
+        ..CodexOptions::default()
+    })
+    .expect("codex")
+}
+
+#[test]
+fn lists_sessions_newest_first() {
+    let home = fixture_home();
+
+    let threads = codex_for(home.path())
+        .list_threads(ThreadListFilter::default())
+        .expect("threads");
+
+    let ids: Vec<String> = threads.iter().map(|thread| thread.id.to_string()).collect();
+    assert_eq!(ids, vec![OTHER_ID, NEW_ID, OLD_ID]);
+    assert_eq!(threads[2].started_at, "2025-01-01T09:00:00.000Z");
+    assert_eq!(threads[2].cwd, Some(PathBuf::from("/repo")));
+    assert_eq!(
+        threads[2].first_user_message_preview.as_deref(),
+        Some("Fix the flaky test")
+    );
+    assert_eq!(
+        threads[2].path.extension().and_then(|ext| ext.to_str()),
+        Some("jsonl")
+    );
+}
+
+#[test]
+fn filters_by_working_directory_with_order_and_limit() {
+    let home = fixture_home();
+
+    let threads = codex_for(home.path())
+        .list_threads(ThreadListFilter {
+            working_directory: Some(PathBuf::from("/repo")),
+            limit: Some(1),
+            order: ThreadListOrder::OldestFirst,
+        })
+        .expect("threads");
+
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0].id.to_string(), OLD_ID);
+}
+
+#[test]
+fn long_first_messages_are_truncated() {
+    let home = fixture_home();
+
+    let threads = codex_for(home.path())
+        .list_threads(ThreadListFilter {
+            working_directory: Some(PathBuf::from("/repo")),
+            ..ThreadListFilter::default()
+        })
+        .expect("threads");
+
+    let preview = threads[0]
+        .first_user_message_preview
+        .clone()
+        .expect("preview");
+    assert_eq!(preview.chars().count(), 80);
+    assert_eq!(preview.ends_with('…'), true);
+}
+
+#[test]
+fn malformed_session_files_are_skipped() {
+    let home = fixture_home();
+    let dir = home
+        .path()
+        .join("sessions")
+        .join("2025")
+        .join("01")
+        .join("03");
+    fs::create_dir_all(&dir).expect("session dir");
+    fs::write(dir.join("rollout-broken.jsonl"), "not json\n").expect("write");
+    fs::write(dir.join("rollout-empty.jsonl"), "").expect("write");
+    fs::write(
+        dir.join("rollout-no-id.jsonl"),
+        "{\"type\":\"session_meta\",\"payload\":{\"cwd\":\"/repo\"}}\n",
+    )
+    .expect("write");
+
+    let threads = codex_for(home.path())
+        .list_threads(ThreadListFilter::default())
+        .expect("threads");
+
+    assert_eq!(threads.len(), 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn unreadable_session_directories_are_skipped() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let home = fixture_home();
+    let locked = home
+        .path()
+        .join("sessions")
+        .join("2025")
+        .join("01")
+        .join("02");
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).expect("chmod");
+    let readable_anyway = fs::read_dir(&locked).is_ok();
+
+    let threads = codex_for(home.path()).list_threads(ThreadListFilter::default());
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).expect("chmod");
+
+    let ids: Vec<String> = threads
+        .expect("threads")
+        .iter()
+        .map(|thread| thread.id.to_string())
+        .collect();
+    if readable_anyway {
+        assert_eq!(ids.len(), 3);
+    } else {
+        assert_eq!(ids, vec![OLD_ID]);
+    }
+}
+
+#[test]
+fn codex_home_env_override_is_honored() {
+    let home = fixture_home();
+    let codex = Codex::new(CodexOptions {
+        env: Some(
+            [(
+                "CODEX_HOME".to_string(),
+                home.path().to_string_lossy().into_owned(),
+            )]
+            .into_iter()
+            .collect(),
+        ),
+        ..CodexOptions::default()
+    })
+    .expect("codex");
+
+    let threads = codex
+        .list_threads(ThreadListFilter::default())
+        .expect("threads");
+    assert_eq!(threads.len(), 3);
+}
+
+#[test]
+fn missing_sessions_directory_lists_nothing() {
+    let home = tempfile::tempdir().expect("tempdir");
+
+    let threads = codex_for(home.path())
+        .list_threads(ThreadListFilter::default())
+        .expect("threads");
+
+    assert_eq!(threads, Vec::new());
+}
@@ -0,0 +1,40 @@
+#![cfg(unix)]
+
+mod common;
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::CodexError;
+
+#[tokio::test]
+async fn list_thread_ids_parses_json_array() {
+    let fake = common::fake_codex(&[r#"["thread-1","thread-2"]"#]);
+
+    let ids = common::codex_for(&fake)
+        .list_thread_ids()
+        .await
+        .expect("ids");
+    assert_eq!(ids, vec!["thread-1".to_string(), "thread-2".to_string()]);
+    assert_eq!(fake.recorded_args(), vec!["list", "--json"]);
+}
+
+#[tokio::test]
+async fn list_thread_ids_reports_unsupported_command() {
+    let fake =
+        common::fake_codex_script("echo \"error: unrecognized subcommand 'list'\" >&2\nexit 2\n");
+
+    let result = common::codex_for(&fake).list_thread_ids().await;
+    assert!(matches!(result, Err(CodexError::ExecFailed(code, stderr))
+        if code == "code 2" && stderr.contains("unrecognized subcommand")));
+}
+
+#[tokio::test]
+async fn exec_failed_stderr_has_ansi_sequences_stripped() {
+    let fake = common::fake_codex_script(
+        "printf '\\033[1;31merror\\033[0m: not logged in\\n' >&2\nexit 1\n",
+    );
+
+    let result = common::codex_for(&fake).list_thread_ids().await;
+    assert!(matches!(result, Err(CodexError::ExecFailed(_, stderr))
+        if stderr == "error: not logged in\n"));
+}
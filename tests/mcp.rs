@@ -0,0 +1,260 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{
+    Codex, CodexError, CodexOptions, McpServerConfig, McpServerEntry, McpTransportKind,
+};
+
+const SCRIPT: &str = r#"cat > /dev/null
+config="$CODEX_HOME/config.toml"
+touch "$config"
+case "$2" in
+  add) printf '[mcp_servers.%s]\n' "$3" >> "$config" ;;
+  remove) grep -v "^\[mcp_servers\.$3\]$" "$config" > "$config.tmp"; mv "$config.tmp" "$config" ;;
+  list)
+    printf '['
+    sep=''
+    for name in $(sed -n 's/^\[mcp_servers\.\(.*\)\]$/\1/p' "$config"); do
+      printf '%s{"name":"%s","enabled":true,"transport":{"type":"stdio","command":"%s-server","args":[],"env":null},"startup_timeout_sec":null,"tool_timeout_sec":null}' "$sep" "$name" "$name"
+      sep=','
+    done
+    printf ']\n'
+    ;;
+esac
+"#;
+
+fn codex_for(fake: &common::FakeCodex, codex_home: &Path) -> Codex {
+    Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        codex_home: Some(codex_home.to_path_buf()),
+        ..CodexOptions::default()
+    })
+    .expect("codex")
+}
+
+fn stdio(command: &str) -> McpServerConfig {
+    McpServerConfig::Stdio {
+        command: command.to_string(),
+        args: Vec::new(),
+        env: None,
+    }
+}
+
+fn names(entries: &[McpServerEntry]) -> Vec<&str> {
+    entries.iter().map(|entry| entry.name.as_str()).collect()
+}
+
+#[tokio::test]
+async fn add_list_and_remove_round_trip_through_codex_home() {
+    let fake = common::fake_codex_script(SCRIPT);
+    let home = tempfile::tempdir().expect("codex home");
+    let mcp = codex_for(&fake, home.path()).mcp();
+
+    mcp.add("docs", stdio("docs-server"))
+        .await
+        .expect("add docs");
+    mcp.add("search", stdio("search-server"))
+        .await
+        .expect("add search");
+    assert_eq!(
+        names(&mcp.list().await.expect("list")),
+        vec!["docs", "search"]
+    );
+
+    mcp.remove("docs").await.expect("remove");
+    let entries = mcp.list().await.expect("list");
+    assert_eq!(names(&entries), vec!["search"]);
+    assert_eq!(entries[0].transport, stdio("search-server"));
+    assert_eq!(
+        std::fs::read_to_string(home.path().join("config.toml")).expect("config"),
+        "[mcp_servers.search]\n"
+    );
+}
+
+#[tokio::test]
+async fn add_passes_stdio_command_and_env() {
+    let fake = common::fake_codex_script(SCRIPT);
+    let home = tempfile::tempdir().expect("codex home");
+
+    codex_for(&fake, home.path())
+        .mcp()
+        .add(
+            "docs",
+            McpServerConfig::Stdio {
+                command: "npx".to_string(),
+                args: vec!["-y".to_string(), "@acme/docs-mcp".to_string()],
+                env: Some(HashMap::from([
+                    ("DOCS_TOKEN".to_string(), "secret".to_string()),
+                    ("DOCS_REGION".to_string(), "eu".to_string()),
+                ])),
+            },
+        )
+        .await
+        .expect("add");
+
+    assert_eq!(
+        fake.recorded_args(),
+        vec![
+            "mcp",
+            "add",
+            "docs",
+            "--env",
+            "DOCS_REGION=eu",
+            "--env",
+            "DOCS_TOKEN=secret",
+            "--",
+            "npx",
+            "-y",
+            "@acme/docs-mcp",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn add_passes_streamable_http_url() {
+    let fake = common::fake_codex_script(SCRIPT);
+    let home = tempfile::tempdir().expect("codex home");
+
+    codex_for(&fake, home.path())
+        .mcp()
+        .add(
+            "remote",
+            McpServerConfig::StreamableHttp {
+                url: "https://mcp.example.com/mcp".to_string(),
+                bearer_token_env_var: Some("MCP_TOKEN".to_string()),
+            },
+        )
+        .await
+        .expect("add");
+
+    assert_eq!(
+        fake.recorded_args(),
+        vec![
+            "mcp",
+            "add",
+            "remote",
+            "--url",
+            "https://mcp.example.com/mcp",
+            "--bearer-token-env-var",
+            "MCP_TOKEN",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn concurrent_adds_are_serialized() {
+    let fake = common::fake_codex_script(SCRIPT);
+    let home = tempfile::tempdir().expect("codex home");
+    let codex = codex_for(&fake, home.path());
+    let first = codex.clone().mcp();
+    let second = codex.mcp();
+
+    let (a, b) = tokio::join!(
+        first.add("alpha", stdio("alpha-server")),
+        second.add("beta", stdio("beta-server")),
+    );
+    a.expect("add alpha");
+    b.expect("add beta");
+
+    let mut listed = names(&first.list().await.expect("list"))
+        .into_iter()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    listed.sort();
+    assert_eq!(listed, vec!["alpha", "beta"]);
+}
+
+#[tokio::test]
+async fn list_parses_transport_kind_and_enabled_state() {
+    let fake = common::fake_codex(&[
+        r#"[{"name":"docs","enabled":true,"transport":{"type":"stdio","command":"docs-server","args":["--stdio"],"env":{"DOCS_TOKEN":"x"}},"startup_timeout_sec":10.0,"tool_timeout_sec":null},{"name":"remote","enabled":false,"transport":{"type":"streamable_http","url":"https://mcp.example.com/mcp","bearer_token_env_var":null},"startup_timeout_sec":null,"tool_timeout_sec":30.0}]"#,
+    ]);
+    let home = tempfile::tempdir().expect("codex home");
+
+    let entries = codex_for(&fake, home.path())
+        .mcp()
+        .list()
+        .await
+        .expect("list");
+
+    assert_eq!(fake.recorded_args(), vec!["mcp", "list", "--json"]);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].transport_kind(), McpTransportKind::Stdio);
+    assert!(entries[0].enabled);
+    assert_eq!(entries[0].startup_timeout_sec, Some(10.0));
+    assert_eq!(
+        entries[1].transport_kind(),
+        McpTransportKind::StreamableHttp
+    );
+    assert!(!entries[1].enabled);
+    assert_eq!(
+        entries[1].transport,
+        McpServerConfig::StreamableHttp {
+            url: "https://mcp.example.com/mcp".to_string(),
+            bearer_token_env_var: None,
+        }
+    );
+}
+
+#[tokio::test]
+async fn invalid_server_names_are_rejected() {
+    let fake = common::fake_codex_script(SCRIPT);
+    let home = tempfile::tempdir().expect("codex home");
+
+    let result = codex_for(&fake, home.path())
+        .mcp()
+        .remove("docs server")
+        .await;
+    assert!(matches!(result, Err(CodexError::InvalidArgs(_))));
+}
+
+#[test]
+fn display_hides_env_values() {
+    let config = McpServerConfig::Stdio {
+        command: "docs-server".to_string(),
+        args: Vec::new(),
+        env: Some(HashMap::from([(
+            "DOCS_TOKEN".to_string(),
+            "secret".to_string(),
+        )])),
+    };
+
+    let rendered = config.to_string();
+    assert!(rendered.contains("DOCS_TOKEN"));
+    assert!(!rendered.contains("secret"));
+}
+
+const RACY_SCRIPT: &str = r#"cat > /dev/null
+config="$CODEX_HOME/config.toml"
+touch "$config"
+existing=$(cat "$config")
+sleep 0.3
+printf '%s\n[mcp_servers.%s]\n' "$existing" "$3" | sed '/^$/d' > "$config"
+"#;
+
+#[tokio::test]
+async fn adds_from_separate_clients_are_serialized_by_the_config_lock() {
+    let fake = common::fake_codex_script(RACY_SCRIPT);
+    let home = tempfile::tempdir().expect("codex home");
+    let first = codex_for(&fake, home.path()).mcp();
+    let second = codex_for(&fake, home.path()).mcp();
+
+    let (a, b) = tokio::join!(
+        first.add("alpha", stdio("alpha-server")),
+        second.add("beta", stdio("beta-server")),
+    );
+    a.expect("add alpha");
+    b.expect("add beta");
+
+    let config = std::fs::read_to_string(home.path().join("config.toml")).expect("config");
+    let mut sections: Vec<&str> = config.lines().collect();
+    sections.sort();
+    assert_eq!(sections, vec!["[mcp_servers.alpha]", "[mcp_servers.beta]"]);
+    assert!(home.path().join("config.toml.lock").exists());
+}
@@ -1,9 +1,14 @@
+use std::path::Path;
+
 use pretty_assertions::assert_eq;
 
-use codex_sdk::{Input, Thread, UserInput};
+use codex_sdk::{CodexError, FileInputLimits, ImageFormat, Input, Thread, UserInput};
 
 #[test]
 fn normalize_input_combines_text_and_collects_images() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let image = write_file(&dir, "image.png", b"png");
+
     let input = Input::Structured(vec![
         UserInput::Text {
             text: "Describe file changes".to_string(),
@@ -12,11 +17,393 @@ fn normalize_input_combines_text_and_collects_images() {
             text: "Focus on impacted tests".to_string(),
         },
         UserInput::LocalImage {
-            path: "./image.png".to_string(),
+            path: image.clone(),
         },
     ]);
 
-    let (prompt, images) = Thread::normalize_input(&input);
+    let (prompt, images, _files) = Thread::normalize_input(&input).expect("normalize");
     assert_eq!(prompt, "Describe file changes\n\nFocus on impacted tests");
-    assert_eq!(images, vec!["./image.png".to_string()]);
+    assert_eq!(images, vec![canonical(&image)]);
+}
+
+#[cfg(not(feature = "image-download"))]
+#[test]
+fn image_urls_pass_through_in_input_order() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let before = write_file(&dir, "before.png", b"png");
+    let after = write_file(&dir, "after.jpg", b"jpg");
+
+    let input = Input::Structured(vec![
+        UserInput::LocalImage {
+            path: before.clone(),
+        },
+        UserInput::ImageUrl {
+            url: "https://bucket.example.com/shot.png?X-Amz-Signature=abc".to_string(),
+        },
+        UserInput::LocalImage {
+            path: after.clone(),
+        },
+    ]);
+
+    let (_, images, _files) = Thread::normalize_input(&input).expect("normalize");
+    assert_eq!(
+        images,
+        vec![
+            canonical(&before),
+            "https://bucket.example.com/shot.png?X-Amz-Signature=abc".to_string(),
+            canonical(&after),
+        ]
+    );
+}
+
+#[cfg(feature = "image-download")]
+fn serve_png() -> (std::net::SocketAddr, std::thread::JoinHandle<()>) {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    let address = listener.local_addr().expect("address");
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("accept");
+        let mut request = [0u8; 1024];
+        let _ = stream.read(&mut request);
+        let body = b"PNGDATA";
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .expect("write headers");
+        stream.write_all(body).expect("write body");
+    });
+    (address, server)
+}
+
+#[cfg(feature = "image-download")]
+#[test]
+fn downloaded_images_live_as_long_as_the_guard() {
+    let (address, server) = serve_png();
+    let dir = tempfile::tempdir().expect("temp dir");
+    let before = write_file(&dir, "before.png", b"png");
+    let input = Input::Structured(vec![
+        UserInput::LocalImage {
+            path: before.clone(),
+        },
+        UserInput::ImageUrl {
+            url: format!("http://{address}/shot.png?sig=abc"),
+        },
+    ]);
+
+    let (_, images, files) = Thread::normalize_input(&input).expect("normalize");
+    server.join().expect("server");
+
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0], canonical(&before));
+    assert!(images[1].ends_with(".png"));
+    assert_eq!(std::fs::read(&images[1]).expect("downloaded"), b"PNGDATA");
+
+    drop(files);
+    assert_eq!(Path::new(&images[1]).exists(), false);
+}
+
+#[cfg(feature = "image-download")]
+#[tokio::test(flavor = "multi_thread")]
+async fn images_download_inside_a_multi_thread_runtime() {
+    let (address, server) = serve_png();
+    let input = Input::Structured(vec![UserInput::ImageUrl {
+        url: format!("http://{address}/shot.png"),
+    }]);
+
+    let (_, images, _files) = Thread::normalize_input(&input).expect("normalize");
+    server.join().expect("server");
+
+    assert_eq!(std::fs::read(&images[0]).expect("downloaded"), b"PNGDATA");
+}
+
+fn canonical(path: &str) -> String {
+    std::fs::canonicalize(path)
+        .expect("canonicalize")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn write_file(dir: &tempfile::TempDir, name: &str, contents: &[u8]) -> String {
+    let path = dir.path().join(name);
+    std::fs::write(&path, contents).expect("write input file");
+    path.to_string_lossy().to_string()
+}
+
+#[test]
+fn file_inputs_are_fenced_in_order() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let main_rs = write_file(&dir, "main.rs", b"fn main() {}\n");
+    let notes = write_file(&dir, "notes.md", b"use ``` for code");
+
+    let input = Input::Structured(vec![
+        UserInput::Text {
+            text: "Review these files".to_string(),
+        },
+        UserInput::File {
+            path: main_rs.clone(),
+        },
+        UserInput::Text {
+            text: "and the notes".to_string(),
+        },
+        UserInput::File {
+            path: notes.clone(),
+        },
+    ]);
+
+    let (prompt, images, _files) = Thread::normalize_input(&input).expect("normalize");
+    assert_eq!(
+        prompt,
+        format!(
+            "Review these files\n\n```path={main_rs}\nfn main() {{}}\n```\n\nand the notes\n\n````path={notes}\nuse ``` for code\n````"
+        )
+    );
+    assert_eq!(images.is_empty(), true);
+}
+
+#[test]
+fn file_over_per_file_limit_is_rejected() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let big = write_file(&dir, "big.txt", &[b'a'; 64]);
+    let limits = FileInputLimits {
+        max_file_bytes: 32,
+        max_total_bytes: 1024,
+    };
+
+    let input = Input::Structured(vec![UserInput::File { path: big }]);
+    let result = Thread::normalize_input_with_limits(&input, &limits);
+    assert!(matches!(result, Err(CodexError::InputTooLarge(_))));
+}
+
+#[test]
+fn files_over_total_limit_are_rejected() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let first = write_file(&dir, "first.txt", &[b'a'; 24]);
+    let second = write_file(&dir, "second.txt", &[b'b'; 24]);
+    let limits = FileInputLimits {
+        max_file_bytes: 32,
+        max_total_bytes: 40,
+    };
+
+    let single = Input::Structured(vec![UserInput::File {
+        path: first.clone(),
+    }]);
+    assert!(Thread::normalize_input_with_limits(&single, &limits).is_ok());
+
+    let both = Input::Structured(vec![
+        UserInput::File { path: first },
+        UserInput::File { path: second },
+    ]);
+    let result = Thread::normalize_input_with_limits(&both, &limits);
+    assert!(matches!(result, Err(CodexError::InputTooLarge(_))));
+}
+
+#[test]
+fn non_utf8_file_is_rejected() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let binary = write_file(&dir, "image.bin", &[0x89, 0x50, 0x4e, 0x47, 0xff, 0xfe]);
+
+    let input = Input::Structured(vec![UserInput::File {
+        path: binary.clone(),
+    }]);
+    let result = Thread::normalize_input(&input);
+    assert!(matches!(result, Err(CodexError::InvalidInputFile(path, _)) if path == binary));
+}
+
+#[test]
+fn missing_image_is_rejected() {
+    let input = Input::Structured(vec![UserInput::LocalImage {
+        path: "./does-not-exist.png".to_string(),
+    }]);
+
+    let result = Thread::normalize_input(&input);
+    assert!(
+        matches!(result, Err(CodexError::ImageNotFound(path)) if path == Path::new("./does-not-exist.png"))
+    );
+}
+
+#[test]
+fn directory_image_is_rejected() {
+    let dir = tempfile::Builder::new()
+        .suffix(".png")
+        .tempdir()
+        .expect("temp dir");
+
+    let input = Input::Structured(vec![UserInput::LocalImage {
+        path: dir.path().to_string_lossy().to_string(),
+    }]);
+
+    let result = Thread::normalize_input(&input);
+    assert!(matches!(result, Err(CodexError::ImageNotFound(_))));
+}
+
+#[test]
+fn image_without_image_extension_is_rejected() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let notes = write_file(&dir, "notes.txt", b"text");
+
+    let input = Input::Structured(vec![UserInput::LocalImage { path: notes }]);
+    let result = Thread::normalize_input(&input);
+    assert!(matches!(result, Err(CodexError::InvalidInputFile(_, _))));
+}
+
+#[test]
+fn relative_image_paths_become_absolute() {
+    let dir = tempfile::Builder::new()
+        .prefix("codex-relative-image-")
+        .tempdir_in(env!("CARGO_TARGET_TMPDIR"))
+        .expect("temp dir");
+    let image = write_file(&dir, "shot.PNG", b"png");
+    let cwd = std::env::current_dir().expect("cwd");
+    let relative = Path::new(&image)
+        .strip_prefix(&cwd)
+        .expect("temp dir under cwd")
+        .to_string_lossy()
+        .to_string();
+
+    let input = Input::Structured(vec![UserInput::LocalImage { path: relative }]);
+    let (_, images, _files) = Thread::normalize_input(&input).expect("normalize");
+    assert_eq!(images, vec![canonical(&image)]);
+    assert_eq!(Path::new(&images[0]).is_absolute(), true);
+}
+
+#[test]
+fn text_input_display_truncates_long_prompts() {
+    assert_eq!(Input::from("Fix the build").to_string(), "Fix the build");
+
+    let exact = "a".repeat(100);
+    assert_eq!(Input::from(exact.as_str()).to_string(), exact);
+
+    let long = format!("{}tail", "b".repeat(100));
+    assert_eq!(
+        Input::from(long).to_string(),
+        format!("{}…", "b".repeat(99))
+    );
+}
+
+#[test]
+fn structured_input_display_counts_parts() {
+    let input = Input::Structured(vec![
+        UserInput::Text {
+            text: "Describe the screenshot".to_string(),
+        },
+        UserInput::File {
+            path: "notes.md".to_string(),
+        },
+        UserInput::LocalImage {
+            path: "shot.png".to_string(),
+        },
+        UserInput::ImageUrl {
+            url: "https://example.com/a.png".to_string(),
+        },
+    ]);
+    assert_eq!(input.to_string(), "[2 text parts, 2 images]");
+
+    let single = Input::Structured(vec![UserInput::ImageBytes {
+        data: vec![0; 3],
+        format: ImageFormat::Png,
+    }]);
+    assert_eq!(single.to_string(), "[0 text parts, 1 image]");
+    assert_eq!(
+        Input::Structured(Vec::new()).to_string(),
+        "[0 text parts, 0 images]"
+    );
+}
+
+#[test]
+fn user_input_display_names_each_kind() {
+    let rendered: Vec<String> = [
+        UserInput::Text {
+            text: "c".repeat(120),
+        },
+        UserInput::LocalImage {
+            path: "shot.png".to_string(),
+        },
+        UserInput::ImageUrl {
+            url: "https://example.com/a.png".to_string(),
+        },
+        UserInput::ImageBytes {
+            data: vec![0; 3],
+            format: ImageFormat::Png,
+        },
+        UserInput::File {
+            path: "notes.md".to_string(),
+        },
+    ]
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+
+    assert_eq!(
+        rendered,
+        vec![
+            format!("text: {}…", "c".repeat(99)),
+            "image: shot.png".to_string(),
+            "image_url: https://example.com/a.png".to_string(),
+            "image_bytes: 3 bytes (png)".to_string(),
+            "file: notes.md".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn truncated_text_is_clipped_with_an_ellipsis() {
+    let input = Input::from("héllo world");
+
+    assert_eq!(input.char_count(), 11);
+    assert_eq!(input.truncated(11), input);
+    assert_eq!(input.truncated(20), input);
+    assert_eq!(input.truncated(5), Input::from("héll…"));
+    assert_eq!(input.truncated(1), Input::from("…"));
+    assert_eq!(input.truncated(0), Input::from(""));
+}
+
+#[test]
+fn truncated_structured_input_clips_the_last_text_part() {
+    let text = |text: &str| UserInput::Text {
+        text: text.to_string(),
+    };
+    let image = UserInput::ImageUrl {
+        url: "https://example.com/a.png".to_string(),
+    };
+    let input = Input::Structured(vec![text("abcd"), text("efghij"), image.clone()]);
+
+    assert_eq!(input.char_count(), 10);
+    assert_eq!(input.truncated(10), input);
+    assert_eq!(
+        input.truncated(7),
+        Input::Structured(vec![text("abcd"), text("ef…"), image.clone()])
+    );
+    assert_eq!(
+        input.truncated(2),
+        Input::Structured(vec![text("a…"), image.clone()])
+    );
+    assert_eq!(input.truncated(0), Input::Structured(vec![image]));
+}
+
+#[test]
+fn truncated_input_never_exceeds_the_limit() {
+    let text = |text: &str| UserInput::Text {
+        text: text.to_string(),
+    };
+    let inputs = [
+        Input::from("héllo world"),
+        Input::Structured(vec![text("abcd"), text(""), text("efghij")]),
+    ];
+
+    for input in inputs {
+        for max_chars in 0..=input.char_count() + 1 {
+            let truncated = input.truncated(max_chars);
+            assert!(
+                truncated.char_count() <= max_chars,
+                "{input:?} at {max_chars}"
+            );
+            if max_chars >= input.char_count() {
+                assert_eq!(truncated, input);
+            }
+        }
+    }
 }
@@ -3,7 +3,7 @@ use std::fs;
 use pretty_assertions::assert_eq;
 use serde_json::json;
 
-use codex_sdk::OutputSchemaFile;
+use codex_sdk::{CodexError, OutputSchemaFile};
 
 #[test]
 fn output_schema_file_is_written_and_cleaned() {
@@ -25,3 +25,174 @@ fn output_schema_file_is_written_and_cleaned() {
 
     assert_eq!(schema_path.exists(), false);
 }
+
+#[test]
+fn non_object_output_schema_is_rejected() {
+    let result = OutputSchemaFile::new(Some(&json!(["not", "a", "schema"])));
+    assert!(matches!(result, Err(CodexError::InvalidOutputSchema(_))));
+}
+
+#[cfg(feature = "schema-validation")]
+#[test]
+fn valid_output_schema_passes_meta_validation() {
+    let schema = json!({
+        "type": "object",
+        "properties": { "status": { "type": "string", "enum": ["ok", "failed"] } },
+        "required": ["status"],
+    });
+
+    let file = OutputSchemaFile::new(Some(&schema)).expect("schema file");
+    assert_eq!(file.schema_path().is_some(), true);
+}
+
+#[cfg(feature = "schema-validation")]
+#[test]
+fn malformed_output_schema_fails_meta_validation() {
+    let schema = json!({
+        "type": "object",
+        "properties": { "status": { "type": 42 } },
+        "required": "status",
+    });
+
+    let result = OutputSchemaFile::new(Some(&schema));
+    assert!(matches!(result, Err(CodexError::InvalidOutputSchema(_))));
+}
+
+#[test]
+fn output_schema_file_at_path_is_kept_after_drop() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("nested").join("schema.json");
+    let schema = json!({ "type": "object" });
+
+    {
+        let file = OutputSchemaFile::at_path(&path, &schema).expect("schema file");
+        assert_eq!(file.schema_path(), Some(path.as_path()));
+    }
+
+    let contents = fs::read_to_string(&path).expect("read schema file");
+    let parsed: serde_json::Value = serde_json::from_str(&contents).expect("json");
+    assert_eq!(parsed, schema);
+}
+
+#[test]
+fn output_schema_cache_key_ignores_key_order() {
+    let first = json!({ "type": "object", "required": ["a"] });
+    let second = json!({ "required": ["a"], "type": "object" });
+
+    assert_eq!(
+        OutputSchemaFile::cache_key(&first),
+        OutputSchemaFile::cache_key(&second)
+    );
+}
+
+#[test]
+fn output_schema_cache_key_distinguishes_schemas() {
+    let first = json!({ "type": "object", "properties": { "a": { "type": "string" } } });
+    let second = json!({ "type": "object", "properties": { "a": { "type": "number" } } });
+
+    assert_ne!(
+        OutputSchemaFile::cache_key(&first),
+        OutputSchemaFile::cache_key(&second)
+    );
+}
+
+#[test]
+fn output_schema_directory_is_copied_with_its_refs() {
+    let source = tempfile::tempdir().expect("temp dir");
+    fs::create_dir_all(source.path().join("defs")).expect("defs dir");
+    fs::write(
+        source.path().join("schema.json"),
+        r#"{"$ref":"defs/answer.json"}"#,
+    )
+    .expect("entry");
+    fs::write(
+        source.path().join("defs/answer.json"),
+        r#"{"type":"object","properties":{"answer":{"type":"string"}}}"#,
+    )
+    .expect("definition");
+
+    let file = OutputSchemaFile::from_directory(source.path(), "schema.json").expect("schema dir");
+    let schema_path = file.schema_path().expect("schema path").to_path_buf();
+    let copy_root = schema_path.parent().expect("copy root").to_path_buf();
+
+    assert_ne!(copy_root, source.path());
+    assert_eq!(
+        fs::read_to_string(&schema_path).expect("entry copy"),
+        r#"{"$ref":"defs/answer.json"}"#
+    );
+    assert_eq!(
+        fs::read_to_string(copy_root.join("defs/answer.json")).expect("definition copy"),
+        r#"{"type":"object","properties":{"answer":{"type":"string"}}}"#
+    );
+
+    drop(file);
+    assert!(!copy_root.exists());
+    assert!(source.path().join("defs/answer.json").exists());
+}
+
+#[test]
+fn output_schema_directory_rejects_entries_outside_the_directory() {
+    let source = tempfile::tempdir().expect("temp dir");
+    fs::write(source.path().join("schema.json"), r#"{"type":"object"}"#).expect("entry");
+    let absolute = source.path().join("schema.json");
+
+    for entry in [
+        "../schema.json",
+        "defs/../../schema.json",
+        absolute.to_str().expect("utf-8"),
+    ] {
+        let err = OutputSchemaFile::from_directory(source.path(), entry).expect_err(entry);
+        assert!(matches!(err, CodexError::InvalidOutputSchema(_)), "{entry}");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn output_schema_directory_rejects_symlinks_that_escape_it() {
+    let outside = tempfile::tempdir().expect("outside dir");
+    fs::write(outside.path().join("secret.json"), r#"{"secret":true}"#).expect("secret");
+    let source = tempfile::tempdir().expect("temp dir");
+    fs::write(source.path().join("schema.json"), r#"{"type":"object"}"#).expect("entry");
+    std::os::unix::fs::symlink(
+        outside.path().join("secret.json"),
+        source.path().join("leak.json"),
+    )
+    .expect("symlink");
+
+    let err =
+        OutputSchemaFile::from_directory(source.path(), "schema.json").expect_err("escaping link");
+    assert!(matches!(err, CodexError::InvalidOutputSchema(_)));
+}
+
+#[cfg(unix)]
+#[test]
+fn output_schema_directory_copies_symlinks_inside_it() {
+    let source = tempfile::tempdir().expect("temp dir");
+    fs::write(source.path().join("real.json"), r#"{"type":"object"}"#).expect("entry");
+    std::os::unix::fs::symlink(
+        source.path().join("real.json"),
+        source.path().join("schema.json"),
+    )
+    .expect("symlink");
+
+    let file = OutputSchemaFile::from_directory(source.path(), "schema.json").expect("schema dir");
+    let schema_path = file.schema_path().expect("schema path");
+
+    assert_eq!(
+        fs::read_to_string(schema_path).expect("entry copy"),
+        r#"{"type":"object"}"#
+    );
+    assert!(!fs::symlink_metadata(schema_path)
+        .expect("metadata")
+        .file_type()
+        .is_symlink());
+}
+
+#[test]
+fn output_schema_directory_requires_the_entry_file() {
+    let source = tempfile::tempdir().expect("temp dir");
+
+    let err =
+        OutputSchemaFile::from_directory(source.path(), "schema.json").expect_err("missing entry");
+    assert!(matches!(err, CodexError::InvalidOutputSchema(_)));
+}
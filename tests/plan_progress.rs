@@ -0,0 +1,142 @@
+use futures::StreamExt;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+use codex_sdk::{CodexError, PlanProgress, ThreadEvent, ThreadEventStreamExt};
+
+fn todo_event(kind: &str, id: &str, steps: &[(&str, bool)]) -> ThreadEvent {
+    let items: Vec<_> = steps
+        .iter()
+        .map(|(text, completed)| json!({ "text": text, "completed": completed }))
+        .collect();
+    serde_json::from_value(json!({
+        "type": kind,
+        "item": { "type": "todo_list", "id": id, "items": items },
+    }))
+    .expect("todo event")
+}
+
+fn updates() -> Vec<ThreadEvent> {
+    vec![
+        serde_json::from_value(json!({ "type": "turn.started" })).expect("turn started"),
+        todo_event(
+            "item.started",
+            "plan",
+            &[
+                ("read code", false),
+                ("fix bug", false),
+                ("run tests", false),
+            ],
+        ),
+        todo_event(
+            "item.updated",
+            "plan",
+            &[
+                ("read code", true),
+                ("fix bug", false),
+                ("run tests", false),
+            ],
+        ),
+        todo_event(
+            "item.updated",
+            "plan",
+            &[
+                ("read code", true),
+                ("fix bug", false),
+                ("run tests", false),
+            ],
+        ),
+        todo_event(
+            "item.updated",
+            "plan",
+            &[
+                ("read code", true),
+                ("write regression test", true),
+                ("fix bug", false),
+                ("run tests", false),
+            ],
+        ),
+        todo_event(
+            "item.started",
+            "docs",
+            &[("update changelog", true), ("bump version", false)],
+        ),
+        todo_event(
+            "item.completed",
+            "plan",
+            &[
+                ("read code", true),
+                ("write regression test", true),
+                ("fix bug", true),
+            ],
+        ),
+    ]
+}
+
+fn summary(progress: &PlanProgress) -> (usize, usize, Option<String>) {
+    (
+        progress.completed_count(),
+        progress.total_count(),
+        progress.last_completed().map(str::to_string),
+    )
+}
+
+#[test]
+fn progress_tracks_growing_shrinking_and_multiple_lists() {
+    let mut progress = PlanProgress::new();
+    let changed: Vec<bool> = updates()
+        .iter()
+        .map(|event| progress.observe(event))
+        .collect();
+
+    assert_eq!(changed, vec![false, true, true, false, true, true, true]);
+    assert_eq!(summary(&progress), (4, 5, Some("fix bug".to_string())));
+    assert_eq!(progress.percent(), 80.0);
+    assert!(!progress.is_complete());
+}
+
+#[test]
+fn empty_progress_is_zero_percent() {
+    let progress = PlanProgress::new();
+
+    assert_eq!(summary(&progress), (0, 0, None));
+    assert_eq!(progress.percent(), 0.0);
+    assert!(!progress.is_complete());
+}
+
+#[tokio::test]
+async fn plan_progress_yields_a_snapshot_per_change() {
+    let events = futures::stream::iter(updates().into_iter().map(Ok::<_, CodexError>));
+
+    let snapshots: Vec<_> = events
+        .plan_progress()
+        .map(|progress| summary(&progress.expect("progress")))
+        .collect()
+        .await;
+
+    assert_eq!(
+        snapshots,
+        vec![
+            (0, 3, None),
+            (1, 3, Some("read code".to_string())),
+            (2, 4, Some("write regression test".to_string())),
+            (3, 6, Some("update changelog".to_string())),
+            (4, 5, Some("fix bug".to_string())),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn plan_progress_stops_at_the_first_error() {
+    let events = futures::stream::iter(vec![
+        Ok(updates().remove(1)),
+        Err(CodexError::Aborted),
+        Ok(updates().remove(2)),
+    ]);
+
+    let results: Vec<_> = events.plan_progress().collect().await;
+
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(CodexError::Aborted)));
+}
@@ -0,0 +1,71 @@
+#![cfg(unix)]
+
+mod common;
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{Codex, CodexOptions, Cost, ModelPricing, ThreadOptions, TurnOptions};
+
+const TURN: &[&str] = &[
+    r#"{"type":"thread.started","thread_id":"0199a213-81c0-7800-8aa1-bbab2a035a53"}"#,
+    r#"{"type":"turn.started"}"#,
+    r#"{"type":"item.completed","item":{"id":"item_0","type":"agent_message","text":"Done."}}"#,
+    r#"{"type":"turn.completed","usage":{"input_tokens":2000000,"cached_input_tokens":1000000,"output_tokens":500000}}"#,
+];
+
+async fn run_turn(options: CodexOptions, model: Option<&str>) -> codex_sdk::Turn {
+    let fake = common::fake_codex(TURN);
+    let codex = Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        ..options
+    })
+    .expect("codex");
+    let thread = codex.start_thread(ThreadOptions {
+        model: model.map(str::to_string),
+        ..ThreadOptions::default()
+    });
+    thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn")
+}
+
+#[tokio::test]
+async fn turn_cost_uses_the_thread_model() {
+    let turn = run_turn(CodexOptions::default(), Some("gpt-5-codex")).await;
+
+    assert_eq!(
+        turn.estimated_cost(),
+        Some(Cost {
+            input: 1.25,
+            cached_input: 0.125,
+            output: 5.0,
+        })
+    );
+}
+
+#[tokio::test]
+async fn unknown_or_default_models_have_no_cost() {
+    let unknown = run_turn(CodexOptions::default(), Some("my-finetune")).await;
+    let default = run_turn(CodexOptions::default(), None).await;
+
+    assert_eq!(unknown.estimated_cost(), None);
+    assert_eq!(default.estimated_cost(), None);
+}
+
+#[tokio::test]
+async fn registered_pricing_is_used_for_custom_models() {
+    let options =
+        CodexOptions::default().with_model_pricing("my-finetune", ModelPricing::new(2.0, 0.5, 8.0));
+
+    let turn = run_turn(options, Some("my-finetune")).await;
+
+    assert_eq!(
+        turn.estimated_cost(),
+        Some(Cost {
+            input: 2.0,
+            cached_input: 0.5,
+            output: 4.0,
+        })
+    );
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{CodexError, Input, PromptTemplate, UserInput};
+
+#[test]
+fn render_interpolates_variables() {
+    let template =
+        PromptTemplate::parse("Review {{file}} focusing on {{ concern }}").expect("parse");
+    let values = HashMap::from([("file", "src/lib.rs"), ("concern", "error handling")]);
+
+    assert_eq!(template.variables(), vec!["file", "concern"]);
+    assert_eq!(
+        template.render(&values).expect("render"),
+        Input::Text("Review src/lib.rs focusing on error handling".to_string())
+    );
+}
+
+#[test]
+fn strict_mode_errors_on_missing_variables() {
+    let template = PromptTemplate::parse("Review {{file}} focusing on {{concern}}")
+        .expect("parse")
+        .strict();
+    let values = HashMap::from([("file", "src/lib.rs")]);
+
+    let result = template.render(&values);
+    assert!(matches!(result, Err(CodexError::MissingTemplateVariable(name)) if name == "concern"));
+}
+
+#[test]
+fn lenient_mode_renders_missing_variables_as_empty() {
+    let template = PromptTemplate::parse("Review {{file}}{{suffix}}").expect("parse");
+    let values = HashMap::from([("file", "src/lib.rs")]);
+
+    assert_eq!(
+        template.render(&values).expect("render"),
+        Input::Text("Review src/lib.rs".to_string())
+    );
+}
+
+#[test]
+fn escaped_braces_render_literally() {
+    let template =
+        PromptTemplate::parse(r"Use \{{name}} syntax for {{lang}} templates").expect("parse");
+    let values = HashMap::from([("lang", "handlebars")]);
+
+    assert_eq!(template.variables(), vec!["lang"]);
+    assert_eq!(
+        template.render(&values).expect("render"),
+        Input::Text("Use {{name}} syntax for handlebars templates".to_string())
+    );
+}
+
+#[test]
+fn malformed_placeholders_fail_to_parse() {
+    assert!(matches!(
+        PromptTemplate::parse("Review {{file"),
+        Err(CodexError::InvalidTemplate(_))
+    ));
+    assert!(matches!(
+        PromptTemplate::parse("Review {{ }}"),
+        Err(CodexError::InvalidTemplate(_))
+    ));
+}
+
+#[test]
+fn image_placeholders_render_structured_input() {
+    let template =
+        PromptTemplate::parse("Compare {{image:before}} with {{image:after}} for {{page}}")
+            .expect("parse");
+    let values = HashMap::from([
+        ("before", "./before.png"),
+        ("after", "./after.png"),
+        ("page", "the login page"),
+    ]);
+
+    assert_eq!(
+        template.render(&values).expect("render"),
+        Input::Structured(vec![
+            UserInput::Text {
+                text: "Compare".to_string(),
+            },
+            UserInput::LocalImage {
+                path: "./before.png".to_string(),
+            },
+            UserInput::Text {
+                text: "with".to_string(),
+            },
+            UserInput::LocalImage {
+                path: "./after.png".to_string(),
+            },
+            UserInput::Text {
+                text: "for the login page".to_string(),
+            },
+        ])
+    );
+}
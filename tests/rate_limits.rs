@@ -0,0 +1,111 @@
+#![cfg(unix)]
+
+mod common;
+
+use futures::StreamExt;
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{RateLimitSnapshot, ThreadEvent, ThreadOptions, TurnOptions, Usage};
+
+const RATE_LIMITS: &str = include_str!("fixtures/rate_limits.jsonl");
+
+fn fixture_line(index: usize) -> &'static str {
+    RATE_LIMITS.lines().nth(index).expect("fixture line")
+}
+
+fn rate_limits_of(line: &str) -> Option<RateLimitSnapshot> {
+    match serde_json::from_str(line).expect("event") {
+        ThreadEvent::TurnCompleted { rate_limits, .. } => rate_limits,
+        other => panic!("unexpected event: {other:?}"),
+    }
+}
+
+fn snapshot() -> RateLimitSnapshot {
+    RateLimitSnapshot {
+        primary_used_percent: Some(12.5),
+        secondary_used_percent: Some(40.0),
+        resets_at: Some(1760620800),
+        secondary_resets_at: Some(1761004800),
+    }
+}
+
+#[test]
+fn flat_rate_limits_are_parsed() {
+    assert_eq!(rate_limits_of(fixture_line(0)), Some(snapshot()));
+}
+
+#[test]
+fn windowed_rate_limits_are_parsed() {
+    assert_eq!(rate_limits_of(fixture_line(1)), Some(snapshot()));
+}
+
+#[test]
+fn missing_or_unknown_rate_limits_are_ignored() {
+    assert_eq!(rate_limits_of(fixture_line(2)), None);
+    assert_eq!(rate_limits_of(fixture_line(3)), None);
+}
+
+#[test]
+fn rate_limits_round_trip() {
+    let event = ThreadEvent::TurnCompleted {
+        usage: Usage::default(),
+        rate_limits: Some(snapshot()),
+    };
+
+    let encoded = serde_json::to_string(&event).expect("serialize");
+
+    assert_eq!(
+        serde_json::from_str::<ThreadEvent>(&encoded).expect("deserialize"),
+        event
+    );
+}
+
+#[test]
+fn events_without_rate_limits_serialize_without_the_field() {
+    let event = ThreadEvent::TurnCompleted {
+        usage: Usage::default(),
+        rate_limits: None,
+    };
+
+    let value = serde_json::to_value(&event).expect("serialize");
+
+    assert_eq!(value.get("rate_limits"), None);
+}
+
+#[tokio::test]
+async fn collected_turn_carries_rate_limits() {
+    let fake = common::fake_codex(&[
+        r#"{"type":"thread.started","thread_id":"0199a213-81c0-7800-8aa1-bbab2a035a53"}"#,
+        r#"{"type":"turn.started"}"#,
+        r#"{"type":"item.completed","item":{"id":"item_0","type":"agent_message","text":"Done."}}"#,
+        fixture_line(1),
+    ]);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let turn = thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    assert_eq!(turn.rate_limits, Some(snapshot()));
+}
+
+#[tokio::test]
+async fn streamed_turn_exposes_rate_limits_after_completion() {
+    let fake = common::fake_codex(&[
+        r#"{"type":"thread.started","thread_id":"0199a213-81c0-7800-8aa1-bbab2a035a53"}"#,
+        r#"{"type":"turn.started"}"#,
+        fixture_line(0),
+    ]);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let mut streamed = thread
+        .run_streamed("hello".into(), TurnOptions::default())
+        .expect("streamed");
+    assert_eq!(streamed.rate_limits(), None);
+    while let Some(event) = streamed.events.next().await {
+        event.expect("event");
+    }
+
+    assert_eq!(streamed.rate_limits(), Some(snapshot()));
+}
@@ -0,0 +1,42 @@
+#![cfg(unix)]
+
+mod common;
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{CodexError, ThreadOptions, TurnOptions};
+
+const SILENT_TURN: &[&str] = &[
+    r#"{"type":"thread.started","thread_id":"thread-1"}"#,
+    r#"{"type":"item.completed","item":{"type":"reasoning","id":"item-0","text":"thinking"}}"#,
+    r#"{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":1}}"#,
+];
+
+#[tokio::test]
+async fn empty_final_response_is_an_error_by_default() {
+    let fake = common::fake_codex(SILENT_TURN);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let result = thread.run("hello".into(), TurnOptions::default()).await;
+
+    assert!(
+        matches!(result, Err(CodexError::TurnFailed(message)) if message == "no agent response produced")
+    );
+}
+
+#[tokio::test]
+async fn empty_final_response_is_allowed_when_not_required() {
+    let fake = common::fake_codex(SILENT_TURN);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        require_response: false,
+        ..ThreadOptions::default()
+    });
+
+    let turn = thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    assert_eq!(turn.final_response, "");
+    assert_eq!(turn.items.len(), 1);
+}
@@ -0,0 +1,61 @@
+#![cfg(unix)]
+
+mod common;
+
+use futures::StreamExt;
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{ThreadEvent, ThreadOptions, TurnOptions};
+
+const EVENTS: &[&str] = &[
+    r#"{"type":"thread.started","thread_id":"5f0c1a52-8d7e-4b3a-9c61-2e4f7a9b0d13"}"#,
+    r#"{"type":"item.completed","item":{"type":"agent_message","id":"item-0","text":"done"}}"#,
+    r#"{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":1}}"#,
+];
+
+#[tokio::test]
+async fn first_turn_resumes_last_then_captures_id() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).resume_last_thread(ThreadOptions::default());
+    assert_eq!(thread.id(), None);
+
+    let mut events = thread
+        .run_streamed("continue".into(), TurnOptions::default())
+        .expect("streamed")
+        .events;
+    let first = events.next().await.expect("event").expect("parsed");
+    assert_eq!(
+        first,
+        ThreadEvent::ThreadStarted {
+            thread_id: common::THREAD_ID.into(),
+        }
+    );
+    while let Some(event) = events.next().await {
+        event.expect("event");
+    }
+
+    let args = fake.recorded_args();
+    assert!(args.windows(2).any(|pair| pair == ["resume", "--last"]));
+    assert_eq!(thread.id(), Some(common::THREAD_ID.into()));
+}
+
+#[tokio::test]
+async fn later_turns_resume_by_id() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).resume_last_thread(ThreadOptions::default());
+
+    thread
+        .run("continue".into(), TurnOptions::default())
+        .await
+        .expect("first turn");
+    thread
+        .run("and then".into(), TurnOptions::default())
+        .await
+        .expect("second turn");
+
+    let args = fake.recorded_args();
+    assert!(args
+        .windows(2)
+        .any(|pair| pair == ["resume", common::THREAD_ID]));
+    assert_eq!(args.iter().any(|arg| arg == "--last"), false);
+}
@@ -0,0 +1,184 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::path::PathBuf;
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{
+    CodexError, ReviewCodeLocation, ReviewLineRange, ReviewRequest, ThreadOptions, TurnOptions,
+    Usage,
+};
+
+const REVIEW_RUN: &str = include_str!("fixtures/review_base_branch.jsonl");
+
+fn review_lines() -> Vec<&'static str> {
+    REVIEW_RUN.lines().collect()
+}
+
+#[tokio::test]
+async fn review_against_base_branch_parses_findings() {
+    let fake = common::fake_codex(&review_lines());
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let result = thread
+        .review(
+            ReviewRequest::BaseBranch("main".to_string()),
+            TurnOptions::default(),
+        )
+        .await
+        .expect("review");
+
+    assert_eq!(
+        fake.recorded_args(),
+        vec!["exec", "--experimental-json", "review", "--base", "main"]
+    );
+    assert_eq!(result.findings.len(), 2);
+    assert_eq!(
+        result.findings[0].title,
+        "[P1] Guard against empty thread ids before resuming"
+    );
+    assert_eq!(result.findings[0].priority, Some(1));
+    assert_eq!(result.findings[0].confidence_score, Some(0.82));
+    assert_eq!(
+        result.findings[0].code_location,
+        Some(ReviewCodeLocation {
+            absolute_file_path: PathBuf::from("/work/codex-sdk-rs/src/codex.rs"),
+            line_range: ReviewLineRange { start: 52, end: 60 },
+        })
+    );
+    assert_eq!(result.findings[1].priority, Some(3));
+    assert_eq!(
+        result.overall_correctness.as_deref(),
+        Some("patch is incorrect")
+    );
+    assert_eq!(result.overall_confidence_score, Some(0.74));
+    assert_eq!(result.items.len(), 3);
+    assert_eq!(
+        result.usage,
+        Some(Usage {
+            input_tokens: 18342,
+            cached_input_tokens: 12032,
+            output_tokens: 611,
+        })
+    );
+}
+
+#[tokio::test]
+async fn review_of_uncommitted_changes_uses_uncommitted_flag() {
+    let fake = common::fake_codex(&review_lines());
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    thread
+        .review(ReviewRequest::Uncommitted, TurnOptions::default())
+        .await
+        .expect("review");
+
+    assert_eq!(
+        fake.recorded_args(),
+        vec!["exec", "--experimental-json", "review", "--uncommitted"]
+    );
+}
+
+#[tokio::test]
+async fn review_of_patch_sends_diff_over_stdin() {
+    let fake = common::fake_codex_script(&format!(
+        "cat > \"$(dirname \"$0\")/stdin.txt\"\n{}",
+        review_lines()
+            .iter()
+            .map(|line| format!("printf '%s\\n' '{}'\n", line.replace('\'', "'\\''")))
+            .collect::<String>()
+    ));
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let patch = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-pub mod a;\n+pub mod b;\n";
+    let result = thread
+        .review(
+            ReviewRequest::Patch(patch.to_string()),
+            TurnOptions::default(),
+        )
+        .await
+        .expect("review");
+
+    assert_eq!(
+        fake.recorded_args(),
+        vec!["exec", "--experimental-json", "review", "-"]
+    );
+    let stdin = std::fs::read_to_string(fake.path.with_file_name("stdin.txt")).expect("stdin");
+    assert!(stdin.contains("```diff\n--- a/src/lib.rs"));
+    assert!(stdin.contains("+pub mod b;\n```"));
+    assert_eq!(result.findings.len(), 2);
+}
+
+#[tokio::test]
+async fn review_does_not_resume_or_replace_thread_id() {
+    let fake = common::fake_codex(&review_lines());
+    let thread =
+        common::codex_for(&fake).resume_thread(common::THREAD_ID, ThreadOptions::default());
+
+    thread
+        .review(ReviewRequest::Uncommitted, TurnOptions::default())
+        .await
+        .expect("review");
+
+    assert!(!fake.recorded_args().iter().any(|arg| arg == "resume"));
+    assert_eq!(
+        thread.id().map(|id| id.to_string()),
+        Some(common::THREAD_ID.to_string())
+    );
+}
+
+#[tokio::test]
+async fn review_without_structured_output_has_no_findings() {
+    let fake = common::fake_codex(&[
+        r#"{"type":"thread.started","thread_id":"0199a7c4-3b5e-7d21-9f10-6c2a8e4b1d07"}"#,
+        r#"{"type":"item.completed","item":{"type":"agent_message","id":"item_0","text":"Looks good to me."}}"#,
+        r#"{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":1}}"#,
+    ]);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let result = thread
+        .review(ReviewRequest::Uncommitted, TurnOptions::default())
+        .await
+        .expect("review");
+
+    assert!(result.findings.is_empty());
+    assert_eq!(result.overall_correctness, None);
+    assert_eq!(result.final_response, "Looks good to me.");
+}
+
+#[tokio::test]
+async fn review_reads_findings_from_fenced_json() {
+    let text = "Review complete.\n\n```json\n{\"findings\":[{\"title\":\"[P2] Leak\",\"body\":\"File handle is never closed.\"}],\"overall_correctness\":\"patch is incorrect\"}\n```\n";
+    let message = serde_json::json!({
+        "type": "item.completed",
+        "item": { "type": "agent_message", "id": "item_0", "text": text },
+    })
+    .to_string();
+    let fake = common::fake_codex(&[&message]);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let result = thread
+        .review(ReviewRequest::Uncommitted, TurnOptions::default())
+        .await
+        .expect("review");
+
+    assert_eq!(result.findings.len(), 1);
+    assert_eq!(result.findings[0].title, "[P2] Leak");
+    assert_eq!(result.findings[0].code_location, None);
+}
+
+#[tokio::test]
+async fn review_rejects_empty_base_branch() {
+    let fake = common::fake_codex(&[]);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let result = thread
+        .review(
+            ReviewRequest::BaseBranch(" ".to_string()),
+            TurnOptions::default(),
+        )
+        .await;
+    assert!(matches!(result, Err(CodexError::InvalidArgs(_))));
+}
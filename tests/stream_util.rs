@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{strip_ansi, with_line_timeout, CodexError, CodexLineStream};
+
+fn delayed_lines(lines: &[(u64, &str)]) -> CodexLineStream {
+    let lines: Vec<(u64, String)> = lines
+        .iter()
+        .map(|(delay, line)| (*delay, line.to_string()))
+        .collect();
+    Box::pin(
+        futures::stream::iter(lines).then(|(delay, line)| async move {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            Ok::<_, CodexError>(line)
+        }),
+    )
+}
+
+#[tokio::test(start_paused = true)]
+async fn lines_within_the_timeout_pass_through() {
+    let stream = with_line_timeout(
+        delayed_lines(&[(50, "one"), (90, "two"), (10, "three")]),
+        Duration::from_millis(100),
+    );
+
+    let lines: Vec<String> = stream.map(|line| line.expect("line")).collect().await;
+    assert_eq!(lines, vec!["one", "two", "three"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_slow_line_times_out_and_ends_the_stream() {
+    let start = tokio::time::Instant::now();
+    let mut stream = with_line_timeout(
+        delayed_lines(&[(50, "one"), (150, "two"), (10, "three")]),
+        Duration::from_millis(100),
+    );
+
+    assert_eq!(stream.next().await.expect("line").expect("ok"), "one");
+    let err = stream.next().await.expect("timeout").expect_err("timeout");
+    assert!(matches!(err, CodexError::Timeout));
+    assert_eq!(start.elapsed(), Duration::from_millis(150));
+    assert!(stream.next().await.is_none());
+}
+
+#[test]
+fn strip_ansi_removes_color_and_cursor_sequences() {
+    assert_eq!(
+        strip_ansi("\u{1b}[1;31merror\u{1b}[0m: missing \u{1b}[2Kconfig"),
+        "error: missing config"
+    );
+}
+
+#[test]
+fn strip_ansi_removes_osc_sequences() {
+    assert_eq!(
+        strip_ansi("\u{1b}]0;codex\u{7}ready \u{1b}]8;;https://x\u{1b}\\link"),
+        "ready link"
+    );
+}
+
+#[test]
+fn strip_ansi_keeps_plain_text() {
+    assert_eq!(strip_ansi("plain [text] ✓"), "plain [text] ✓");
+}
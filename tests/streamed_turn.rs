@@ -0,0 +1,268 @@
+#![cfg(unix)]
+
+mod common;
+
+use futures::StreamExt;
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{CodexError, ThreadEvent, ThreadItem, ThreadOptions, TurnOptions};
+
+const EVENTS: &[&str] = &[
+    r#"{"type":"thread.started","thread_id":"5f0c1a52-8d7e-4b3a-9c61-2e4f7a9b0d13"}"#,
+    r#"{"type":"turn.started"}"#,
+    r#"{"type":"item.completed","item":{"type":"reasoning","id":"item-0","text":"thinking"}}"#,
+    r#"{"type":"item.completed","item":{"type":"agent_message","id":"item-1","text":"done"}}"#,
+    r#"{"type":"turn.completed","usage":{"input_tokens":3,"cached_input_tokens":1,"output_tokens":2}}"#,
+];
+
+#[tokio::test]
+async fn collect_returns_every_event_in_order() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let events = thread
+        .run_streamed("hello".into(), TurnOptions::default())
+        .expect("streamed")
+        .collect()
+        .await
+        .expect("events");
+
+    let expected: Vec<ThreadEvent> = EVENTS
+        .iter()
+        .map(|line| serde_json::from_str(line).expect("event"))
+        .collect();
+    assert_eq!(events, expected);
+}
+
+#[tokio::test]
+async fn collect_turn_matches_run() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let from_run = thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("run");
+    let from_stream = thread
+        .run_streamed("hello".into(), TurnOptions::default())
+        .expect("streamed")
+        .collect_turn()
+        .await
+        .expect("collect turn");
+
+    assert_eq!(from_stream, from_run);
+    assert_eq!(from_run.final_response, "done");
+    assert_eq!(from_run.items.len(), 2);
+}
+
+#[tokio::test]
+async fn collect_turn_after_inspecting_events_aggregates_the_rest() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let mut streamed = thread
+        .run_streamed("hello".into(), TurnOptions::default())
+        .expect("streamed");
+    let first = streamed.events.next().await.expect("event").expect("ok");
+    assert_eq!(
+        first,
+        ThreadEvent::ThreadStarted {
+            thread_id: "5f0c1a52-8d7e-4b3a-9c61-2e4f7a9b0d13".into()
+        }
+    );
+
+    let turn = streamed.collect_turn().await.expect("collect turn");
+    assert_eq!(turn.final_response, "done");
+}
+
+#[tokio::test]
+async fn collect_short_circuits_on_error() {
+    let fake = common::fake_codex(&[r#"{"type":"turn.started"}"#, "not json"]);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let result = thread
+        .run_streamed("hello".into(), TurnOptions::default())
+        .expect("streamed")
+        .collect()
+        .await;
+    assert!(matches!(result, Err(CodexError::InvalidEvent(_))));
+}
+
+#[tokio::test]
+async fn invalid_utf8_output_is_reported() {
+    let fake = common::fake_codex_script(
+        "cat > /dev/null\nprintf '%s\\n' '{\"type\":\"turn.started\"}'\nprintf '{\"type\":\"\\377\\376\"}\\n'\n",
+    );
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let result = thread
+        .run_streamed("hello".into(), TurnOptions::default())
+        .expect("streamed")
+        .collect()
+        .await;
+    assert!(matches!(result, Err(CodexError::InvalidUtf8(_))));
+}
+
+#[tokio::test]
+async fn run_with_broadcast_fans_items_out_to_every_subscriber() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let (sender, turn) = thread
+        .run_with_broadcast("hello".into(), TurnOptions::default(), 8)
+        .expect("broadcast");
+    let first = sender.subscribe();
+    let second = sender.subscribe();
+    drop(sender);
+
+    let collect = |mut receiver: tokio::sync::broadcast::Receiver<ThreadItem>| async move {
+        let mut ids = Vec::new();
+        while let Ok(item) = receiver.recv().await {
+            ids.push(item.id().to_string());
+        }
+        ids
+    };
+    let (turn, first_ids, second_ids) = tokio::join!(turn, collect(first), collect(second));
+
+    let turn = turn.expect("turn");
+    assert_eq!(turn.final_response, "done");
+    assert_eq!(first_ids, vec!["item-0", "item-1"]);
+    assert_eq!(second_ids, vec!["item-0", "item-1"]);
+}
+
+#[test]
+fn run_with_broadcast_rejects_zero_capacity() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let err = thread
+        .run_with_broadcast("hello".into(), TurnOptions::default(), 0)
+        .err()
+        .expect("zero capacity");
+    assert!(matches!(err, CodexError::InvalidArgs(_)));
+}
+
+#[tokio::test]
+async fn subscribe_items_sends_completed_items_and_resolves_turn() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let (mut items, handle) = thread
+        .subscribe_items("hello".into(), TurnOptions::default(), 1)
+        .expect("subscribe");
+    let mut ids = Vec::new();
+    while let Some(item) = items.recv().await {
+        ids.push(item.id().to_string());
+    }
+
+    let turn = handle.await.expect("join").expect("turn");
+    assert_eq!(ids, vec!["item-0", "item-1"]);
+    assert_eq!(turn.final_response, "done");
+    assert_eq!(thread.turn_history().len(), 1);
+}
+
+#[tokio::test]
+async fn subscribe_items_aborts_when_receiver_is_dropped() {
+    let mut lines = EVENTS[..2].to_vec();
+    let items: Vec<String> = (0..5)
+        .map(|index| {
+            format!(
+                r#"{{"type":"item.completed","item":{{"type":"reasoning","id":"item-{index}","text":"step"}}}}"#
+            )
+        })
+        .collect();
+    lines.extend(items.iter().map(String::as_str));
+    lines.extend(&EVENTS[3..]);
+    let fake = common::fake_codex(&lines);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let (mut receiver, handle) = thread
+        .subscribe_items("hello".into(), TurnOptions::default(), 1)
+        .expect("subscribe");
+    let first = receiver.recv().await.expect("first item");
+    assert_eq!(first.id(), "item-0");
+    drop(receiver);
+
+    let result = tokio::time::timeout(std::time::Duration::from_secs(5), handle)
+        .await
+        .expect("task terminates")
+        .expect("join");
+    assert!(matches!(result, Err(CodexError::Aborted)));
+    assert!(!thread.is_running());
+}
+
+#[test]
+fn subscribe_items_rejects_zero_buffer() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let err = thread
+        .subscribe_items("hello".into(), TurnOptions::default(), 0)
+        .expect_err("zero buffer");
+    assert!(matches!(err, CodexError::InvalidArgs(_)));
+}
+
+#[tokio::test]
+async fn timestamped_items_arrive_in_non_decreasing_order() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+    let before = std::time::SystemTime::now();
+
+    let items: Vec<_> = thread
+        .run_streamed_timestamped("hello".into(), TurnOptions::default())
+        .expect("streamed")
+        .map(|item| item.expect("item"))
+        .collect()
+        .await;
+
+    assert_eq!(
+        items.iter().map(|item| item.item.id()).collect::<Vec<_>>(),
+        vec!["item-0", "item-1"]
+    );
+    assert!(items[0].received_at >= before);
+    assert!(items
+        .windows(2)
+        .all(|pair| pair[0].received_at <= pair[1].received_at));
+}
+
+#[tokio::test]
+async fn timestamped_stream_surfaces_turn_failures() {
+    let fake = common::fake_codex(&[
+        r#"{"type":"turn.started"}"#,
+        r#"{"type":"turn.failed","error":{"message":"rate limited"}}"#,
+    ]);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let results: Vec<_> = thread
+        .run_streamed_timestamped("hello".into(), TurnOptions::default())
+        .expect("streamed")
+        .collect()
+        .await;
+
+    assert!(matches!(
+        results.as_slice(),
+        [Err(CodexError::TurnFailed(message))] if message == "rate limited"
+    ));
+}
+
+#[tokio::test]
+async fn raw_lines_are_unparsed_thread_events() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let lines = thread
+        .run_streamed_raw("hello".into(), TurnOptions::default())
+        .expect("raw stream");
+    assert!(thread.is_running());
+    let lines: Vec<String> = lines.map(|line| line.expect("line")).collect().await;
+
+    assert_eq!(lines, EVENTS);
+    for line in &lines {
+        serde_json::from_str::<ThreadEvent>(line).expect("thread event");
+    }
+    assert!(!thread.is_running());
+    assert_eq!(
+        thread.id().map(|id| id.to_string()).as_deref(),
+        Some("5f0c1a52-8d7e-4b3a-9c61-2e4f7a9b0d13")
+    );
+}
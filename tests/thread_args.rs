@@ -0,0 +1,427 @@
+#![cfg(unix)]
+
+mod common;
+
+use pretty_assertions::assert_eq;
+
+use std::collections::HashMap;
+
+use codex_sdk::{
+    Codex, CodexError, CodexOptions, HistoryPersistence, Input, ModelProviderConfig,
+    ModelReasoningEffort, ThreadOptions, TurnOptions, UserInput, WireApi,
+};
+
+const EVENTS: &[&str] = &[
+    r#"{"type":"thread.started","thread_id":"5f0c1a52-8d7e-4b3a-9c61-2e4f7a9b0d13"}"#,
+    r#"{"type":"item.completed","item":{"type":"agent_message","id":"item-0","text":"done"}}"#,
+    r#"{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":1}}"#,
+];
+
+fn values_after<'a>(args: &'a [String], flag: &str) -> Vec<&'a str> {
+    args.windows(2)
+        .filter(|pair| pair[0] == flag)
+        .map(|pair| pair[1].as_str())
+        .collect()
+}
+
+#[tokio::test]
+async fn turn_directories_merge_with_thread_directories() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        additional_directories: Some(vec!["/repo/a".to_string(), "/repo/b".to_string()]),
+        ..ThreadOptions::default()
+    });
+
+    thread
+        .run(
+            "hello".into(),
+            TurnOptions {
+                additional_directories: Some(vec!["/repo/b".to_string(), "/repo/c".to_string()]),
+                ..TurnOptions::default()
+            },
+        )
+        .await
+        .expect("turn");
+
+    let args = fake.recorded_args();
+    assert_eq!(
+        values_after(&args, "--add-dir"),
+        vec!["/repo/a", "/repo/b", "/repo/c"]
+    );
+}
+
+#[tokio::test]
+async fn output_schema_file_is_reused_across_turns() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+    let turn_options = TurnOptions {
+        output_schema: Some(serde_json::json!({ "type": "object" })),
+        ..TurnOptions::default()
+    };
+
+    thread
+        .run("first".into(), turn_options.clone())
+        .await
+        .expect("first turn");
+    let first = values_after(&fake.recorded_args(), "--output-schema")[0].to_string();
+
+    thread
+        .run("second".into(), turn_options)
+        .await
+        .expect("second turn");
+    let args = fake.recorded_args();
+
+    assert_eq!(values_after(&args, "--output-schema"), vec![first.as_str()]);
+}
+
+#[tokio::test]
+async fn output_schema_cache_evicts_the_least_recently_used_schema() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+    let schema = |index: usize| TurnOptions {
+        output_schema: Some(serde_json::json!({ "type": "object", "title": format!("s{index}") })),
+        ..TurnOptions::default()
+    };
+
+    thread.run("first".into(), schema(0)).await.expect("turn");
+    let first = values_after(&fake.recorded_args(), "--output-schema")[0].to_string();
+    for index in 1..=16 {
+        thread
+            .run("other".into(), schema(index))
+            .await
+            .expect("turn");
+    }
+    thread.run("again".into(), schema(0)).await.expect("turn");
+    let args = fake.recorded_args();
+
+    assert_ne!(values_after(&args, "--output-schema"), vec![first.as_str()]);
+    assert_eq!(std::path::Path::new(&first).exists(), false);
+}
+
+#[tokio::test]
+async fn schema_dir_places_output_schema_files() {
+    let fake = common::fake_codex(EVENTS);
+    let dir = tempfile::tempdir().expect("tempdir");
+    let schema_dir = dir.path().join("schemas");
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        schema_dir: Some(schema_dir.clone()),
+        ..ThreadOptions::default()
+    });
+
+    thread
+        .run(
+            "hello".into(),
+            TurnOptions {
+                output_schema: Some(serde_json::json!({ "type": "object" })),
+                ..TurnOptions::default()
+            },
+        )
+        .await
+        .expect("turn");
+
+    let args = fake.recorded_args();
+    let schema_path = std::path::Path::new(values_after(&args, "--output-schema")[0]);
+    assert_eq!(schema_path.parent(), Some(schema_dir.as_path()));
+    assert_eq!(schema_path.exists(), true);
+}
+
+async fn profile_args(thread_profile: Option<&str>, turn_profile: Option<&str>) -> Vec<String> {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        profile: thread_profile.map(str::to_string),
+        ..ThreadOptions::default()
+    });
+
+    thread
+        .run(
+            "hello".into(),
+            TurnOptions {
+                profile: turn_profile.map(str::to_string),
+                ..TurnOptions::default()
+            },
+        )
+        .await
+        .expect("turn");
+
+    let args = fake.recorded_args();
+    values_after(&args, "--profile")
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+#[tokio::test]
+async fn turn_profile_overrides_thread_profile() {
+    assert_eq!(profile_args(Some("fast"), None).await, vec!["fast"]);
+    assert_eq!(profile_args(None, Some("deep")).await, vec!["deep"]);
+    assert_eq!(profile_args(Some("fast"), Some("deep")).await, vec!["deep"]);
+    assert_eq!(profile_args(None, None).await, Vec::<String>::new());
+}
+
+#[tokio::test]
+async fn dangerous_bypass_requires_client_opt_in() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        dangerously_bypass_approvals_and_sandbox: Some(true),
+        ..ThreadOptions::default()
+    });
+
+    let err = thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect_err("bypass without opt-in");
+    assert!(matches!(err, CodexError::DangerousBypassNotAllowed));
+}
+
+#[tokio::test]
+async fn dangerous_bypass_emits_flag_when_allowed() {
+    let fake = common::fake_codex(EVENTS);
+    let codex = Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        allow_dangerous_bypass: Some(true),
+        ..CodexOptions::default()
+    })
+    .expect("codex");
+    let thread = codex.start_thread(ThreadOptions {
+        dangerously_bypass_approvals_and_sandbox: Some(true),
+        ..ThreadOptions::default()
+    });
+
+    thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    let args = fake.recorded_args();
+    assert!(args
+        .iter()
+        .any(|arg| arg == "--dangerously-bypass-approvals-and-sandbox"));
+    assert!(!args.iter().any(|arg| arg == "--sandbox"));
+}
+
+#[tokio::test]
+async fn append_instructions_are_added_to_the_prompt() {
+    let mut script = String::from("cat > \"$(dirname \"$0\")/stdin.txt\"\n");
+    for line in EVENTS {
+        script.push_str(&format!("printf '%s\\n' '{line}'\n"));
+    }
+    let fake = common::fake_codex_script(&script);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        base_instructions: Some("You review Rust code.".to_string()),
+        append_instructions: Some("Answer in one line.".to_string()),
+        ..ThreadOptions::default()
+    });
+
+    thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    let stdin = std::fs::read_to_string(fake.path.with_file_name("stdin.txt")).expect("stdin");
+    assert_eq!(stdin, "hello\n\nAnswer in one line.");
+    assert_eq!(
+        values_after(&fake.recorded_args(), "--config"),
+        vec!["base_instructions=\"You review Rust code.\""]
+    );
+}
+
+#[tokio::test]
+async fn model_providers_become_config_overrides() {
+    let fake = common::fake_codex(EVENTS);
+    let codex = Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        model_providers: Some(HashMap::from([(
+            "azure".to_string(),
+            ModelProviderConfig {
+                name: "Azure OpenAI".to_string(),
+                base_url: "https://acme.openai.azure.com/openai".to_string(),
+                env_key: Some("AZURE_OPENAI_API_KEY".to_string()),
+                wire_api: Some(WireApi::Responses),
+                query_params: Some(HashMap::from([(
+                    "api-version".to_string(),
+                    "2025-04-01-preview".to_string(),
+                )])),
+            },
+        )])),
+        ..CodexOptions::default()
+    })
+    .expect("codex");
+    let thread = codex.start_thread(ThreadOptions {
+        model_provider: Some("azure".to_string()),
+        ..ThreadOptions::default()
+    });
+
+    thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    assert_eq!(
+        values_after(&fake.recorded_args(), "--config"),
+        vec![
+            "model_providers.azure.base_url=\"https://acme.openai.azure.com/openai\"",
+            "model_providers.azure.env_key=\"AZURE_OPENAI_API_KEY\"",
+            "model_providers.azure.name=\"Azure OpenAI\"",
+            "model_providers.azure.query_params.api-version=\"2025-04-01-preview\"",
+            "model_providers.azure.wire_api=\"responses\"",
+            "model_provider=\"azure\"",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn notify_command_becomes_a_config_array() {
+    let fake = common::fake_codex(EVENTS);
+    let codex = Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        notify_command: Some(vec![
+            "/Applications/My App.app/notify".to_string(),
+            "--title".to_string(),
+            "Codex \"done\"".to_string(),
+        ]),
+        ..CodexOptions::default()
+    })
+    .expect("codex");
+
+    codex
+        .start_thread(ThreadOptions::default())
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    assert_eq!(
+        values_after(&fake.recorded_args(), "--config"),
+        vec!["notify=[\"/Applications/My App.app/notify\", \"--title\", \"Codex \\\"done\\\"\"]"]
+    );
+}
+
+#[test]
+fn empty_notify_command_is_rejected() {
+    let err = Codex::new(CodexOptions {
+        notify_command: Some(Vec::new()),
+        ..CodexOptions::default()
+    })
+    .expect_err("empty notify command");
+
+    assert!(matches!(err, CodexError::InvalidArgs(message) if message.contains("notify_command")));
+}
+
+#[tokio::test]
+async fn history_and_response_storage_become_config_overrides() {
+    let fake = common::fake_codex(EVENTS);
+    let codex = Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        history: Some(HistoryPersistence::None),
+        ..CodexOptions::default()
+    })
+    .expect("codex");
+
+    codex
+        .start_thread(ThreadOptions {
+            disable_response_storage: Some(true),
+            ..ThreadOptions::default()
+        })
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    assert_eq!(
+        values_after(&fake.recorded_args(), "--config"),
+        vec![
+            "history.persistence=\"none\"",
+            "disable_response_storage=true"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn unset_history_and_response_storage_emit_no_config() {
+    let fake = common::fake_codex(EVENTS);
+
+    common::codex_for(&fake)
+        .start_thread(ThreadOptions::default())
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    assert_eq!(
+        values_after(&fake.recorded_args(), "--config"),
+        Vec::<&str>::new()
+    );
+}
+
+#[tokio::test]
+async fn turn_reasoning_effort_overrides_the_thread_value() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        model_reasoning_effort: Some(ModelReasoningEffort::Low),
+        ..ThreadOptions::default()
+    });
+
+    thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("first turn");
+    assert_eq!(
+        values_after(&fake.recorded_args(), "--config"),
+        vec!["model_reasoning_effort=\"low\""]
+    );
+
+    thread
+        .run(
+            "hello".into(),
+            TurnOptions {
+                model_reasoning_effort: Some(ModelReasoningEffort::High),
+                ..TurnOptions::default()
+            },
+        )
+        .await
+        .expect("second turn");
+    assert_eq!(
+        values_after(&fake.recorded_args(), "--config"),
+        vec!["model_reasoning_effort=\"high\""]
+    );
+}
+
+#[tokio::test]
+async fn preamble_is_prepended_to_the_prompt() {
+    let mut script = String::from("cat > \"$(dirname \"$0\")/stdin.txt\"\n");
+    for line in EVENTS {
+        script.push_str(&format!("printf '%s\\n' '{line}'\n"));
+    }
+    let fake = common::fake_codex_script(&script);
+    let image = tempfile::Builder::new()
+        .suffix(".png")
+        .tempfile()
+        .expect("image");
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        append_instructions: Some("Answer in one line.".to_string()),
+        ..ThreadOptions::default()
+    });
+
+    thread
+        .run(
+            Input::Structured(vec![
+                UserInput::Text {
+                    text: "hello".to_string(),
+                },
+                UserInput::LocalImage {
+                    path: image.path().to_string_lossy().into_owned(),
+                },
+            ]),
+            TurnOptions {
+                preamble: Some("You are reviewing the codex-sdk crate.".to_string()),
+                ..TurnOptions::default()
+            },
+        )
+        .await
+        .expect("turn");
+
+    let stdin = std::fs::read_to_string(fake.path.with_file_name("stdin.txt")).expect("stdin");
+    assert!(stdin.starts_with("You are reviewing the codex-sdk crate.\n\n"));
+    assert_eq!(
+        stdin,
+        "You are reviewing the codex-sdk crate.\n\nhello\n\nAnswer in one line."
+    );
+    assert_eq!(values_after(&fake.recorded_args(), "--image").len(), 1);
+}
@@ -0,0 +1,98 @@
+use std::fs;
+use std::path::Path;
+
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+use codex_sdk::{Codex, CodexOptions, ThreadItem, ThreadOptions};
+
+const THREAD_ID: &str = "5f0c1a52-8d7e-4b3a-9c61-2e4f7a9b0d13";
+
+fn write_rollout(home: &Path, records: &[serde_json::Value]) {
+    let dir = home.join("sessions").join("2025").join("01").join("02");
+    fs::create_dir_all(&dir).expect("session dir");
+    let contents: String = records.iter().map(|record| format!("{record}\n")).collect();
+    fs::write(
+        dir.join(format!("rollout-2025-01-02T09-00-00-{THREAD_ID}.jsonl")),
+        contents,
+    )
+    .expect("write rollout");
+}
+
+fn codex_for(home: &Path) -> Codex {
+    Codex::new(CodexOptions {
+        codex_home: Some(home.to_path_buf()),
+        ..CodexOptions::default()
+    })
+    .expect("codex")
+}
+
+#[tokio::test]
+async fn history_maps_rollout_entries_in_order() {
+    let home = tempfile::tempdir().expect("tempdir");
+    write_rollout(
+        home.path(),
+        &[
+            json!({ "type": "session_meta", "payload": { "id": THREAD_ID, "timestamp": "2025-01-02T09:00:00.000Z", "cwd": "/repo" } }),
+            json!({ "type": "response_item", "payload": { "type": "message", "role": "user", "content": [{ "type": "input_text", "text": "Fix the test" }] } }),
+            json!({ "type": "event_msg", "payload": { "type": "user_message", "message": "Fix the test" } }),
+            json!({ "type": "response_item", "payload": { "type": "reasoning", "summary": [{ "type": "summary_text", "text": "Look at the failure" }] } }),
+            json!({ "type": "response_item", "payload": { "type": "function_call", "name": "shell", "call_id": "call-1", "arguments": "{\"command\":[\"bash\",\"-lc\",\"cargo test\"]}" } }),
+            json!({ "type": "response_item", "payload": { "type": "function_call_output", "call_id": "call-1", "output": "{\"output\":\"1 failed\",\"metadata\":{\"exit_code\":101}}" } }),
+            json!({ "type": "response_item", "payload": { "type": "custom_tool_call", "name": "apply_patch", "call_id": "call-2", "input": "*** Begin Patch" } }),
+            json!({ "type": "response_item", "payload": { "type": "message", "role": "assistant", "content": [{ "type": "output_text", "text": "Fixed it" }] } }),
+        ],
+    );
+    let thread = codex_for(home.path()).resume_thread(THREAD_ID, ThreadOptions::default());
+
+    let history = thread.history().await.expect("history");
+
+    assert_eq!(
+        history,
+        vec![
+            ThreadItem::Reasoning {
+                id: "item_0".to_string(),
+                text: "Look at the failure".to_string(),
+            },
+            ThreadItem::CommandExecution {
+                id: "item_1".to_string(),
+                command: "cargo test".to_string(),
+                aggregated_output: "1 failed".to_string(),
+                stdout: None,
+                stderr: None,
+                exit_code: Some(101),
+                status: codex_sdk::items::CommandExecutionStatus::Failed,
+                started_at: None,
+                duration_ms: None,
+                cwd: None,
+            },
+            ThreadItem::Other,
+            ThreadItem::AgentMessage {
+                id: "item_3".to_string(),
+                text: "Fixed it".to_string(),
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn fresh_thread_has_empty_history() {
+    let home = tempfile::tempdir().expect("tempdir");
+    let codex = codex_for(home.path());
+
+    let started = codex.start_thread(ThreadOptions::default());
+    assert_eq!(started.history().await.expect("history"), Vec::new());
+
+    let not_persisted = codex.resume_thread(THREAD_ID, ThreadOptions::default());
+    assert_eq!(not_persisted.history().await.expect("history"), Vec::new());
+}
+
+#[test]
+fn unknown_item_types_deserialize_as_other() {
+    let item: ThreadItem =
+        serde_json::from_value(json!({ "type": "image_generation", "id": "item-9" }))
+            .expect("item");
+
+    assert_eq!(item, ThreadItem::Other);
+    assert_eq!(item.to_string(), "other");
+}
@@ -0,0 +1,80 @@
+#![cfg(unix)]
+
+mod common;
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{CodexError, ThreadEvent, ThreadId, ThreadOptions, TurnOptions};
+
+#[test]
+fn uuid_thread_ids_parse() {
+    let id: ThreadId = common::THREAD_ID.parse().expect("parse");
+
+    assert_eq!(id.as_str(), common::THREAD_ID);
+    assert_eq!(id.to_string(), common::THREAD_ID);
+}
+
+#[test]
+fn malformed_thread_ids_are_rejected() {
+    for value in [
+        "",
+        "thread-1",
+        "5f0c1a52-8d7e-4b3a-9c61",
+        "5f0c1a52-8d7e-4b3a-9c61-2e4f7a9b0d1z",
+        "5f0c1a528d7e4b3a9c612e4f7a9b0d13",
+    ] {
+        let result = value.parse::<ThreadId>();
+        assert!(
+            matches!(result, Err(CodexError::InvalidThreadId(_))),
+            "{value:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn thread_started_wire_format_is_unchanged() {
+    let line = format!(
+        r#"{{"type":"thread.started","thread_id":"{}"}}"#,
+        common::THREAD_ID
+    );
+
+    let event: ThreadEvent = serde_json::from_str(&line).expect("event");
+    assert_eq!(
+        event,
+        ThreadEvent::ThreadStarted {
+            thread_id: common::THREAD_ID.into(),
+        }
+    );
+    assert_eq!(serde_json::to_string(&event).expect("serialize"), line);
+}
+
+#[tokio::test]
+async fn resumed_thread_emits_resume_flag() {
+    let fake = common::fake_codex(&[
+        r#"{"type":"item.completed","item":{"type":"agent_message","id":"item-0","text":"done"}}"#,
+        r#"{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":1}}"#,
+    ]);
+    let thread =
+        common::codex_for(&fake).resume_thread(common::THREAD_ID, ThreadOptions::default());
+
+    thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    let args = fake.recorded_args();
+    assert!(args
+        .windows(2)
+        .any(|pair| pair == ["resume", common::THREAD_ID]));
+}
+
+#[tokio::test]
+async fn invalid_resume_id_fails_before_spawning() {
+    let fake = common::fake_codex(&[]);
+    let thread =
+        common::codex_for(&fake).resume_thread("sesion-typo".to_string(), ThreadOptions::default());
+
+    let result = thread.run("hello".into(), TurnOptions::default()).await;
+
+    assert!(matches!(result, Err(CodexError::InvalidThreadId(_))));
+}
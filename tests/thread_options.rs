@@ -0,0 +1,103 @@
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+use codex_sdk::{ApprovalMode, ModelReasoningEffort, ModelVerbosity, ReasoningSummaryMode};
+
+#[test]
+fn standard_reasoning_efforts_round_trip() {
+    for name in ["minimal", "low", "medium", "high", "xhigh"] {
+        let effort: ModelReasoningEffort = name.parse().expect("parse");
+        assert_eq!(effort.as_str(), name);
+        assert_eq!(effort.is_standard(), true);
+    }
+}
+
+#[test]
+fn unknown_reasoning_effort_falls_back_to_custom() {
+    let effort: ModelReasoningEffort = "ultra".parse().expect("parse");
+
+    assert!(matches!(&effort, ModelReasoningEffort::Custom(value) if value == "ultra"));
+    assert_eq!(effort.as_str(), "ultra");
+    assert_eq!(effort.to_string(), "ultra");
+    assert_eq!(effort.is_standard(), false);
+}
+
+#[test]
+fn known_approval_modes_round_trip() {
+    for name in ["never", "on-request", "on-failure", "untrusted"] {
+        let mode: ApprovalMode = name.parse().expect("parse");
+        assert_eq!(mode.as_str(), name);
+        assert_eq!(mode.is_known(), true);
+    }
+}
+
+#[test]
+fn unknown_approval_mode_deserializes_as_custom() {
+    let config = json!({ "approval_policy": "ask-twice" });
+
+    let mode: ApprovalMode =
+        serde_json::from_value(config["approval_policy"].clone()).expect("deserialize");
+
+    assert!(matches!(&mode, ApprovalMode::Custom(value) if value == "ask-twice"));
+    assert_eq!(mode.to_string(), "ask-twice");
+    assert_eq!(mode.is_known(), false);
+}
+
+#[test]
+fn model_verbosity_round_trips() {
+    for name in ["low", "medium", "high"] {
+        let verbosity: ModelVerbosity = name.parse().expect("parse");
+        assert_eq!(verbosity.as_str(), name);
+        assert_eq!(verbosity.to_string(), name);
+        assert_eq!(
+            serde_json::to_value(&verbosity).expect("serialize"),
+            json!(name)
+        );
+        assert_eq!(
+            serde_json::from_value::<ModelVerbosity>(json!(name)).expect("deserialize"),
+            verbosity
+        );
+        assert_eq!(verbosity.is_standard(), true);
+    }
+}
+
+#[test]
+fn unknown_model_verbosity_falls_back_to_custom() {
+    let verbosity: ModelVerbosity = "loud".parse().expect("parse");
+
+    assert_eq!(verbosity, ModelVerbosity::Custom("loud".to_string()));
+    assert_eq!(verbosity.to_string(), "loud");
+    assert_eq!(verbosity.is_standard(), false);
+    assert_eq!(
+        serde_json::from_value::<ModelVerbosity>(json!("loud")).expect("deserialize"),
+        verbosity
+    );
+}
+
+#[test]
+fn reasoning_summary_mode_round_trips() {
+    for name in ["auto", "concise", "detailed", "none"] {
+        let mode: ReasoningSummaryMode = name.parse().expect("parse");
+        assert_eq!(mode.as_str(), name);
+        assert_eq!(mode.to_string(), name);
+        assert_eq!(serde_json::to_value(&mode).expect("serialize"), json!(name));
+        assert_eq!(
+            serde_json::from_value::<ReasoningSummaryMode>(json!(name)).expect("deserialize"),
+            mode
+        );
+        assert_eq!(mode.is_standard(), true);
+    }
+}
+
+#[test]
+fn unknown_reasoning_summary_mode_falls_back_to_custom() {
+    let mode: ReasoningSummaryMode = "verbose".parse().expect("parse");
+
+    assert_eq!(mode, ReasoningSummaryMode::Custom("verbose".to_string()));
+    assert_eq!(mode.to_string(), "verbose");
+    assert_eq!(mode.is_standard(), false);
+    assert_eq!(
+        serde_json::from_value::<ReasoningSummaryMode>(json!("verbose")).expect("deserialize"),
+        mode
+    );
+}
@@ -0,0 +1,66 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use codex_sdk::{Codex, CodexOptions, ShellEnvironmentPolicy, ThreadOptions, TurnOptions};
+
+static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct CaptureLogger;
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        RECORDS
+            .lock()
+            .expect("records")
+            .push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+#[tokio::test]
+async fn thread_options_are_logged_without_secrets() {
+    log::set_logger(&CaptureLogger).expect("logger");
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let fake = common::fake_codex(&[
+        r#"{"type":"item.completed","item":{"type":"agent_message","id":"item-0","text":"done"}}"#,
+    ]);
+    let codex = Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        ..CodexOptions::default()
+    })
+    .expect("codex");
+    codex
+        .start_thread(ThreadOptions {
+            shell_environment_policy: Some(ShellEnvironmentPolicy {
+                set: Some(HashMap::from([(
+                    "GITHUB_TOKEN".to_string(),
+                    "ghp-very-secret".to_string(),
+                )])),
+                ..ShellEnvironmentPolicy::default()
+            }),
+            base_instructions: Some("confidential base instructions".to_string()),
+            ..ThreadOptions::default()
+        })
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    let records = RECORDS.lock().expect("records");
+    let options = records
+        .iter()
+        .find(|message| message.starts_with("Thread options:"))
+        .expect("thread options log");
+    assert!(options.contains("GITHUB_TOKEN"));
+    assert!(!options.contains("ghp-very-secret"));
+    assert!(!options.contains("confidential base instructions"));
+}
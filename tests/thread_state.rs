@@ -0,0 +1,183 @@
+#![cfg(unix)]
+
+mod common;
+
+use futures::StreamExt;
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{CodexError, SandboxMode, ThreadOptions, TurnOptions};
+
+const EVENTS: &[&str] = &[
+    r#"{"type":"thread.started","thread_id":"thread-1"}"#,
+    r#"{"type":"turn.started"}"#,
+    r#"{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":1}}"#,
+];
+
+#[tokio::test]
+async fn is_running_tracks_the_stream_lifetime() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+    assert_eq!(thread.is_running(), false);
+
+    let mut events = thread
+        .run_streamed("hello".into(), TurnOptions::default())
+        .expect("streamed")
+        .events;
+    assert_eq!(thread.is_running(), true);
+
+    events.next().await.expect("event").expect("ok");
+    assert_eq!(thread.is_running(), true);
+
+    while let Some(event) = events.next().await {
+        event.expect("ok");
+    }
+    assert_eq!(thread.is_running(), false);
+}
+
+#[tokio::test]
+async fn is_running_clears_after_an_error() {
+    let fake = common::fake_codex(&["not json"]);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let result = thread.run("hello".into(), TurnOptions::default()).await;
+    assert!(result.is_err());
+    assert_eq!(thread.is_running(), false);
+}
+
+#[tokio::test]
+async fn is_running_clears_when_the_stream_is_dropped() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let streamed = thread
+        .run_streamed("hello".into(), TurnOptions::default())
+        .expect("streamed");
+    assert_eq!(thread.is_running(), true);
+
+    drop(streamed);
+    assert_eq!(thread.is_running(), false);
+}
+
+#[tokio::test]
+async fn concurrent_runs_on_the_same_thread_fail_fast() {
+    let mut script = String::from("cat > /dev/null\nsleep 1\n");
+    for line in EVENTS {
+        script.push_str(&format!("printf '%s\\n' '{line}'\n"));
+    }
+    let fake = common::fake_codex_script(&script);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        require_response: false,
+        ..ThreadOptions::default()
+    });
+
+    let first = tokio::spawn({
+        let thread = thread.clone();
+        async move { thread.run("first".into(), TurnOptions::default()).await }
+    });
+    let second = tokio::spawn({
+        let thread = thread.clone();
+        async move { thread.run("second".into(), TurnOptions::default()).await }
+    });
+
+    let results = [
+        first.await.expect("first task"),
+        second.await.expect("second task"),
+    ];
+    let rejected = results
+        .iter()
+        .filter(|result| matches!(result, Err(CodexError::ConcurrentTurnNotAllowed)))
+        .count();
+    assert_eq!(rejected, 1);
+    assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+    assert_eq!(thread.is_running(), false);
+}
+
+#[test]
+fn thread_exposes_its_options() {
+    let fake = common::fake_codex(EVENTS);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        model: Some("gpt-5-codex".to_string()),
+        ..ThreadOptions::default()
+    });
+
+    assert_eq!(
+        thread.thread_options().model.as_deref(),
+        Some("gpt-5-codex")
+    );
+    assert_eq!(
+        thread.codex_options().codex_path_override.as_ref(),
+        Some(&fake.path)
+    );
+}
+
+#[tokio::test]
+async fn turn_history_keeps_the_most_recent_turns() {
+    let fake = common::fake_codex_script(concat!(
+        "cat > /dev/null\n",
+        "count=\"$(dirname \"$0\")/count\"\n",
+        "n=$(( $(cat \"$count\" 2>/dev/null || echo 0) + 1 ))\n",
+        "echo $n > \"$count\"\n",
+        "printf '{\"type\":\"item.completed\",\"item\":{\"type\":\"agent_message\",\"id\":\"item-0\",\"text\":\"turn %s\"}}\\n' $n\n",
+        "printf '{\"type\":\"turn.completed\",\"usage\":{\"input_tokens\":%s,\"cached_input_tokens\":0,\"output_tokens\":1}}\\n' $n\n",
+    ));
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions {
+        max_history_turns: Some(2),
+        ..ThreadOptions::default()
+    });
+    assert_eq!(thread.turn_history(), Vec::new());
+
+    for _ in 0..3 {
+        thread
+            .run("hello".into(), TurnOptions::default())
+            .await
+            .expect("turn");
+    }
+
+    let history = thread.turn_history();
+    let responses: Vec<&str> = history
+        .iter()
+        .map(|turn| turn.final_response.as_str())
+        .collect();
+    assert_eq!(responses, vec!["turn 2", "turn 3"]);
+    assert_eq!(
+        history
+            .iter()
+            .map(|turn| turn.usage.as_ref().map(|usage| usage.input_tokens))
+            .collect::<Vec<_>>(),
+        vec![Some(2), Some(3)]
+    );
+
+    thread.clear_turn_history();
+    assert_eq!(thread.turn_history(), Vec::new());
+}
+
+#[tokio::test]
+async fn display_summarizes_the_thread() {
+    let fake = common::fake_codex(EVENTS);
+    let codex = common::codex_for(&fake);
+    let thread = codex.start_thread(ThreadOptions {
+        model: Some("gpt-5-codex".to_string()),
+        sandbox_mode: Some(SandboxMode::WorkspaceWrite),
+        ..ThreadOptions::default()
+    });
+    assert_eq!(
+        thread.to_string(),
+        "Thread(id=unstarted, model=gpt-5-codex, sandbox=workspace-write)"
+    );
+
+    thread
+        .run_streamed("hello".into(), TurnOptions::default())
+        .expect("streamed")
+        .collect()
+        .await
+        .expect("events");
+
+    assert_eq!(
+        thread.to_string(),
+        "Thread(id=thread-1, model=gpt-5-codex, sandbox=workspace-write)"
+    );
+    assert_eq!(
+        codex.start_thread(ThreadOptions::default()).to_string(),
+        "Thread(id=unstarted, model=default, sandbox=default)"
+    );
+}
@@ -0,0 +1,102 @@
+#![cfg(all(unix, feature = "tracing"))]
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use pretty_assertions::assert_eq;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+use codex_sdk::{Codex, CodexOptions, ThreadOptions, TurnOptions};
+
+#[derive(Clone, Default)]
+struct Captured {
+    span_fields: Arc<Mutex<HashMap<String, String>>>,
+    events_in_turn: Arc<Mutex<Vec<String>>>,
+}
+
+struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+impl<S> Layer<S> for Captured
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        if attrs.metadata().name() == "turn" {
+            attrs.record(&mut FieldVisitor(&mut self.span_fields.lock().unwrap()));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if ctx.span(id).map(|span| span.name()) == Some("turn") {
+            values.record(&mut FieldVisitor(&mut self.span_fields.lock().unwrap()));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if ctx.event_span(event).map(|span| span.name()) == Some("turn") {
+            let mut fields = HashMap::new();
+            event.record(&mut FieldVisitor(&mut fields));
+            if let Some(name) = fields.remove("event") {
+                self.events_in_turn.lock().unwrap().push(name);
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn turn_span_records_thread_id_and_events() {
+    let captured = Captured::default();
+    let subscriber = tracing_subscriber::registry().with(captured.clone());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let fake = common::fake_codex(&[
+        r#"{"type":"thread.started","thread_id":"thread-42"}"#,
+        r#"{"type":"turn.started"}"#,
+        r#"{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":1}}"#,
+    ]);
+    let codex = Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        ..CodexOptions::default()
+    })
+    .expect("codex");
+    let thread = codex.start_thread(ThreadOptions {
+        require_response: false,
+        ..ThreadOptions::default()
+    });
+
+    thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    assert_eq!(
+        captured
+            .span_fields
+            .lock()
+            .unwrap()
+            .get("thread_id")
+            .cloned(),
+        Some("thread-42".to_string())
+    );
+    assert_eq!(
+        *captured.events_in_turn.lock().unwrap(),
+        vec!["thread.started", "turn.started", "turn.completed"]
+    );
+}
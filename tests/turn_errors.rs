@@ -0,0 +1,207 @@
+use pretty_assertions::assert_eq;
+
+use codex_sdk::items::{CommandExecutionStatus, PatchApplyStatus, PatchChangeKind};
+use codex_sdk::{FileUpdateChange, ThreadItem, Turn};
+
+fn turn(items: Vec<ThreadItem>) -> Turn {
+    Turn {
+        items,
+        final_response: String::new(),
+        usage: None,
+        skipped_lines: 0,
+        pricing: None,
+        rate_limits: None,
+        auto_compacted: false,
+    }
+}
+
+fn command(id: &str, exit_code: Option<i32>) -> ThreadItem {
+    timed_command(id, exit_code, None)
+}
+
+fn timed_command(id: &str, exit_code: Option<i32>, duration_ms: Option<u64>) -> ThreadItem {
+    ThreadItem::CommandExecution {
+        id: id.to_string(),
+        command: "cargo test".to_string(),
+        aggregated_output: String::new(),
+        stdout: None,
+        stderr: None,
+        exit_code,
+        status: match exit_code {
+            Some(0) => CommandExecutionStatus::Completed,
+            Some(_) => CommandExecutionStatus::Failed,
+            None => CommandExecutionStatus::InProgress,
+        },
+        started_at: None,
+        duration_ms,
+        cwd: None,
+    }
+}
+
+fn message(id: &str) -> ThreadItem {
+    ThreadItem::AgentMessage {
+        id: id.to_string(),
+        text: "done".to_string(),
+    }
+}
+
+#[test]
+fn turn_without_error_items_has_no_errors() {
+    let turn = turn(vec![
+        message("m1"),
+        command("c1", Some(0)),
+        command("c2", None),
+    ]);
+
+    assert_eq!(turn.first_error(), None);
+    assert!(!turn.has_errors());
+}
+
+#[test]
+fn failed_command_is_reported() {
+    let turn = turn(vec![
+        command("c1", Some(0)),
+        command("c2", Some(101)),
+        message("m1"),
+    ]);
+
+    assert_eq!(turn.first_error(), Some(&command("c2", Some(101))));
+    assert!(turn.has_errors());
+}
+
+#[test]
+fn first_error_item_wins() {
+    let error = ThreadItem::Error {
+        id: "e1".to_string(),
+        message: "sandbox denied write".to_string(),
+        will_retry: None,
+        attempt: None,
+    };
+    let turn = turn(vec![message("m1"), error.clone(), command("c1", Some(1))]);
+
+    assert_eq!(turn.first_error(), Some(&error));
+    assert!(turn.has_errors());
+}
+
+fn ids(items: Vec<&ThreadItem>) -> Vec<String> {
+    items
+        .into_iter()
+        .map(|item| item.id().to_string())
+        .collect()
+}
+
+#[test]
+fn slowest_commands_are_ordered_by_duration() {
+    let turn = turn(vec![
+        timed_command("fast", Some(0), Some(12)),
+        message("m"),
+        timed_command("untimed", Some(0), None),
+        timed_command("slow", Some(1), Some(4_500)),
+        timed_command("medium", Some(0), Some(300)),
+    ]);
+
+    assert_eq!(ids(turn.slowest_commands(2)), vec!["slow", "medium"]);
+    assert_eq!(
+        ids(turn.slowest_commands(10)),
+        vec!["slow", "medium", "fast"]
+    );
+    assert!(turn.slowest_commands(0).is_empty());
+}
+
+fn file_change(id: &str, changes: &[(&str, Option<&str>)]) -> ThreadItem {
+    ThreadItem::FileChange {
+        id: id.to_string(),
+        changes: changes
+            .iter()
+            .map(|(path, diff)| FileUpdateChange {
+                path: path.to_string(),
+                kind: PatchChangeKind::Update,
+                diff: diff.map(str::to_string),
+            })
+            .collect(),
+        status: PatchApplyStatus::Completed,
+    }
+}
+
+#[test]
+fn combined_diff_orders_files_by_path() {
+    let without_diffs = turn(vec![file_change("f", &[("a.rs", None)])]);
+    let turn = turn(vec![
+        file_change(
+            "f1",
+            &[
+                (
+                    "src/main.rs",
+                    Some("--- a/src/main.rs\n+++ b/src/main.rs\n"),
+                ),
+                ("README.md", None),
+            ],
+        ),
+        message("m"),
+        file_change(
+            "f2",
+            &[
+                ("src/lib.rs", Some("--- a/src/lib.rs\n+++ b/src/lib.rs")),
+                ("src/main.rs", Some("@@ -2 +2 @@\n")),
+            ],
+        ),
+    ]);
+
+    assert_eq!(
+        turn.combined_diff().as_deref(),
+        Some(
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -2 +2 @@\n"
+        )
+    );
+    assert_eq!(without_diffs.combined_diff(), None);
+}
+
+#[test]
+fn combined_diff_merges_repeated_changes_to_a_path() {
+    let first = "--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1 +1 @@\n-a\n+b\n";
+    let second = "--- a/src/main.rs\n+++ b/src/main.rs\n@@ -9 +9 @@\n-c\n+d\n";
+    let turn = turn(vec![
+        file_change("f1", &[("src/main.rs", Some(first))]),
+        file_change("f2", &[("src/main.rs", Some(second))]),
+        file_change("f3", &[("src/main.rs", Some(first))]),
+    ]);
+
+    assert_eq!(
+        turn.combined_diff().as_deref(),
+        Some("--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1 +1 @@\n-a\n+b\n@@ -9 +9 @@\n-c\n+d\n")
+    );
+}
+
+#[test]
+fn citations_collect_unique_urls_in_order() {
+    let items: Vec<ThreadItem> = serde_json::from_value(serde_json::json!([
+        {
+            "type": "web_search",
+            "id": "w1",
+            "query": "tokio select",
+            "result": { "urls": ["https://docs.rs/tokio", "https://tokio.rs"], "snippet": null },
+        },
+        { "type": "agent_message", "id": "m", "text": "done" },
+        {
+            "type": "web_search",
+            "id": "w2",
+            "query": "tokio spawn",
+            "results": [
+                { "url": "https://tokio.rs", "title": "Tokio" },
+                { "title": "no link" },
+                { "url": "https://docs.rs/tokio/latest/tokio/fn.spawn.html" },
+            ],
+        },
+        { "type": "web_search", "id": "w3", "query": "no results" },
+    ]))
+    .expect("items");
+
+    assert_eq!(
+        turn(items).citations(),
+        vec![
+            "https://docs.rs/tokio",
+            "https://tokio.rs",
+            "https://docs.rs/tokio/latest/tokio/fn.spawn.html",
+        ]
+    );
+}
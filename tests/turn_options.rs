@@ -0,0 +1,34 @@
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{CodexError, TurnOptions};
+
+#[test]
+fn preamble_is_loaded_from_a_file() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir.path().join("preamble.md");
+    std::fs::write(&path, "You are reviewing the codex-sdk crate.\n").expect("write preamble");
+
+    let options = TurnOptions {
+        profile: Some("ci".to_string()),
+        ..TurnOptions::default()
+    }
+    .with_preamble_from_file(&path)
+    .expect("preamble");
+
+    assert_eq!(
+        options.preamble.as_deref(),
+        Some("You are reviewing the codex-sdk crate.\n")
+    );
+    assert_eq!(options.profile.as_deref(), Some("ci"));
+}
+
+#[test]
+fn unreadable_preamble_file_is_an_io_error() {
+    let dir = tempfile::tempdir().expect("temp dir");
+
+    let err = TurnOptions::default()
+        .with_preamble_from_file(dir.path().join("missing.md"))
+        .expect_err("missing file");
+
+    assert!(matches!(err, CodexError::Io(ref io) if io.kind() == std::io::ErrorKind::NotFound));
+}
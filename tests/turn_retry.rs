@@ -0,0 +1,112 @@
+#![cfg(unix)]
+
+mod common;
+
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{CodexError, ThreadError, ThreadEvent, ThreadItem, ThreadOptions, TurnOptions};
+
+const TURN_RETRY: &str = include_str!("fixtures/turn_retry.jsonl");
+
+fn fixture_lines() -> Vec<&'static str> {
+    TURN_RETRY.lines().collect()
+}
+
+#[test]
+fn retry_metadata_is_deserialized() {
+    let events: Vec<ThreadEvent> = fixture_lines()
+        .into_iter()
+        .map(|line| serde_json::from_str(line).expect("event"))
+        .collect();
+
+    assert!(matches!(
+        &events[2],
+        ThreadEvent::ItemCompleted {
+            item: ThreadItem::Error {
+                will_retry: Some(true),
+                attempt: Some(1),
+                ..
+            }
+        }
+    ));
+    assert_eq!(
+        events[3],
+        ThreadEvent::TurnFailed {
+            error: ThreadError {
+                message: "stream disconnected before completion".to_string(),
+                will_retry: Some(true),
+                attempt: Some(1),
+            },
+        }
+    );
+    assert!(!events[3].is_terminal());
+
+    let legacy: ThreadError = serde_json::from_str(r#"{"message":"boom"}"#).expect("legacy error");
+    assert_eq!(legacy.will_retry, None);
+    assert_eq!(legacy.attempt, None);
+    assert!(!legacy.is_retrying());
+}
+
+#[tokio::test]
+async fn retried_turn_succeeds() {
+    let fake = common::fake_codex(&fixture_lines());
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let turn = thread
+        .run("hello".into(), TurnOptions::default())
+        .await
+        .expect("turn");
+
+    assert_eq!(turn.final_response, "Recovered.");
+    assert_eq!(turn.items.len(), 2);
+    assert!(turn.usage.is_some());
+}
+
+#[tokio::test]
+async fn retry_that_never_restarts_fails_the_turn() {
+    let lines = &fixture_lines()[..4];
+    let fake = common::fake_codex(lines);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let result = thread.run("hello".into(), TurnOptions::default()).await;
+
+    assert!(matches!(
+        result,
+        Err(CodexError::TurnFailed(message)) if message == "stream disconnected before completion"
+    ));
+}
+
+#[tokio::test]
+async fn final_failure_still_ends_the_turn() {
+    let fake = common::fake_codex(&[
+        r#"{"type":"turn.started"}"#,
+        r#"{"type":"turn.failed","error":{"message":"quota exceeded","will_retry":false,"attempt":5}}"#,
+        r#"{"type":"turn.started"}"#,
+        r#"{"type":"item.completed","item":{"type":"agent_message","id":"item_1","text":"late"}}"#,
+    ]);
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let result = thread.run("hello".into(), TurnOptions::default()).await;
+
+    assert!(matches!(
+        result,
+        Err(CodexError::TurnFailed(message)) if message == "quota exceeded"
+    ));
+}
+
+#[tokio::test]
+async fn timestamped_stream_skips_retried_failures() {
+    use futures::StreamExt;
+
+    let fake = common::fake_codex(&fixture_lines());
+    let thread = common::codex_for(&fake).start_thread(ThreadOptions::default());
+
+    let items: Vec<_> = thread
+        .run_streamed_timestamped("hello".into(), TurnOptions::default())
+        .expect("streamed")
+        .collect()
+        .await;
+
+    assert_eq!(items.len(), 2);
+    assert!(items.iter().all(Result::is_ok));
+}
@@ -0,0 +1,101 @@
+use pretty_assertions::assert_eq;
+
+use codex_sdk::{Cost, CostModel, ModelPricing, Usage, UsageAccumulator};
+
+#[test]
+fn accumulates_usage_and_prices_it_per_million_tokens() {
+    let mut accumulator = UsageAccumulator::new(CostModel {
+        input: 1.25,
+        output: 10.0,
+    });
+
+    accumulator.add(&Usage {
+        input_tokens: 600_000,
+        cached_input_tokens: 100_000,
+        output_tokens: 50_000,
+    });
+    accumulator.add(&Usage {
+        input_tokens: 400_000,
+        cached_input_tokens: 0,
+        output_tokens: 150_000,
+    });
+
+    assert_eq!(
+        accumulator.total,
+        Usage {
+            input_tokens: 1_000_000,
+            cached_input_tokens: 100_000,
+            output_tokens: 200_000,
+        }
+    );
+    assert!((accumulator.total_cost() - 3.25).abs() < 1e-9);
+    assert!(accumulator.exceeds_budget(3.0));
+    assert!(!accumulator.exceeds_budget(3.25));
+}
+
+#[test]
+fn empty_accumulator_costs_nothing() {
+    let accumulator = UsageAccumulator::new(CostModel {
+        input: 1.25,
+        output: 10.0,
+    });
+
+    assert_eq!(accumulator.total_cost(), 0.0);
+    assert!(!accumulator.exceeds_budget(0.0));
+}
+
+#[test]
+fn cached_input_is_billed_at_the_cached_rate() {
+    let usage = Usage {
+        input_tokens: 2_000_000,
+        cached_input_tokens: 1_000_000,
+        output_tokens: 500_000,
+    };
+
+    let cost = usage.estimated_cost(&ModelPricing {
+        input_per_million: 1.25,
+        cached_input_per_million: 0.125,
+        output_per_million: 10.0,
+    });
+
+    assert_eq!(
+        cost,
+        Cost {
+            input: 1.25,
+            cached_input: 0.125,
+            output: 5.0,
+        }
+    );
+    assert_eq!(cost.total(), 6.375);
+    assert_eq!(cost.to_string(), "$6.3750");
+}
+
+#[test]
+fn known_models_have_pricing_and_unknown_models_do_not() {
+    assert_eq!(
+        ModelPricing::for_model("gpt-5-codex"),
+        Some(ModelPricing::new(1.25, 0.125, 10.0))
+    );
+    assert_eq!(ModelPricing::for_model("gpt-5-codex-preview"), None);
+    assert_eq!(ModelPricing::for_model("llama3"), None);
+}
+
+#[test]
+fn accumulator_prices_cached_input_with_model_pricing() {
+    let pricing = ModelPricing::new(1.25, 0.125, 10.0);
+    let usage = Usage {
+        input_tokens: 2_000_000,
+        cached_input_tokens: 1_000_000,
+        output_tokens: 500_000,
+    };
+    let mut accumulator = UsageAccumulator::with_pricing(pricing);
+
+    accumulator.add(&usage);
+
+    assert_eq!(
+        accumulator.total_cost(),
+        usage.estimated_cost(&pricing).total()
+    );
+    assert_eq!(accumulator.total_cost(), 6.375);
+    assert!(accumulator.exceeds_budget(6.0));
+}
@@ -0,0 +1,80 @@
+#![cfg(unix)]
+
+mod common;
+
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+
+use codex_sdk::{Codex, CodexError, CodexOptions, ThreadOptions, Turn, TurnOptions};
+
+fn schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "summary": { "type": "string" },
+            "status": { "type": "string", "enum": ["ok", "action_required"] }
+        },
+        "required": ["summary", "status"],
+        "additionalProperties": false
+    })
+}
+
+async fn run_with_answer(answer: &str) -> Result<Turn, CodexError> {
+    let item = json!({
+        "type": "item.completed",
+        "item": { "type": "agent_message", "id": "item-1", "text": answer }
+    })
+    .to_string();
+    let fake = common::fake_codex(&[
+        r#"{"type":"thread.started","thread_id":"thread-1"}"#,
+        &item,
+        r#"{"type":"turn.completed","usage":{"input_tokens":1,"cached_input_tokens":0,"output_tokens":1}}"#,
+    ]);
+    let codex = Codex::new(CodexOptions {
+        codex_path_override: Some(fake.path.clone()),
+        ..CodexOptions::default()
+    })
+    .expect("codex");
+
+    codex
+        .start_thread(ThreadOptions::default())
+        .run(
+            "Summarize".into(),
+            TurnOptions {
+                output_schema: Some(schema()),
+                validate_output: Some(true),
+                ..TurnOptions::default()
+            },
+        )
+        .await
+}
+
+#[tokio::test]
+async fn conforming_output_passes_validation() {
+    let answer = r#"{"summary":"all good","status":"ok"}"#;
+    let turn = run_with_answer(answer).await.expect("turn");
+    assert_eq!(turn.final_response, answer);
+}
+
+#[tokio::test]
+async fn non_json_output_is_a_violation_with_raw_text() {
+    let result = run_with_answer("Sure! Here is the summary.").await;
+    assert!(
+        matches!(result, Err(CodexError::OutputSchemaViolation { raw, .. })
+        if raw == "Sure! Here is the summary.")
+    );
+}
+
+#[cfg(feature = "schema-validation")]
+#[tokio::test]
+async fn non_conforming_output_reports_schema_errors() {
+    let answer = r#"{"summary":"done","status":"maybe","extra":1}"#;
+    let result = run_with_answer(answer).await;
+    match result {
+        Err(CodexError::OutputSchemaViolation { errors, raw }) => {
+            assert_eq!(raw, answer);
+            assert_eq!(errors.len(), 2, "{errors:?}");
+        }
+        other => panic!("expected schema violation, got {other:?}"),
+    }
+}